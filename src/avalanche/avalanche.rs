@@ -0,0 +1,292 @@
+// Diffusion of a hash in numbers: random inputs, every input bit is flipped and the output difference is recorded.
+// 1. Avalanche - a flipped input bit changes half of the output bits on average.
+// 2. SAC (strict avalanche criterion, Webster and Tavares) - every output bit flips with probability 1/2
+//    for every input bit.
+// 3. BIC (bit independence criterion) - flips of different output bits are not correlated, for every input bit.
+use std::collections::HashMap;
+use digest::Digest;
+use rand::{Rng, RngCore};
+use crate::error::Error;
+use crate::fips140::fips140::fips140;
+
+// FIPS 140 tests work on a 20,000-bit sample
+pub const FIPS140_SAMPLE_BITS: usize = 20_000;
+
+#[derive(Clone, Debug)]
+pub struct AvalancheReport {
+    pub input_bits: usize,
+    pub output_bits: usize,
+    pub samples: usize,
+    // flip_probability[i][j] - share of the samples where flipping the input bit 'i' flipped the output bit 'j'
+    pub flip_probability: Vec<Vec<f64>>,
+    // hamming_distribution[d] - how many times the outputs differed in 'd' bits
+    pub hamming_distribution: Vec<usize>,
+    // Absolute correlation of the flips of two output bits when one input bit is flipped,
+    // the biggest one over all pairs of the output bits and all input bits. A pair with a bit that never
+    // or always flips has no correlation and is skipped: it's a SAC failure, seen in 'flip_probability'
+    pub max_bic_correlation: f64,
+}
+
+impl AvalancheReport {
+    // Average share of the output bits changed by one flipped input bit, 0.5 is ideal
+    pub fn avalanche_mean(&self) -> f64 {
+        let trials: usize = self.hamming_distribution.iter().sum();
+        let total_distance: usize = self.hamming_distribution.iter().enumerate().map(|(distance, count)| distance * count).sum();
+
+        total_distance as f64 / (trials * self.output_bits) as f64
+    }
+
+    // The biggest |p - 1/2| in the flip probability matrix, 0 is ideal
+    pub fn max_sac_deviation(&self) -> f64 {
+        self.flip_probability.iter().flatten().map(|probability| (probability - 0.5).abs()).fold(0.0, f64::max)
+    }
+
+    // Average |p - 1/2| in the flip probability matrix
+    pub fn mean_sac_deviation(&self) -> f64 {
+        let deviations: f64 = self.flip_probability.iter().flatten().map(|probability| (probability - 0.5).abs()).sum();
+        deviations / (self.input_bits * self.output_bits) as f64
+    }
+}
+
+// 'hash' maps an input of 'input_length' bytes to a digest, 'samples' random inputs are checked
+pub fn analyze<R: RngCore>(hash: impl Fn(&[u8]) -> Vec<u8>, input_length: usize, samples: usize, rng: &mut R) -> Result<AvalancheReport, Error> {
+    if input_length == 0 || samples == 0 {
+        return Err(Error::InvalidArgument("Input length and samples count must be positive".to_string()));
+    }
+
+    let input_bits = input_length * 8;
+    let mut input = vec![0u8; input_length];
+    rng.fill_bytes(&mut input);
+    let output_bits = hash(&input).len() * 8;
+    if output_bits == 0 {
+        return Err(Error::InvalidArgument("Hash output must not be empty".to_string()));
+    }
+
+    // Every input bit is flipped in the same random inputs
+    let inputs: Vec<Vec<u8>> = (0..samples)
+        .map(|_| {
+            rng.fill_bytes(&mut input);
+            input.clone()
+        })
+        .collect();
+    let outputs: Vec<Vec<u8>> = inputs.iter().map(|input| hash(input)).collect();
+
+    let mut flip_counts = vec![vec![0usize; output_bits]; input_bits];
+    let mut hamming_distribution = vec![0usize; output_bits + 1];
+    // Joint flips of every pair of the output bits for the current input bit, for the correlations
+    let mut pair_counts = vec![vec![0usize; output_bits]; output_bits];
+    let mut max_bic_correlation: f64 = 0.0;
+
+    for (input_bit, bit_flip_counts) in flip_counts.iter_mut().enumerate() {
+        pair_counts.iter_mut().for_each(|counts| counts.fill(0));
+
+        for (input, output) in inputs.iter().zip(outputs.iter()) {
+            let mut flipped_input = input.clone();
+            flipped_input[input_bit / 8] ^= 0x80 >> (input_bit % 8);
+            let flipped_output = hash(&flipped_input);
+
+            let flipped_bits: Vec<usize> = (0..output_bits).filter(|bit| bit_of(output, *bit) != bit_of(&flipped_output, *bit)).collect();
+
+            hamming_distribution[flipped_bits.len()] += 1;
+            for (position, bit) in flipped_bits.iter().enumerate() {
+                bit_flip_counts[*bit] += 1;
+                for other in flipped_bits[position + 1..].iter() {
+                    pair_counts[*bit][*other] += 1;
+                }
+            }
+        }
+
+        max_bic_correlation = max_bic_correlation.max(max_correlation(bit_flip_counts, &pair_counts, samples));
+    }
+
+    let flip_probability = flip_counts
+        .iter()
+        .map(|counts| counts.iter().map(|count| *count as f64 / samples as f64).collect())
+        .collect();
+
+    Ok(AvalancheReport {
+        input_bits,
+        output_bits,
+        samples,
+        flip_probability,
+        hamming_distribution,
+        max_bic_correlation,
+    })
+}
+
+// The same for any 'Digest', e.g. Sha1Realization32 or the SHA-2 realizations
+pub fn analyze_digest<D: Digest, R: RngCore>(input_length: usize, samples: usize, rng: &mut R) -> Result<AvalancheReport, Error> {
+    analyze(|input| D::digest(input).to_vec(), input_length, samples, rng)
+}
+
+// Pearson correlation of two 0/1 variables from the counts of ones and of the joint ones,
+// pairs with zero variance are skipped
+fn max_correlation(single_counts: &[usize], pair_counts: &[Vec<usize>], trials: usize) -> f64 {
+    let trials = trials as f64;
+    let mut max_correlation: f64 = 0.0;
+
+    for j in 0..single_counts.len() {
+        for k in j + 1..single_counts.len() {
+            let p_j = single_counts[j] as f64 / trials;
+            let p_k = single_counts[k] as f64 / trials;
+            let p_jk = pair_counts[j][k] as f64 / trials;
+
+            let variance = p_j * (1.0 - p_j) * p_k * (1.0 - p_k);
+            if variance > 0.0 {
+                let correlation = (p_jk - p_j * p_k) / variance.sqrt();
+                max_correlation = max_correlation.max(correlation.abs());
+            }
+        }
+    }
+
+    max_correlation
+}
+
+fn bit_of(bytes: &[u8], bit: usize) -> u8 {
+    (bytes[bit / 8] >> (7 - bit % 8)) & 1
+}
+
+// Concatenated digests of random inputs as a vector of 0/1, 'bit_count' bits long
+pub fn output_bits<R: Rng>(hash: impl Fn(&[u8]) -> Vec<u8>, input_length: usize, bit_count: usize, rng: &mut R) -> Result<Vec<u8>, Error> {
+    let mut bits = Vec::with_capacity(bit_count);
+    let mut input = vec![0u8; input_length];
+
+    while bits.len() < bit_count {
+        rng.fill_bytes(&mut input);
+        let output = hash(&input);
+        // An empty output would never fill the vector
+        if output.is_empty() {
+            return Err(Error::InvalidArgument("Hash output must not be empty".to_string()));
+        }
+        bits.extend((0..output.len() * 8).map(|bit| bit_of(&output, bit)));
+    }
+    bits.truncate(bit_count);
+
+    Ok(bits)
+}
+
+// Runs the 'fips140' checks (monobit, series, the longest series, poker) on 20,000 output bits,
+// the error names the failed check. The limits are from FIPS 140-1, the series intervals are doubled
+// because 'fips140' counts the series of zeros and ones together. A series of 34 bits fails.
+pub fn fips140_check_outputs<R: Rng>(hash: impl Fn(&[u8]) -> Vec<u8>, input_length: usize, rng: &mut R) -> Result<(), Error> {
+    let bits = output_bits(hash, input_length, FIPS140_SAMPLE_BITS, rng)?;
+
+    let max_series_count: HashMap<u16, [u16; 2]> = HashMap::from([
+        (1, [4534, 5466]),
+        (2, [2158, 2842]),
+        (3, [1004, 1496]),
+        (4, [446, 804]),
+        (5, [180, 446]),
+        (6, [180, 446]),
+    ]);
+
+    fips140(&bits, [9654, 10346], max_series_count, 33, [1.03, 57.4])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use crate::sha1::reduced::ReducedSha1;
+    use crate::sha1::sha1::Sha1Realization32;
+
+    fn sha1(input: &[u8]) -> Vec<u8> {
+        Sha1Realization32::new().update_bytes(input).finalize().digest().to_vec()
+    }
+
+    #[test]
+    fn test_sha1_avalanche() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let report = analyze(sha1, 8, 200, &mut rng).unwrap();
+
+        assert_eq!((report.input_bits, report.output_bits, report.samples), (64, 160, 200));
+        assert_eq!(report.hamming_distribution.iter().sum::<usize>(), 64 * 200);
+        assert!((report.avalanche_mean() - 0.5).abs() < 0.01, "{}", report.avalanche_mean());
+
+        // With 200 samples the standard error of every probability is about 0.035
+        assert!(report.max_sac_deviation() < 0.2, "{}", report.max_sac_deviation());
+        assert!(report.mean_sac_deviation() < 0.05, "{}", report.mean_sac_deviation());
+        // Every correlation comes from 200 trials (standard error about 0.07), the biggest of
+        // 64 * 12720 pairs is about 5 errors away for a good hash
+        assert!(report.max_bic_correlation < 0.45, "{}", report.max_bic_correlation);
+
+        // Distances concentrate around 80 of 160 bits
+        let near_half: usize = report.hamming_distribution[60..=100].iter().sum();
+        assert!(near_half as f64 > 0.99 * (64 * 200) as f64);
+
+        // The same through the 'Digest' traits
+        let report = analyze_digest::<Sha1Realization32, _>(8, 20, &mut rng).unwrap();
+        assert!((report.avalanche_mean() - 0.5).abs() < 0.02);
+    }
+
+    #[test]
+    fn test_reduced_sha1_avalanche() {
+        // After 8 steps the input words haven't reached all the state: some output bits never flip
+        let mut rng = StdRng::seed_from_u64(2);
+        let reduced = |steps: usize| move |input: &[u8]| ReducedSha1::new(steps).unwrap().update_bytes(input).finalize().to_vec();
+        let report = analyze(reduced(8), 8, 50, &mut rng).unwrap();
+
+        assert_eq!(report.max_sac_deviation(), 0.5);
+        assert!(report.avalanche_mean() < 0.35, "{}", report.avalanche_mean());
+        // Bits that never flip are skipped, the correlation comes from bits that flip together
+        assert!(report.max_bic_correlation > 0.99, "{}", report.max_bic_correlation);
+
+        // 24 steps already look like the full hash on these inputs
+        let report = analyze(reduced(24), 8, 50, &mut rng).unwrap();
+        assert!((report.avalanche_mean() - 0.5).abs() < 0.01, "{}", report.avalanche_mean());
+        assert!(report.mean_sac_deviation() < 0.07, "{}", report.mean_sac_deviation());
+
+        // Identity isn't a hash at all: a flip changes exactly one bit
+        let report = analyze(|input: &[u8]| input.to_vec(), 4, 10, &mut rng).unwrap();
+        assert_eq!(report.hamming_distribution[1], 32 * 10);
+        assert_eq!(report.flip_probability[5][5], 1.0);
+        assert_eq!(report.max_sac_deviation(), 0.5);
+        // Every bit always or never flips: no pair has a defined correlation
+        assert_eq!(report.max_bic_correlation, 0.0);
+
+        assert!(analyze(sha1, 0, 10, &mut rng).is_err());
+        assert!(analyze(sha1, 8, 0, &mut rng).is_err());
+        assert!(analyze(|_: &[u8]| Vec::new(), 8, 10, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_bic_per_input_bit() {
+        // Flips of the output bits 0 and 1 are the same for the second half of the input bits and opposite for
+        // the first half: pooled over all input bits the correlation cancels out, for one input bit it's 1
+        let entangled = |input: &[u8]| {
+            let mut output = sha1(input);
+            let parity = input[..input.len() / 2].iter().fold(0u8, |parity, byte| parity ^ byte).count_ones() as u8 & 1;
+            let bit = (output[0] >> 7) ^ parity;
+            output[0] = (output[0] & 0xbf) | (bit << 6);
+            output
+        };
+
+        let mut rng = StdRng::seed_from_u64(5);
+        let report = analyze(entangled, 8, 50, &mut rng).unwrap();
+        assert!(report.max_bic_correlation > 0.999, "{}", report.max_bic_correlation);
+        assert!((report.avalanche_mean() - 0.5).abs() < 0.01, "{}", report.avalanche_mean());
+    }
+
+    #[test]
+    fn test_sha1_outputs_fips140() {
+        let mut rng = StdRng::seed_from_u64(3);
+
+        let bits = output_bits(sha1, 16, 1000, &mut rng).unwrap();
+        assert_eq!(bits.len(), 1000);
+        assert!(bits.iter().all(|bit| *bit <= 1));
+
+        assert_eq!(fips140_check_outputs(sha1, 16, &mut rng), Ok(()));
+
+        // An empty output is an error, not an endless loop
+        assert!(matches!(output_bits(|_: &[u8]| Vec::new(), 16, 1000, &mut rng), Err(Error::InvalidArgument(_))));
+        assert!(fips140_check_outputs(|_: &[u8]| Vec::new(), 16, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_constant_outputs_fail_fips140() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let result = fips140_check_outputs(|_: &[u8]| vec![0u8; 20], 16, &mut rng);
+        assert_eq!(result, Err(Error::MonobitTest { zeros: 20000, range: [9654, 10346] }));
+    }
+}
@@ -0,0 +1,11 @@
+use std::ffi::OsString;
+use std::io;
+use std::process;
+use codewars::sha1sum::sha1sum;
+
+fn main() {
+    let args: Vec<OsString> = std::env::args_os().skip(1).collect();
+
+    let code = sha1sum::run(&args, &mut io::stdin().lock(), &mut io::stdout().lock(), &mut io::stderr().lock());
+    process::exit(code);
+}
@@ -0,0 +1,298 @@
+// Runner for the NIST CAVP DRBG response files (Hash_DRBG.rsp from drbgtestvectors.zip: the no_reseed,
+// pr_false and pr_true variants). The files aren't a part of the repository: download them from the CAVP page
+// and set CAVP_DRBG_DIRECTORY (environment or .env) to the directory with them.
+use std::fs;
+use std::path::{Path, PathBuf};
+use digest::Digest;
+use super::hash_drbg::HashDrbg;
+use crate::error::Error;
+use crate::sha1::sha1::Sha1Realization32;
+use crate::sha2::sha2::{Sha224Realization32, Sha256Realization32, Sha384Realization64, Sha512Realization64, Sha512_224Realization64, Sha512_256Realization64};
+
+pub const DIRECTORY_VARIABLE: &str = "CAVP_DRBG_DIRECTORY";
+
+// Generate calls of every vector, the output of the last one is ReturnedBits
+const GENERATE_CALLS: usize = 2;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct DrbgVector {
+    // Section name: "SHA-1", "SHA-256", ...
+    pub algorithm: String,
+    pub count: usize,
+    pub prediction_resistance: bool,
+    pub entropy_input: Vec<u8>,
+    pub nonce: Vec<u8>,
+    pub personalization_string: Vec<u8>,
+    // EntropyInputReseed and AdditionalInputReseed of the pr_false files
+    pub reseed: Option<(Vec<u8>, Vec<u8>)>,
+    pub additional_inputs: Vec<Vec<u8>>,
+    // EntropyInputPR of the pr_true files, one for every generate call
+    pub prediction_resistance_inputs: Vec<Vec<u8>>,
+    pub returned_bits: Vec<u8>,
+}
+
+#[derive(Debug, Default)]
+pub struct DrbgReport {
+    pub passed: usize,
+    // Descriptions of the failed vectors
+    pub failed: Vec<String>,
+    // Vectors of unsupported hash functions
+    pub skipped: usize,
+}
+
+impl DrbgReport {
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+// Parses the response file: "[SHA-1]", "[PredictionResistance = True]" and other headers, then the vectors
+// from "COUNT" to "ReturnedBits". Comments (#) and empty lines are skipped.
+pub fn parse_rsp(content: &str) -> Result<Vec<DrbgVector>, Error> {
+    let mut vectors = Vec::new();
+    let mut algorithm: Option<String> = None;
+    let mut prediction_resistance = false;
+    // The vector being read and its EntropyInputReseed waiting for AdditionalInputReseed
+    let mut vector: Option<(DrbgVector, Option<Vec<u8>>)> = None;
+
+    for (line_index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let line_error = || format!("Line {}: '{}'", line_index + 1, line);
+
+        if let Some(header) = line.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+            match header.split_once('=').map(|(key, value)| (key.trim(), value.trim())) {
+                Some(("PredictionResistance", "True")) => prediction_resistance = true,
+                Some(("PredictionResistance", "False")) => prediction_resistance = false,
+                Some(("PredictionResistance", _)) => return Err(Error::InvalidFormat(format!("{}: expected True or False", line_error()))),
+                Some(_) => {}
+                None => algorithm = Some(header.to_string()),
+            }
+            continue;
+        }
+
+        let (key, value) = line.split_once('=').map(|(key, value)| (key.trim(), value.trim())).ok_or_else(|| Error::InvalidFormat(line_error()))?;
+
+        if key == "COUNT" {
+            if vector.is_some() {
+                return Err(Error::InvalidFormat(format!("{}: previous vector has no ReturnedBits", line_error())));
+            }
+            let current = DrbgVector {
+                algorithm: algorithm.clone().ok_or_else(|| Error::InvalidFormat(format!("{}: vector before the hash function header", line_error())))?,
+                count: value.parse().map_err(|_| Error::InvalidFormat(line_error()))?,
+                prediction_resistance,
+                entropy_input: Vec::new(),
+                nonce: Vec::new(),
+                personalization_string: Vec::new(),
+                reseed: None,
+                additional_inputs: Vec::new(),
+                prediction_resistance_inputs: Vec::new(),
+                returned_bits: Vec::new(),
+            };
+            vector = Some((current, None));
+            continue;
+        }
+
+        let (current, reseed_entropy) = vector.as_mut().ok_or_else(|| Error::InvalidFormat(format!("{}: field before COUNT", line_error())))?;
+        let bytes = hex::decode(value).map_err(|_| Error::InvalidFormat(line_error()))?;
+
+        match key {
+            "EntropyInput" => current.entropy_input = bytes,
+            "Nonce" => current.nonce = bytes,
+            "PersonalizationString" => current.personalization_string = bytes,
+            "EntropyInputReseed" => *reseed_entropy = Some(bytes),
+            "AdditionalInputReseed" => {
+                let entropy = reseed_entropy.take().ok_or_else(|| Error::InvalidFormat(format!("{}: AdditionalInputReseed without EntropyInputReseed", line_error())))?;
+                current.reseed = Some((entropy, bytes));
+            }
+            "AdditionalInput" => current.additional_inputs.push(bytes),
+            "EntropyInputPR" => current.prediction_resistance_inputs.push(bytes),
+            "ReturnedBits" => {
+                let (mut finished, reseed_entropy) = vector.take().unwrap();
+                finished.returned_bits = bytes;
+
+                if reseed_entropy.is_some() {
+                    return Err(Error::InvalidFormat(format!("{}: EntropyInputReseed without AdditionalInputReseed", line_error())));
+                }
+                if finished.additional_inputs.len() != GENERATE_CALLS {
+                    return Err(Error::InvalidFormat(format!("{}: expected {} AdditionalInput values", line_error(), GENERATE_CALLS)));
+                }
+                if finished.prediction_resistance && finished.prediction_resistance_inputs.len() != GENERATE_CALLS {
+                    return Err(Error::InvalidFormat(format!("{}: expected {} EntropyInputPR values", line_error(), GENERATE_CALLS)));
+                }
+                vectors.push(finished);
+            }
+            _ => return Err(Error::InvalidFormat(format!("{}: unknown field", line_error()))),
+        }
+    }
+
+    if vector.is_some() {
+        return Err(Error::InvalidFormat("Last vector has no ReturnedBits".to_string()));
+    }
+
+    Ok(vectors)
+}
+
+// CAVP procedure: instantiate, reseed (pr_false), generate twice (with fresh entropy for pr_true),
+// the second output must be ReturnedBits
+pub fn check_vector<D: Digest>(vector: &DrbgVector) -> Result<bool, Error> {
+    let mut drbg = HashDrbg::<D>::instantiate(&vector.entropy_input, &vector.nonce, &vector.personalization_string)?;
+    if let Some((entropy, additional_input)) = &vector.reseed {
+        drbg.reseed(entropy, additional_input)?;
+    }
+
+    let mut output = vec![0u8; vector.returned_bits.len()];
+    for (call, additional_input) in vector.additional_inputs.iter().enumerate() {
+        if vector.prediction_resistance {
+            drbg.generate_with_prediction_resistance(&mut output, &vector.prediction_resistance_inputs[call], additional_input)?;
+        } else {
+            drbg.generate(&mut output, additional_input)?;
+        }
+    }
+
+    Ok(output == vector.returned_bits)
+}
+
+pub fn check_vectors(vectors: &[DrbgVector]) -> DrbgReport {
+    let mut report = DrbgReport::default();
+
+    for vector in vectors {
+        let result = match vector.algorithm.as_str() {
+            "SHA-1" => check_vector::<Sha1Realization32>(vector),
+            "SHA-224" => check_vector::<Sha224Realization32>(vector),
+            "SHA-256" => check_vector::<Sha256Realization32>(vector),
+            "SHA-384" => check_vector::<Sha384Realization64>(vector),
+            "SHA-512" => check_vector::<Sha512Realization64>(vector),
+            "SHA-512/224" => check_vector::<Sha512_224Realization64>(vector),
+            "SHA-512/256" => check_vector::<Sha512_256Realization64>(vector),
+            _ => {
+                report.skipped += 1;
+                continue;
+            }
+        };
+
+        let description = format!("{} (PredictionResistance = {}) COUNT = {}", vector.algorithm, vector.prediction_resistance, vector.count);
+        match result {
+            Ok(true) => report.passed += 1,
+            Ok(false) => report.failed.push(format!("{}: wrong ReturnedBits", description)),
+            Err(error) => report.failed.push(format!("{}: {}", description, error)),
+        }
+    }
+
+    report
+}
+
+pub fn run_rsp_file(path: &Path) -> Result<DrbgReport, Error> {
+    let content = fs::read_to_string(path).map_err(|error| Error::io(path, error))?;
+    let vectors = parse_rsp(&content).map_err(|error| Error::InvalidFormat(format!("Can't parse {}: {}", path.display(), error)))?;
+
+    Ok(check_vectors(&vectors))
+}
+
+// All Hash_DRBG*.rsp files of the directory and its subdirectories (the zip has one per variant)
+pub fn rsp_files(directory: &Path) -> Result<Vec<PathBuf>, Error> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(directory).map_err(|error| Error::io(directory, error))? {
+        let path = entry.map_err(|error| Error::io(directory, error))?.path();
+        if path.is_dir() {
+            files.extend(rsp_files(&path)?);
+            continue;
+        }
+
+        let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+        if name.starts_with("Hash_DRBG") && name.ends_with(".rsp") {
+            files.push(path);
+        }
+    }
+    files.sort();
+
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Vectors in the CAVP format, ReturnedBits from 'test_data/hash_drbg.py': SHA-1, SHA-256 and SHA-512,
+    // without reseed, with reseed and with prediction resistance
+    const REFERENCE_VECTORS: &str = include_str!("test_data/Hash_DRBG_reference.rsp");
+
+    #[test]
+    fn test_drbg_reference_file() {
+        let vectors = parse_rsp(REFERENCE_VECTORS).unwrap();
+        assert_eq!(vectors.len(), 36);
+        assert!(vectors.iter().any(|vector| vector.reseed.is_some()));
+        assert!(vectors.iter().any(|vector| vector.prediction_resistance && vector.prediction_resistance_inputs.len() == 2));
+
+        let report = check_vectors(&vectors);
+        assert!(report.is_success(), "{:?}", report.failed);
+        assert_eq!((report.passed, report.skipped), (36, 0));
+
+        // A broken output is reported
+        let broken = REFERENCE_VECTORS.replacen("ReturnedBits = b9", "ReturnedBits = b8", 1);
+        let report = check_vectors(&parse_rsp(&broken).unwrap());
+        assert_eq!(report.failed.len(), 1);
+        assert!(report.failed[0].starts_with("SHA-1 (PredictionResistance = false) COUNT = 0"));
+
+        // Unknown hash functions are skipped
+        let report = check_vectors(&parse_rsp(&REFERENCE_VECTORS.replace("[SHA-512]", "[SHA3-256]")).unwrap());
+        assert_eq!((report.passed, report.skipped), (24, 12));
+    }
+
+    // The NIST no_reseed cases, so the official vectors are checked even without CAVP_DRBG_DIRECTORY
+    const NIST_VECTORS: &str = include_str!("test_data/Hash_DRBG_nist.rsp");
+
+    #[test]
+    fn test_drbg_nist_file() {
+        let vectors = parse_rsp(NIST_VECTORS).unwrap();
+        assert_eq!(vectors.len(), 2);
+        assert_eq!(vectors[0].returned_bits.len(), 80);
+        assert_eq!(vectors[1].returned_bits.len(), 128);
+
+        let report = check_vectors(&vectors);
+        assert!(report.is_success(), "{:?}", report.failed);
+        assert_eq!((report.passed, report.skipped), (2, 0));
+    }
+
+    #[test]
+    fn test_drbg_parse_errors() {
+        assert!(parse_rsp("COUNT = 0\nEntropyInput = 00").is_err());
+        assert!(parse_rsp("[SHA-1]\nEntropyInput = 00").is_err());
+        assert!(parse_rsp("[SHA-1]\nCOUNT = 0\nEntropyInput = zz").is_err());
+        assert!(parse_rsp("[SHA-1]\nCOUNT = 0\nAdditionalInput = \nReturnedBits = 00").is_err());
+        assert!(parse_rsp("[SHA-1]\nCOUNT = 0\nAdditionalInputReseed = 00").is_err());
+        // EntropyInputReseed without its AdditionalInputReseed isn't dropped silently
+        let pending_reseed = "[SHA-1]\nCOUNT = 0\nEntropyInputReseed = 00\nAdditionalInput = \nAdditionalInput = \nReturnedBits = 00";
+        assert!(matches!(parse_rsp(pending_reseed), Err(Error::InvalidFormat(_))));
+        assert!(parse_rsp("[SHA-1]\nCOUNT = 0\nAdditionalInput = \nAdditionalInput = ").is_err());
+        assert!(parse_rsp("[SHA-1]\n[PredictionResistance = True]\nCOUNT = 0\nAdditionalInput = \nAdditionalInput = \nReturnedBits = 00").is_err());
+        assert!(parse_rsp("[SHA-1]\n[PredictionResistance = maybe]").is_err());
+        assert!(parse_rsp("[SHA-1]\nCOUNT = 0\nSomething = 00").is_err());
+
+        // A missing file or directory is reported with its path
+        assert!(matches!(run_rsp_file(Path::new("missing.rsp")), Err(Error::Io { .. })));
+        assert!(matches!(rsp_files(Path::new("missing")), Err(Error::Io { .. })));
+    }
+
+    // Checks every Hash_DRBG*.rsp file from CAVP_DRBG_DIRECTORY, nothing to do when it isn't set
+    #[test]
+    fn test_drbg_local_files() {
+        dotenv::dotenv().ok();
+        let Ok(directory) = std::env::var(DIRECTORY_VARIABLE) else {
+            println!("{} isn't set, CAVP files are skipped", DIRECTORY_VARIABLE);
+            return;
+        };
+
+        let files = rsp_files(Path::new(&directory)).unwrap();
+        assert!(!files.is_empty(), "No Hash_DRBG*.rsp files in {}", directory);
+
+        for file in files {
+            let report = run_rsp_file(&file).unwrap();
+            println!("{}: {} passed, {} skipped", file.display(), report.passed, report.skipped);
+            assert!(report.is_success(), "{}: {:?}", file.display(), report.failed);
+        }
+    }
+}
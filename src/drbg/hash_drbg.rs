@@ -0,0 +1,347 @@
+// Hash_DRBG from NIST SP 800-90A Rev. 1 (10.1.1) over any 'Digest': Sha1Realization32, Sha256Realization32, ...
+// The state is V and C of 'seedlen' bits, every request is answered with Hashgen(V), then V is updated
+// with the constant C and the reseed counter, so the previous outputs can't be recovered from the state.
+use std::marker::PhantomData;
+use crate::error::Error;
+use digest::Digest;
+use rand::{CryptoRng, RngCore};
+
+// Limits from SP 800-90A, table 2
+pub const MAX_BYTES_PER_REQUEST: usize = 1 << 16;
+pub const MAX_RESEED_INTERVAL: u64 = 1 << 48;
+
+pub struct HashDrbg<D: Digest> {
+    value: Vec<u8>,
+    constant: Vec<u8>,
+    reseed_counter: u64,
+    // Generate requests allowed before a reseed is required
+    reseed_interval: u64,
+    // Source of the fresh entropy for the automatic reseeds when used as 'RngCore'
+    entropy_source: Option<Box<dyn RngCore + Send>>,
+    prediction_resistance: bool,
+    _digest: PhantomData<D>,
+}
+
+impl<D: Digest> HashDrbg<D> {
+    // Instantiate: seed = Hash_df(entropy || nonce || personalization), V = seed, C = Hash_df(0x00 || V)
+    pub fn instantiate(entropy: &[u8], nonce: &[u8], personalization: &[u8]) -> Result<Self, Error> {
+        check_entropy::<D>(entropy)?;
+        if nonce.len() * 2 < security_strength::<D>() {
+            return Err(Error::InvalidArgument(format!("Nonce must be at least {} bytes, got: {}", security_strength::<D>() / 2, nonce.len())));
+        }
+
+        let value = hash_df::<D>(&[entropy, nonce, personalization], seed_length::<D>());
+        let constant = hash_df::<D>(&[&[0x00], &value], seed_length::<D>());
+
+        Ok(HashDrbg {
+            value,
+            constant,
+            reseed_counter: 1,
+            reseed_interval: MAX_RESEED_INTERVAL,
+            entropy_source: None,
+            prediction_resistance: false,
+            _digest: PhantomData,
+        })
+    }
+
+    // Instantiates from the entropy source and keeps it for the reseeds, e.g. 'rand::rngs::OsRng'.
+    // With 'prediction_resistance' every request gets fresh entropy first.
+    pub fn from_entropy_source(mut entropy_source: Box<dyn RngCore + Send>, personalization: &[u8], prediction_resistance: bool) -> Result<Self, Error> {
+        let mut entropy = vec![0u8; security_strength::<D>()];
+        let mut nonce = vec![0u8; security_strength::<D>() / 2];
+        entropy_source.try_fill_bytes(&mut entropy).map_err(|error| Error::EntropySourceFailed(error.to_string()))?;
+        entropy_source.try_fill_bytes(&mut nonce).map_err(|error| Error::EntropySourceFailed(error.to_string()))?;
+
+        let mut drbg = HashDrbg::instantiate(&entropy, &nonce, personalization)?;
+        drbg.entropy_source = Some(entropy_source);
+        drbg.prediction_resistance = prediction_resistance;
+
+        Ok(drbg)
+    }
+
+    pub fn reseed_counter(&self) -> u64 {
+        self.reseed_counter
+    }
+
+    pub fn set_reseed_interval(&mut self, reseed_interval: u64) -> Result<(), Error> {
+        if reseed_interval == 0 || reseed_interval > MAX_RESEED_INTERVAL {
+            return Err(Error::InvalidArgument(format!("Reseed interval must be from 1 to 2^48, got: {}", reseed_interval)));
+        }
+        self.reseed_interval = reseed_interval;
+        Ok(())
+    }
+
+    // Reseed: seed = Hash_df(0x01 || V || entropy || additional input), V = seed, C = Hash_df(0x00 || V)
+    pub fn reseed(&mut self, entropy: &[u8], additional_input: &[u8]) -> Result<(), Error> {
+        check_entropy::<D>(entropy)?;
+
+        self.value = hash_df::<D>(&[&[0x01], &self.value, entropy, additional_input], seed_length::<D>());
+        self.constant = hash_df::<D>(&[&[0x00], &self.value], seed_length::<D>());
+        self.reseed_counter = 1;
+
+        Ok(())
+    }
+
+    // Fills the output, fails when a reseed is required or the request is too big
+    pub fn generate(&mut self, output: &mut [u8], additional_input: &[u8]) -> Result<(), Error> {
+        if output.len() > MAX_BYTES_PER_REQUEST {
+            return Err(Error::InvalidArgument(format!("Request must be not bigger than {} bytes, got: {}", MAX_BYTES_PER_REQUEST, output.len())));
+        }
+        if self.reseed_counter > self.reseed_interval {
+            return Err(Error::ReseedRequired { interval: self.reseed_interval });
+        }
+
+        if !additional_input.is_empty() {
+            let w = D::new().chain_update([0x02]).chain_update(&self.value).chain_update(additional_input).finalize();
+            add_mod(&mut self.value, &w);
+        }
+
+        self.hashgen(output);
+
+        // V = (V + H + C + reseed_counter) mod 2^seedlen
+        let h = D::new().chain_update([0x03]).chain_update(&self.value).finalize();
+        let constant = self.constant.clone();
+        add_mod(&mut self.value, &h);
+        add_mod(&mut self.value, &constant);
+        add_mod(&mut self.value, &self.reseed_counter.to_be_bytes());
+        self.reseed_counter += 1;
+
+        Ok(())
+    }
+
+    // Generate with prediction resistance (9.3.1): reseed with the fresh entropy and the additional input,
+    // then generate without additional input
+    pub fn generate_with_prediction_resistance(&mut self, output: &mut [u8], entropy: &[u8], additional_input: &[u8]) -> Result<(), Error> {
+        self.reseed(entropy, additional_input)?;
+        self.generate(output, &[])
+    }
+
+    // Hashgen: Hash(V) || Hash(V + 1) || ..., the leftmost bytes
+    fn hashgen(&self, output: &mut [u8]) {
+        let mut data = self.value.clone();
+
+        for chunk in output.chunks_mut(<D as Digest>::output_size()) {
+            let block = D::digest(&data);
+            chunk.copy_from_slice(&block[..chunk.len()]);
+            add_mod(&mut data, &[1]);
+        }
+    }
+
+    // Reseeds from the entropy source when it's needed, then generates in chunks of the maximum request
+    fn fill_from_source(&mut self, output: &mut [u8]) -> Result<(), rand::Error> {
+        for chunk in output.chunks_mut(MAX_BYTES_PER_REQUEST) {
+            if self.prediction_resistance || self.reseed_counter > self.reseed_interval {
+                let mut entropy = vec![0u8; security_strength::<D>()];
+                match self.entropy_source.as_mut() {
+                    Some(entropy_source) => entropy_source.try_fill_bytes(&mut entropy)?,
+                    None => return Err(rand::Error::new("Reseed is required, but there is no entropy source")),
+                }
+                self.reseed(&entropy, &[]).map_err(|error| rand::Error::new(error.to_string()))?;
+            }
+
+            self.generate(chunk, &[]).map_err(|error| rand::Error::new(error.to_string()))?;
+        }
+
+        Ok(())
+    }
+}
+
+// The DRBG can be passed anywhere a cryptographic 'RngCore' is expected, e.g. key generation
+impl<D: Digest> RngCore for HashDrbg<D> {
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0u8; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0u8; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        if let Err(error) = self.fill_from_source(dest) {
+            panic!("Hash_DRBG failed: {}", error)
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_from_source(dest)
+    }
+}
+
+impl<D: Digest> CryptoRng for HashDrbg<D> {}
+
+// seedlen from SP 800-90A, table 2: 440 bits up to SHA-256, 888 bits for SHA-384 and SHA-512
+fn seed_length<D: Digest>() -> usize {
+    if <D as Digest>::output_size() <= 32 { 55 } else { 111 }
+}
+
+// Security strength in bytes (SP 800-57): 128 bits for SHA-1, 192 for SHA-224, 256 for the bigger hashes
+fn security_strength<D: Digest>() -> usize {
+    match <D as Digest>::output_size() {
+        20 => 16,
+        28 => 24,
+        _ => 32,
+    }
+}
+
+fn check_entropy<D: Digest>(entropy: &[u8]) -> Result<(), Error> {
+    if entropy.len() < security_strength::<D>() {
+        return Err(Error::InvalidArgument(format!("Entropy input must be at least {} bytes, got: {}", security_strength::<D>(), entropy.len())));
+    }
+    Ok(())
+}
+
+// Hash_df (10.3.1): Hash(counter || bits to return || input) for counter = 1, 2, ..., the leftmost 'length' bytes
+fn hash_df<D: Digest>(input: &[&[u8]], length: usize) -> Vec<u8> {
+    let bits = (length as u32 * 8).to_be_bytes();
+    let mut output = Vec::with_capacity(length + <D as Digest>::output_size());
+
+    let mut counter: u8 = 1;
+    while output.len() < length {
+        let mut hasher = D::new().chain_update([counter]).chain_update(bits);
+        for part in input {
+            hasher.update(part);
+        }
+        output.extend_from_slice(&hasher.finalize());
+        counter += 1;
+    }
+    output.truncate(length);
+
+    output
+}
+
+// value = (value + addend) mod 2^(8 * value.len()), both big-endian
+fn add_mod(value: &mut [u8], addend: &[u8]) {
+    let mut carry = 0u16;
+    let mut addend_bytes = addend.iter().rev();
+
+    for byte in value.iter_mut().rev() {
+        let sum = *byte as u16 + *addend_bytes.next().unwrap_or(&0) as u16 + carry;
+        *byte = sum as u8;
+        carry = sum >> 8;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use crate::sha1::sha1::Sha1Realization32;
+    use crate::sha2::sha2::Sha256Realization32;
+
+    fn sha256_drbg() -> HashDrbg<Sha256Realization32> {
+        let entropy: Vec<u8> = (0..32).collect();
+        let nonce: Vec<u8> = (32..48).collect();
+        HashDrbg::instantiate(&entropy, &nonce, &[]).unwrap()
+    }
+
+    #[test]
+    fn test_hash_drbg_generate() {
+        // Values printed by 'python3 test_data/hash_drbg.py' (SP 800-90A over Python's hashlib)
+        let mut drbg = sha256_drbg();
+        let mut output = [0u8; 16];
+        drbg.generate(&mut output, &[]).unwrap();
+        assert_eq!(hex::encode(output), "48f1bd755b6b0625155a440483340d86");
+        drbg.generate(&mut output, b"add").unwrap();
+        assert_eq!(hex::encode(output), "3040e1f14d78b9f88091f9446f2b4ac5");
+
+        assert_eq!(hex::encode(&drbg.value), "a8e2c61eaa67c489c6fb99ce57cf83edfb4b5b3cf3c5375c31d8324df9e63efc8f9c0e79b346180abff207de48d9973a152c716b1f5dbd");
+        assert_eq!(hex::encode(&drbg.constant), "78722e72ef495f78da9b4a26efe7300cafe72e71fda5b3e5eef10bddc795fe2d817cda329c7cd733bd07e5fcf4f36c120033bcee8ae1d9");
+        assert_eq!(drbg.reseed_counter(), 3);
+
+        // Output of the same request doesn't repeat, short requests are the prefix of Hashgen
+        let mut first = sha256_drbg();
+        let mut second = sha256_drbg();
+        let mut long = [0u8; 100];
+        let mut short = [0u8; 33];
+        first.generate(&mut long, &[]).unwrap();
+        second.generate(&mut short, &[]).unwrap();
+        assert_eq!(long[..33], short);
+        first.generate(&mut short, &[]).unwrap();
+        assert_ne!(long[..33], short);
+
+        assert!(drbg.generate(&mut vec![0u8; MAX_BYTES_PER_REQUEST + 1], &[]).is_err());
+    }
+
+    #[test]
+    fn test_hash_drbg_reseed_counter() {
+        let mut drbg = sha256_drbg();
+        drbg.set_reseed_interval(2).unwrap();
+        let mut output = [0u8; 32];
+
+        drbg.generate(&mut output, &[]).unwrap();
+        drbg.generate(&mut output, &[]).unwrap();
+        assert_eq!(drbg.reseed_counter(), 3);
+        assert_eq!(drbg.generate(&mut output, &[]), Err(Error::ReseedRequired { interval: 2 }));
+
+        assert!(matches!(drbg.reseed(&[0u8; 31], &[]), Err(Error::InvalidArgument(_))));
+        drbg.reseed(&[7u8; 32], b"reseed").unwrap();
+        assert_eq!(drbg.reseed_counter(), 1);
+        drbg.generate(&mut output, &[]).unwrap();
+
+        assert!(drbg.set_reseed_interval(0).is_err());
+        assert!(drbg.set_reseed_interval(MAX_RESEED_INTERVAL + 1).is_err());
+    }
+
+    #[test]
+    fn test_hash_drbg_instantiate_errors() {
+        // SHA-1 needs 128 bits of entropy and 64 bits of nonce, SHA-256 needs 256 and 128
+        assert!(HashDrbg::<Sha1Realization32>::instantiate(&[0u8; 16], &[0u8; 8], &[]).is_ok());
+        assert!(HashDrbg::<Sha1Realization32>::instantiate(&[0u8; 15], &[0u8; 8], &[]).is_err());
+        assert!(HashDrbg::<Sha1Realization32>::instantiate(&[0u8; 16], &[0u8; 7], &[]).is_err());
+        assert!(HashDrbg::<Sha256Realization32>::instantiate(&[0u8; 16], &[0u8; 16], &[]).is_err());
+
+        // Personalization string changes the output
+        let mut plain = HashDrbg::<Sha1Realization32>::instantiate(&[1u8; 16], &[2u8; 8], &[]).unwrap();
+        let mut personalized = HashDrbg::<Sha1Realization32>::instantiate(&[1u8; 16], &[2u8; 8], b"device 1").unwrap();
+        assert_ne!(plain.next_u64(), personalized.next_u64());
+    }
+
+    #[test]
+    fn test_hash_drbg_rng() {
+        // Entropy source is seeded, so the whole sequence is reproducible
+        let source = || Box::new(StdRng::seed_from_u64(42)) as Box<dyn RngCore + Send>;
+        let mut first = HashDrbg::<Sha256Realization32>::from_entropy_source(source(), b"test", false).unwrap();
+        let mut second = HashDrbg::<Sha256Realization32>::from_entropy_source(source(), b"test", false).unwrap();
+        assert_eq!(first.next_u64(), second.next_u64());
+
+        // Requests bigger than the limit are split, the reseed happens automatically
+        first.set_reseed_interval(1).unwrap();
+        let mut big = vec![0u8; 3 * MAX_BYTES_PER_REQUEST + 10];
+        first.fill_bytes(&mut big);
+        assert_eq!(first.reseed_counter(), 2);
+        assert!(big[big.len() - 32..].iter().any(|byte| *byte != 0));
+
+        // Prediction resistance reseeds before every request
+        let mut resistant = HashDrbg::<Sha256Realization32>::from_entropy_source(source(), b"test", true).unwrap();
+        let mut plain = HashDrbg::<Sha256Realization32>::from_entropy_source(source(), b"test", false).unwrap();
+        assert_ne!(resistant.next_u64(), plain.next_u64());
+        assert_eq!(resistant.reseed_counter(), 2);
+
+        // Without entropy source there is nothing to reseed from
+        let mut drbg = sha256_drbg();
+        drbg.set_reseed_interval(1).unwrap();
+        drbg.next_u32();
+        assert!(drbg.try_fill_bytes(&mut [0u8; 4]).is_err());
+
+        // Can be used with 'rand' helpers
+        let value: u32 = rand::Rng::gen_range(&mut second, 10..20);
+        assert!((10..20).contains(&value));
+    }
+
+    #[test]
+    fn test_hash_drbg_add_mod() {
+        let mut value = [0x00, 0xff, 0xff];
+        add_mod(&mut value, &[1]);
+        assert_eq!(value, [0x01, 0x00, 0x00]);
+
+        // Overflow is dropped, as well as the addend bytes beyond the value
+        let mut value = [0xff, 0xff];
+        add_mod(&mut value, &[0x01, 0x00, 0x02]);
+        assert_eq!(value, [0x00, 0x01]);
+    }
+}
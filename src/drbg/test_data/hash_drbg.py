@@ -0,0 +1,132 @@
+#!/usr/bin/env python3
+# Hash_DRBG of NIST SP 800-90A Rev. 1 (10.1.1) on top of Python's hashlib, written separately from the Rust code.
+# It is the source of the expected values of 'hash_drbg.rs' and 'Hash_DRBG_reference.rsp':
+#   python3 hash_drbg.py                          - prints the values of 'test_hash_drbg_generate'
+#   python3 hash_drbg.py Hash_DRBG_nist.rsp ...   - recomputes ReturnedBits of every vector of the files
+# The NIST file checks this script itself, the reference file is checked the same way.
+import hashlib
+import sys
+
+# Hash function and seedlen in bytes (SP 800-90A, table 2)
+HASHES = {
+    "SHA-1": ("sha1", 55),
+    "SHA-224": ("sha224", 55),
+    "SHA-256": ("sha256", 55),
+    "SHA-384": ("sha384", 111),
+    "SHA-512": ("sha512", 111),
+    "SHA-512/224": ("sha512_224", 55),
+    "SHA-512/256": ("sha512_256", 55),
+}
+
+
+class HashDrbg:
+    def __init__(self, algorithm, entropy, nonce, personalization):
+        self.name, self.seed_length = HASHES[algorithm]
+        self.set_value(self.hash_df(entropy + nonce + personalization))
+
+    def hash(self, data):
+        return hashlib.new(self.name, data).digest()
+
+    # Hash_df (10.3.1): Hash(counter || bit count || input) until 'seed_length' bytes
+    def hash_df(self, data):
+        output = b""
+        counter = 1
+        while len(output) < self.seed_length:
+            output += self.hash(bytes([counter]) + (self.seed_length * 8).to_bytes(4, "big") + data)
+            counter += 1
+        return output[:self.seed_length]
+
+    def set_value(self, value):
+        self.value = value
+        self.constant = self.hash_df(b"\x00" + value)
+        self.reseed_counter = 1
+
+    def add(self, *numbers):
+        total = sum(int.from_bytes(number, "big") for number in numbers)
+        return (total % (1 << (self.seed_length * 8))).to_bytes(self.seed_length, "big")
+
+    def reseed(self, entropy, additional_input):
+        self.set_value(self.hash_df(b"\x01" + self.value + entropy + additional_input))
+
+    def generate(self, length, additional_input=b""):
+        if additional_input:
+            self.value = self.add(self.value, self.hash(b"\x02" + self.value + additional_input))
+
+        output = b""
+        data = self.value
+        while len(output) < length:
+            output += self.hash(data)
+            data = self.add(data, b"\x01")
+
+        h = self.hash(b"\x03" + self.value)
+        self.value = self.add(self.value, h, self.constant, self.reseed_counter.to_bytes(8, "big"))
+        self.reseed_counter += 1
+        return output[:length]
+
+
+# Vectors of a CAVP response file as dictionaries, the procedure of 'cavp.rs'
+def parse_rsp(path):
+    vectors = []
+    algorithm, prediction_resistance, vector = None, False, None
+    for line in open(path):
+        line = line.strip()
+        if not line or line.startswith("#"):
+            continue
+        if line.startswith("["):
+            header = line[1:-1]
+            if header.startswith("PredictionResistance"):
+                prediction_resistance = header.split("=")[1].strip() == "True"
+            elif "=" not in header:
+                algorithm = header
+            continue
+
+        key, value = (part.strip() for part in line.split("=", 1))
+        if key == "COUNT":
+            vector = {"algorithm": algorithm, "count": value, "pr": prediction_resistance, "AdditionalInput": [], "EntropyInputPR": []}
+        elif key in ("AdditionalInput", "EntropyInputPR"):
+            vector[key].append(bytes.fromhex(value))
+        else:
+            vector[key] = bytes.fromhex(value)
+            if key == "ReturnedBits":
+                vectors.append(vector)
+    return vectors
+
+
+def returned_bits(vector):
+    drbg = HashDrbg(vector["algorithm"], vector["EntropyInput"], vector["Nonce"], vector["PersonalizationString"])
+    if "EntropyInputReseed" in vector:
+        drbg.reseed(vector["EntropyInputReseed"], vector["AdditionalInputReseed"])
+
+    length = len(vector["ReturnedBits"])
+    for call, additional_input in enumerate(vector["AdditionalInput"]):
+        if vector["pr"]:
+            drbg.reseed(vector["EntropyInputPR"][call], additional_input)
+            output = drbg.generate(length)
+        else:
+            output = drbg.generate(length, additional_input)
+    return output
+
+
+def main():
+    if len(sys.argv) == 1:
+        # 'test_hash_drbg_generate': SHA-256, entropy 00..1f, nonce 20..2f
+        drbg = HashDrbg("SHA-256", bytes(range(32)), bytes(range(32, 48)), b"")
+        print(drbg.generate(16).hex())
+        print(drbg.generate(16, b"add").hex())
+        print(drbg.value.hex())
+        print(drbg.constant.hex())
+        return
+
+    failed = 0
+    for path in sys.argv[1:]:
+        vectors = parse_rsp(path)
+        for vector in vectors:
+            if returned_bits(vector) != vector["ReturnedBits"]:
+                failed += 1
+                print("{}: {} COUNT = {} doesn't match".format(path, vector["algorithm"], vector["count"]))
+        print("{}: {} vectors checked".format(path, len(vectors)))
+    sys.exit(1 if failed else 0)
+
+
+if __name__ == "__main__":
+    main()
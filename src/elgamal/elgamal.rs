@@ -1,188 +1,495 @@
 extern crate rand;
 
-use num_bigint::{BigInt, BigUint};
+use num_bigint::{BigUint, RandBigInt};
 use num_traits::{Zero, ToPrimitive};
-use num_primes::{Generator, Verification, BigUint as BigUintPrime};
 use num_traits::One;
-use rand::Rng;
-use num_integer::gcd;
+use num_integer::Integer;
+use rand::{CryptoRng, Rng, RngCore};
+use rand::rngs::OsRng;
 use hex;
+use crate::error::Error;
 
-pub fn hex_to_number(hex: String) -> BigInt {
-    let hex: Vec<u8> = hex::decode(hex).expect("Decoding failed");
-    BigInt::from(BigUint::from_bytes_be(&hex))
+// Miller-Rabin rounds for the generated primes, the error probability is below 4^-64
+const MILLER_RABIN_ROUNDS: usize = 64;
+
+// Trial division before Miller-Rabin
+const SMALL_PRIMES: [u32; 25] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97];
+
+// Upper bound for the bit length of the cofactor 'h' in p = 2hq + 1
+const MAX_COFACTOR_BITS: usize = 32;
+
+// Random candidates checked before giving up on a primitive root
+const PRIMITIVE_ROOT_ATTEMPTS: usize = 1000;
+
+// Random nonces tried before 'sign_with_order' gives up on a non-zero s
+const SIGN_ATTEMPTS: usize = 1000;
+
+// Smallest order of g accepted by 'DomainParameters': phi(n) >= 4 for n >= 7, so there are
+// at least 3 invertible nonces k in [2, order) and not a single one that may always give s = 0
+pub const MIN_ORDER: u32 = 7;
+
+// Odd primes below the bound sieve out safe prime candidates before any modpow
+const SIEVE_BOUND: u32 = 1 << 16;
+
+// Candidates q, q + 2, ... tested from one random starting point
+const SIEVE_WINDOW: u32 = 1 << 16;
+
+// Bit length of the prime subgroup order in the Schnorr group mode
+pub const SCHNORR_Q_BITS: usize = 256;
+
+pub fn hex_to_number(hex: String) -> Result<BigUint, Error> {
+    let hex: Vec<u8> = hex::decode(hex)?;
+    Ok(BigUint::from_bytes_be(&hex))
 }
 
-pub fn genereate_keys(p_bits_from: usize, p_bits_to: usize) -> (BigInt, BigInt, BigInt, BigInt) {
-    let p = generate_prime_number(p_bits_from, p_bits_to);
-    let g = generate_primitive_root(&p);
-    let private_key = generate_number_in_range(&2, &(p.to_usize().unwrap() - 1));
+// Key generation with OS randomness, see 'genereate_keys_with_rng'
+pub fn genereate_keys(p_bits_from: usize, p_bits_to: usize) -> Result<(BigUint, BigUint, BigUint, BigUint), Error> {
+    genereate_keys_with_rng(p_bits_from, p_bits_to, &mut OsRng)
+}
+
+// All the randomness (p, g and the private key) comes from 'rng': a seeded generator gives the same keys
+pub fn genereate_keys_with_rng<R: RngCore + CryptoRng>(
+    p_bits_from: usize,
+    p_bits_to: usize,
+    rng: &mut R
+) -> Result<(BigUint, BigUint, BigUint, BigUint), Error> {
+    let (p, factors) = generate_prime_number(p_bits_from, p_bits_to, rng)?;
+    let g = generate_primitive_root(&p, &factors, rng)?;
+
+    // x = [2, p - 1)
+    let private_key = rng.gen_biguint_range(&BigUint::from(2u32), &(&p - 1u32));
     let public_key = g.modpow(&private_key, &p);
 
-    return (BigInt::from(p), BigInt::from(g), BigInt::from(private_key), BigInt::from(public_key));
+    Ok((p, g, private_key, public_key))
+}
+
+// Safe prime p = 2q + 1, g is a primitive root: g^2 != 1 and g^q != 1 mod p
+pub fn genereate_safe_prime_keys(p_bits: usize) -> Result<(BigUint, BigUint, BigUint, BigUint), Error> {
+    genereate_safe_prime_keys_with_rng(p_bits, &mut OsRng)
+}
+
+pub fn genereate_safe_prime_keys_with_rng<R: RngCore + CryptoRng>(
+    p_bits: usize,
+    rng: &mut R
+) -> Result<(BigUint, BigUint, BigUint, BigUint), Error> {
+    let (p, q) = generate_safe_prime(p_bits, rng)?;
+    let g = generate_primitive_root(&p, &[BigUint::from(2u32), q], rng)?;
+
+    // x = [2, p - 1)
+    let private_key = rng.gen_biguint_range(&BigUint::from(2u32), &(&p - 1u32));
+    let public_key = g.modpow(&private_key, &p);
+
+    Ok((p, g, private_key, public_key))
+}
+
+// Schnorr group with 'SCHNORR_Q_BITS'-bit q: g has order q, the private key is below q
+// Returns p, q, g, private key, public key, the '_with_order' functions take q as the order
+pub fn genereate_schnorr_keys(p_bits: usize) -> Result<(BigUint, BigUint, BigUint, BigUint, BigUint), Error> {
+    genereate_schnorr_keys_with_rng(p_bits, &mut OsRng)
+}
+
+pub fn genereate_schnorr_keys_with_rng<R: RngCore + CryptoRng>(
+    p_bits: usize,
+    rng: &mut R
+) -> Result<(BigUint, BigUint, BigUint, BigUint, BigUint), Error> {
+    let (p, q, g) = generate_schnorr_group(p_bits, SCHNORR_Q_BITS, rng)?;
+
+    // x = [2, q)
+    let private_key = rng.gen_biguint_range(&BigUint::from(2u32), &q);
+    let public_key = g.modpow(&private_key, &p);
+
+    Ok((p, q, g, private_key, public_key))
 }
 
 // Select: k, where 1 < k < p - 1
 // a = g^k mod p
 // b = y^k*H(m) mod p
 // Where y: public_key
-// Where H(m): hash message in BigUint representation
-pub fn encode(hex_num: &BigInt, p: &BigInt, g: &BigInt, public_key: &BigInt) -> (BigInt, BigInt) {
-    let one = BigInt::from(1u32);
-    let p_minus_one = p - &one;
+// Where H(m): hash message in BigUint representation, 0 < H(m) < p
+pub fn encode(hex_num: &BigUint, p: &BigUint, g: &BigUint, public_key: &BigUint) -> Result<(BigUint, BigUint), Error> {
+    encode_with_rng(hex_num, p, g, public_key, &mut OsRng)
+}
+
+pub fn encode_with_rng<R: RngCore + CryptoRng>(hex_num: &BigUint, p: &BigUint, g: &BigUint, public_key: &BigUint, rng: &mut R) -> Result<(BigUint, BigUint), Error> {
+    encode_with_order(hex_num, p, &(p - 1u32), g, public_key, rng)
+}
 
-    // k = [2, p - 1)
-    let k = BigInt::from(generate_number_in_range(&2, &p_minus_one.to_usize().unwrap()));
+// 'order' is the order of g: p - 1 for a primitive root, q in a Schnorr group
+pub fn encode_with_order<R: RngCore + CryptoRng>(
+    hex_num: &BigUint,
+    p: &BigUint,
+    order: &BigUint,
+    g: &BigUint,
+    public_key: &BigUint,
+    rng: &mut R
+) -> Result<(BigUint, BigUint), Error> {
+    check_message_and_order(hex_num, p, order)?;
+
+    // k = [2, order)
+    let k = rng.gen_biguint_range(&BigUint::from(2u32), order);
     let a = g.modpow(&k, p);
-    let b = (public_key.pow(k.to_u32().unwrap()) * hex_num) % p;
+    let b = public_key.modpow(&k, p) * hex_num % p;
 
-    return (a, b);
+    Ok((a, b))
 }
 
 // H(m) = b(a^x)^(-1) mod p = b * a^(p - 1 - x) mod p
 // Where x: private_key
 // Where H(m): hash message in BigUint representation
-pub fn decode(a: &BigInt, b: &BigInt, p: &BigInt, private_key: &BigInt) -> BigInt {
-    b * a.pow(p.to_u32().unwrap() - 1 - private_key.to_u32().unwrap()) % p
+pub fn decode(a: &BigUint, b: &BigUint, p: &BigUint, private_key: &BigUint) -> BigUint {
+    decode_with_order(a, b, p, &(p - 1u32), private_key)
 }
 
-pub fn sign(hex_num: &BigInt, p: &BigInt, g: &BigInt, private_key: &BigInt) -> (BigInt, BigInt) {
-    let one = BigInt::from(1u32);
-    let p_minus_one = p - &one;
+// H(m) = b * a^(order - x) mod p, 'a' lies in the subgroup generated by g
+// x is reduced modulo the order first, so keys >= order don't underflow
+pub fn decode_with_order(a: &BigUint, b: &BigUint, p: &BigUint, order: &BigUint, private_key: &BigUint) -> BigUint {
+    b * a.modpow(&(order - private_key % order), p) % p
+}
 
-    let mut k = BigInt::from(generate_number_in_range(&2, &p_minus_one.to_usize().unwrap()));
-    while gcd(k.clone(), p_minus_one.clone()) != one {
-        k = BigInt::from(generate_number_in_range(&2, &p_minus_one.to_usize().unwrap()));
+pub fn sign(hex_num: &BigUint, p: &BigUint, g: &BigUint, private_key: &BigUint) -> Result<(BigUint, BigUint), Error> {
+    sign_with_rng(hex_num, p, g, private_key, &mut OsRng)
+}
+
+// Select: k, where 1 < k < p - 1 and gcd(k, p - 1) = 1
+// r = g^k mod p
+// s = (H(m) - x*r) * k^(-1) mod (p - 1)
+pub fn sign_with_rng<R: RngCore + CryptoRng>(hex_num: &BigUint, p: &BigUint, g: &BigUint, private_key: &BigUint, rng: &mut R) -> Result<(BigUint, BigUint), Error> {
+    sign_with_order(hex_num, p, &(p - 1u32), g, private_key, rng)
+}
+
+// k and s are taken modulo the order of g, 'verify_sign' is the same for both modes
+// Tiny orders may have no nonce with s != 0, so the search stops after 'SIGN_ATTEMPTS' nonces
+pub fn sign_with_order<R: RngCore + CryptoRng>(
+    hex_num: &BigUint,
+    p: &BigUint,
+    order: &BigUint,
+    g: &BigUint,
+    private_key: &BigUint,
+    rng: &mut R
+) -> Result<(BigUint, BigUint), Error> {
+    check_message_and_order(hex_num, p, order)?;
+
+    for _ in 0..SIGN_ATTEMPTS {
+        let k = rng.gen_biguint_range(&BigUint::from(2u32), order);
+        // No inverse when gcd(k, order) != 1
+        let Some(k_inverse_modulo) = k.modinv(order) else {
+            continue;
+        };
+
+        let r = g.modpow(&k, p);
+        // H(m) - x*r is kept non-negative by adding the order
+        let xr = private_key * &r % order;
+        let s = (hex_num % order + order - xr) * k_inverse_modulo % order;
+
+        // s = 0 would make the signature independent of the private key
+        if !s.is_zero() {
+            return Ok((r, s));
+        }
     }
-    let k_inverse_modulo = BigInt::from(calculate_inverse_modulo(k.to_i64().unwrap(), p_minus_one.to_i64().unwrap()) as u64);
 
-    let r = g.modpow(&k, p);
-    let s = ((hex_num - private_key * &r) * k_inverse_modulo).modpow(&one, &p_minus_one);
+    Err(Error::InvalidArgument(format!("No nonce gives a non-zero s in {} attempts", SIGN_ATTEMPTS)))
+}
+
+// 0 < m < p and MIN_ORDER <= order < p, so the nonce range [2, order) isn't empty
+fn check_message_and_order(hex_num: &BigUint, p: &BigUint, order: &BigUint) -> Result<(), Error> {
+    if hex_num.is_zero() || hex_num >= p {
+        return Err(Error::InvalidArgument("The message is not in (0, p)".to_string()));
+    }
+    if *order < BigUint::from(MIN_ORDER) || order >= p {
+        return Err(Error::InvalidArgument(format!("The order is not in [{}, p)", MIN_ORDER)));
+    }
 
-    return (r, s)
+    Ok(())
 }
 
-// y^r * r^s mod p = g^m mod p
+// y^r * r^s mod p = g^m mod p, where 0 < r < p and 0 < s < p - 1
 // Where y: public_key
+pub fn verify_sign(hex_num: &BigUint, p: &BigUint, g: &BigUint, r: &BigUint, s: &BigUint, public_key: &BigUint) -> bool {
+    if r.is_zero() || r >= p || s.is_zero() || *s >= p - 1u32 {
+        return false;
+    }
 
-pub fn verify_sign(hex_num: &BigInt, p: &BigInt, g: &BigInt, r: &BigInt, s: &BigInt, public_key: &BigInt) -> bool {
-    let left = (public_key.pow(r.try_into().unwrap()) * r.pow(s.try_into().unwrap())) % p;
+    let left = public_key.modpow(r, p) * r.modpow(s, p) % p;
     let right = g.modpow(hex_num, p);
 
-    return left == right;
+    left == right
 }
 
-fn generate_prime_number(p_bits_from: usize, p_bits_to: usize) -> BigUint {
+// p = 2hq + 1, where q is prime and h is a small cofactor, so p - 1 is factored by construction
+// Returns p and the distinct prime factors of p - 1
+pub(crate) fn generate_prime_number<R: RngCore + CryptoRng>(p_bits_from: usize, p_bits_to: usize, rng: &mut R) -> Result<(BigUint, Vec<BigUint>), Error> {
+    if p_bits_from.max(1).div_ceil(8) > p_bits_to / 8 {
+        return Err(Error::InvalidArgument(format!("No multiple of 8 bits in [{}, {}]", p_bits_from, p_bits_to)));
+    }
+
     // Generate 'p' bits length
     let mut bits = 0;
     while bits == 0 || bits % 8 != 0 {
-        bits = rand::thread_rng().gen_range(p_bits_from..=p_bits_to);
+        bits = rng.gen_range(p_bits_from..=p_bits_to);
+    }
+
+    let cofactor_bits = (bits / 4).min(MAX_COFACTOR_BITS);
+    let q_bits = bits - cofactor_bits - 1;
+
+    loop {
+        let q = generate_prime_with_length(q_bits, rng);
+        if let Some((p, h)) = find_prime_with_cofactor(&q, bits, rng) {
+            let mut factors: Vec<BigUint> = small_prime_factors(h.to_u64().unwrap())
+                .into_iter()
+                .map(BigUint::from)
+                .collect();
+            factors.push(BigUint::from(2u32));
+            factors.push(q);
+            factors.sort();
+            factors.dedup();
+
+            return Ok((p, factors));
+        }
+    }
+}
+
+// p = 2hq + 1 for random 'h' from the range that gives 'p' exactly 'bits' length
+// Returns p and h, None when no prime is found in 'bits * 4' attempts
+fn find_prime_with_cofactor<R: RngCore + CryptoRng>(q: &BigUint, bits: usize, rng: &mut R) -> Option<(BigUint, BigUint)> {
+    let p_min = BigUint::one() << (bits - 1);
+    let p_max = (BigUint::one() << bits) - 1u32;
+
+    let two_q = q << 1;
+    let h_min = Integer::div_ceil(&(&p_min - 1u32), &two_q);
+    let h_max = (&p_max - 1u32) / &two_q;
+    if h_min.is_zero() || h_min > h_max {
+        return None;
     }
 
-    // Generate 'p' value with 'bits' length
-    let mut prime: BigUintPrime = Zero::zero();
-    while prime.is_zero() || !Verification::is_prime(&prime) {
-        prime = Generator::new_prime(bits);
+    for _ in 0..bits * 4 {
+        let h = rng.gen_biguint_range(&h_min, &(&h_max + 1u32));
+        let p = &two_q * &h + 1u32;
+        if is_probable_prime(&p, rng) {
+            return Some((p, h));
+        }
     }
 
-    BigUint::from(prime.to_u64().unwrap())
+    None
 }
 
-fn generate_primitive_root(p: &BigUint) -> BigUint {
+// Safe prime p = 2q + 1 with 'bits' length, returns p and q
+// Candidates q, q + 2, ... from a random odd start are sieved: q and 2q + 1 must have no factor below 'SIEVE_BOUND'
+pub fn generate_safe_prime<R: RngCore + CryptoRng>(bits: usize, rng: &mut R) -> Result<(BigUint, BigUint), Error> {
+    if bits < 3 {
+        return Err(Error::InvalidArgument(format!("No safe prime with {} bits", bits)));
+    }
+
+    let q_bits = bits - 1;
+    // A small q must not be sieved out by itself
+    let sieve: Vec<u32> = sieve_primes(SIEVE_BOUND)
+        .into_iter()
+        .filter(|&prime| q_bits > 32 || u64::from(prime) < 1u64 << (q_bits - 1))
+        .collect();
     let one = BigUint::one();
-    let p_minus_one = p - &one;
+    let two = BigUint::from(2u32);
+
+    loop {
+        let mut start = rng.gen_biguint(q_bits as u64);
+        start.set_bit(q_bits as u64 - 1, true);
+        start.set_bit(0, true);
+        let residues: Vec<u64> = sieve.iter().map(|&prime| (&start % prime).to_u64().unwrap()).collect();
+
+        for offset in (0..SIEVE_WINDOW).step_by(2) {
+            let sieved_out = sieve.iter().zip(residues.iter()).any(|(&prime, &residue)| {
+                let prime = u64::from(prime);
+                let q_residue = (residue + u64::from(offset)) % prime;
+                q_residue == 0 || (2 * q_residue + 1) % prime == 0
+            });
+            if sieved_out {
+                continue;
+            }
+
+            let q = &start + offset;
+            if q.bits() != q_bits as u64 {
+                break;
+            }
+
+            // Cheap Fermat tests with base 2 first, Miller-Rabin only for the survivors
+            let p = (&q << 1) + 1u32;
+            if q > two && two.modpow(&(&q - 1u32), &q) != one {
+                continue;
+            }
+            if two.modpow(&(&p - 1u32), &p) != one {
+                continue;
+            }
+            if is_probable_prime(&q, rng) && is_probable_prime(&p, rng) {
+                return Ok((p, q));
+            }
+        }
+    }
+}
+
+// Schnorr group: primes p and q, where q has 'q_bits' bits and divides p - 1
+// g = h^((p - 1) / q) != 1 has order q, returns p, q, g
+pub fn generate_schnorr_group<R: RngCore + CryptoRng>(p_bits: usize, q_bits: usize, rng: &mut R) -> Result<(BigUint, BigUint, BigUint), Error> {
+    if q_bits < 2 || p_bits < q_bits + 2 {
+        return Err(Error::InvalidArgument(format!("No Schnorr group with {}-bit p and {}-bit q", p_bits, q_bits)));
+    }
 
-    let mut i = 0;
-    let mut g = BigUint::from(2u32);
-    let mut g_vec: Vec<BigUint> = Vec::new();
-    while g < p_minus_one {
-        if i == 5 {
-            break;
+    let (p, q) = loop {
+        let q = generate_prime_with_length(q_bits, rng);
+        if let Some((p, _)) = find_prime_with_cofactor(&q, p_bits, rng) {
+            break (p, q);
         }
+    };
 
-        if is_primitive_root(&g, &p) {
-            g_vec.push(g.clone());
-            i += 1
+    let one = BigUint::one();
+    let cofactor = (&p - 1u32) / &q;
+    loop {
+        let h = rng.gen_biguint_range(&BigUint::from(2u32), &(&p - 1u32));
+        let g = h.modpow(&cofactor, &p);
+        if g != one {
+            return Ok((p, q, g));
         }
+    }
+}
 
-        g += &one;
+// Odd primes below 'bound', sieve of Eratosthenes
+fn sieve_primes(bound: u32) -> Vec<u32> {
+    let mut composite = vec![false; bound as usize];
+    let mut primes = Vec::new();
+    for n in 2..bound {
+        if composite[n as usize] {
+            continue;
+        }
+        if n != 2 {
+            primes.push(n);
+        }
+        for multiple in (n * n..bound).step_by(n as usize) {
+            composite[multiple as usize] = true;
+        }
+    }
+
+    primes
+}
+
+// Random prime with 'bits' length: the top bit makes the length exact, the low bit makes it odd
+fn generate_prime_with_length<R: RngCore + CryptoRng>(bits: usize, rng: &mut R) -> BigUint {
+    let mut prime: BigUint = Zero::zero();
+    while prime.is_zero() || !is_probable_prime(&prime, rng) {
+        prime = rng.gen_biguint(bits as u64);
+        prime.set_bit(bits as u64 - 1, true);
+        prime.set_bit(0, true);
     }
 
-    if g_vec.len() == 0 {
-        panic!("Primitive root isn't found");
+    prime
+}
+
+// Distinct prime factors by trial division, 'n' fits 'MAX_COFACTOR_BITS'
+fn small_prime_factors(mut n: u64) -> Vec<u64> {
+    let mut factors = Vec::new();
+    let mut divisor = 2;
+    while divisor * divisor <= n {
+        if n.is_multiple_of(divisor) {
+            factors.push(divisor);
+            while n.is_multiple_of(divisor) {
+                n /= divisor;
+            }
+        }
+        divisor += 1;
+    }
+    if n > 1 {
+        factors.push(n);
     }
 
-    let g_rand = &g_vec[rand::thread_rng().gen_range(0..g_vec.len())];
-    g_rand.clone()
+    factors
 }
 
-fn is_primitive_root(g: &BigUint, p: &BigUint) -> bool {
+// Trial division by the small primes, then Miller-Rabin with random bases from 'rng'
+pub(crate) fn is_probable_prime<R: RngCore + CryptoRng>(n: &BigUint, rng: &mut R) -> bool {
     let one = BigUint::one();
+    let two = BigUint::from(2u32);
 
-    // Проверка, что g и p взаимно просты
-    if gcd(g.clone(), p.clone()) != one {
-        return false;
+    for small_prime in SMALL_PRIMES {
+        let small_prime = BigUint::from(small_prime);
+        if *n == small_prime {
+            return true;
+        }
+        if (n % &small_prime).is_zero() {
+            return false;
+        }
     }
-
-  
-    let p_minus_one = p - &one;
-    if g.modpow(&p_minus_one, p) != one {
+    if *n < two {
         return false;
     }
 
-    let mut exp = BigUint::one();
-    let mut res = BigUint::zero();
-    while exp < p.clone() {
-        res = g.modpow(&exp, p);
+    // n - 1 = d * 2^s, d is odd
+    let n_minus_one = n - &one;
+    let s = n_minus_one.trailing_zeros().unwrap_or(0);
+    let d = &n_minus_one >> s;
 
-        if res == one && exp != p_minus_one {
-            return false;
+    'witness: for _ in 0..MILLER_RABIN_ROUNDS {
+        let base = rng.gen_biguint_range(&two, &n_minus_one);
+        let mut x = base.modpow(&d, n);
+        if x == one || x == n_minus_one {
+            continue;
         }
 
-        exp += &one;
-    }
+        for _ in 1..s {
+            x = x.modpow(&two, n);
+            if x == n_minus_one {
+                continue 'witness;
+            }
+        }
 
-    res == one
-}
+        return false;
+    }
 
-fn generate_number_in_range(from: &usize, to: &usize) -> BigUint {
-    BigUint::from(rand::thread_rng().gen_range(*from..*to))
+    true
 }
 
+// Random candidates in [2, p - 1), 'factors' are the distinct prime factors of p - 1
+pub(crate) fn generate_primitive_root<R: RngCore + CryptoRng>(p: &BigUint, factors: &[BigUint], rng: &mut R) -> Result<BigUint, Error> {
+    let two = BigUint::from(2u32);
+    let p_minus_one = p - 1u32;
+    if p_minus_one <= two {
+        return Err(Error::PrimitiveRootNotFound);
+    }
 
-// Realization for Euclid's algorithm
-fn calculate_inverse_modulo(k: i64, p: i64) -> i64 {
-    let (_, x, _) = extended_euclidean_algorithm(k, p);
-
-    let result = (x % p + p) % p;
+    for _ in 0..PRIMITIVE_ROOT_ATTEMPTS {
+        let g = rng.gen_biguint_range(&two, &p_minus_one);
+        if is_primitive_root(&g, p, factors) {
+            return Ok(g);
+        }
+    }
 
-    result
+    Err(Error::PrimitiveRootNotFound)
 }
 
-fn extended_euclidean_algorithm(a: i64, b: i64) -> (i64, i64, i64) {
-    if b == 0 {
-        return (a, 1, 0);
-    }
-
-    let (d, x1, y1) = extended_euclidean_algorithm(b, a % b);
-    let x = y1;
-    let y = x1 - (a / b) * y1;
+// g has order p - 1 when g^((p - 1) / f) != 1 mod p for every prime factor f of p - 1
+fn is_primitive_root(g: &BigUint, p: &BigUint, factors: &[BigUint]) -> bool {
+    let one = BigUint::one();
+    let p_minus_one = p - &one;
 
-    (d, x, y)
+    factors.iter().all(|factor| g.modpow(&(&p_minus_one / factor), p) != one)
 }
 
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use crate::drbg::hash_drbg::HashDrbg;
+    use crate::sha2::sha2::Sha256Realization32;
+
+    // p, g, private key, public key, a, b, r, s for the seed 2020
+    const EXPECTED_SEEDED: [&str; 8] = ["39227", "33470", "25425", "31637", "37325", "33040", "27954", "23583"];
+    // p, g, private key, public key from the Hash_DRBG
+    const EXPECTED_DRBG: [&str; 4] = ["33941", "12310", "7747", "17803"];
 
     #[test]
     fn test_all() {
-        let hex_num = hex_to_number("1a2b".to_string());
+        let hex_num = hex_to_number("1a2b".to_string()).unwrap();
         println!("hex_num: {}", hex_num);
         println!("");
 
-        let (p, g, private_key, public_key) = genereate_keys(16, 16);
+        let (p, g, private_key, public_key) = genereate_keys(16, 16).unwrap();
 
         println!("p: {}", p);
         println!("g: {}", g);
@@ -191,7 +498,7 @@ mod tests {
         println!("");
 
       
-        let (a, b) = encode(&hex_num, &p, &g, &public_key);
+        let (a, b) = encode(&hex_num, &p, &g, &public_key).unwrap();
         println!("a: {}", a);
         println!("b: {}", b);
        
@@ -202,13 +509,13 @@ mod tests {
 
         assert_eq!(hex_num, m);
 
-        // let hex_num = BigInt::from(3u32);
-        // let p = BigInt::from(23u32);
-        // let g = BigInt::from(5u32);
-        // let private_key = BigInt::from(7u32);
-        // let public_key = BigInt::from(17u32);
+        // let hex_num = BigUint::from(3u32);
+        // let p = BigUint::from(23u32);
+        // let g = BigUint::from(5u32);
+        // let private_key = BigUint::from(7u32);
+        // let public_key = BigUint::from(17u32);
 
-        let (r, s) = sign(&hex_num, &p, &g, &private_key);
+        let (r, s) = sign(&hex_num, &p, &g, &private_key).unwrap();
         println!("r: {}", r);
         println!("s: {}", s);
         println!("");
@@ -216,10 +523,219 @@ mod tests {
         let verify_result = verify_sign(&hex_num, &p, &g, &r, &s, &public_key);
         assert_eq!(verify_result, true);
 
-        let r = BigInt::from(1u32);
+        let r = BigUint::from(1u32);
         let verify_result = verify_sign(&hex_num, &p, &g, &r, &s, &public_key);
         assert_eq!(verify_result, false)
     }
+
+    #[test]
+    fn test_errors() {
+        assert!(matches!(hex_to_number("1a2".to_string()), Err(Error::InvalidHex(_))));
+        assert!(matches!(hex_to_number("xyz0".to_string()), Err(Error::InvalidHex(_))));
+
+        // Candidates are [2, p - 1): none for 3, primitive roots 3 and 5 for 7
+        let mut rng = StdRng::seed_from_u64(7);
+        let factors = [BigUint::from(2u32), BigUint::from(3u32)];
+        assert_eq!(generate_primitive_root(&BigUint::from(3u32), &factors[..1], &mut rng), Err(Error::PrimitiveRootNotFound));
+        for _ in 0..10 {
+            let g = generate_primitive_root(&BigUint::from(7u32), &factors, &mut rng).unwrap();
+            assert!(g == BigUint::from(3u32) || g == BigUint::from(5u32));
+        }
+        // 2 and 4 have order 3 in Z_7*
+        assert!(!is_primitive_root(&BigUint::from(2u32), &BigUint::from(7u32), &factors));
+        assert!(!is_primitive_root(&BigUint::from(4u32), &BigUint::from(7u32), &factors));
+
+        // No length that is a multiple of 8 bits
+        assert!(matches!(genereate_keys_with_rng(9, 15, &mut rng), Err(Error::InvalidArgument(_))));
+        assert!(matches!(genereate_keys_with_rng(0, 7, &mut rng), Err(Error::InvalidArgument(_))));
+        assert!(matches!(genereate_keys_with_rng(16, 8, &mut rng), Err(Error::InvalidArgument(_))));
+
+        // Private keys >= order are reduced: x, x + (p - 1) and p - 1 itself decode like x and 0
+        let (p, g) = (BigUint::from(23u32), BigUint::from(5u32));
+        let order = BigUint::from(22u32);
+        let (x, m, k) = (BigUint::from(6u32), BigUint::from(10u32), BigUint::from(3u32));
+        let a = g.modpow(&k, &p);
+        let b = &m * g.modpow(&x, &p).modpow(&k, &p) % &p;
+        assert_eq!(decode(&a, &b, &p, &x), m);
+        assert_eq!(decode(&a, &b, &p, &(&x + &order)), m);
+        assert_eq!(decode_with_order(&a, &b, &p, &order, &(&x + &order * 3u32)), m);
+        assert_eq!(decode(&a, &m, &p, &order), m);
+
+        // Order 3 in Z_7*: the only nonce k = 2 gives s = 0 for x = 1 and m = 1, it's refused before signing
+        let (p, order, g) = (BigUint::from(7u32), BigUint::from(3u32), BigUint::from(2u32));
+        let one = BigUint::one();
+        assert!(matches!(sign_with_order(&one, &p, &order, &g, &one, &mut rng), Err(Error::InvalidArgument(_))));
+
+        // Orders without a nonce and messages out of (0, p) are errors instead of panics
+        let (p, g, y) = (BigUint::from(23u32), BigUint::from(5u32), BigUint::from(17u32));
+        let three = BigUint::from(3u32);
+        assert!(matches!(encode_with_rng(&one, &three, &BigUint::from(2u32), &BigUint::from(2u32), &mut rng), Err(Error::InvalidArgument(_))));
+        assert!(matches!(encode_with_order(&one, &p, &BigUint::from(2u32), &g, &y, &mut rng), Err(Error::InvalidArgument(_))));
+        assert!(matches!(encode_with_order(&one, &p, &p, &g, &y, &mut rng), Err(Error::InvalidArgument(_))));
+        for m in [BigUint::zero(), p.clone(), &p + 1u32] {
+            assert!(matches!(encode_with_rng(&m, &p, &g, &y, &mut rng), Err(Error::InvalidArgument(_))));
+            assert!(matches!(sign_with_rng(&m, &p, &g, &three, &mut rng), Err(Error::InvalidArgument(_))));
+        }
+    }
+
+    #[test]
+    fn test_probable_prime() {
+        let mut rng = StdRng::seed_from_u64(11);
+
+        for prime in [2u64, 3, 97, 101, 65521, 2147483647, 2305843009213693951] {
+            assert!(is_probable_prime(&BigUint::from(prime), &mut rng), "{}", prime);
+        }
+        // Carmichael numbers pass the Fermat test, but not Miller-Rabin
+        for composite in [0u64, 1, 91, 561, 1105, 62745, 4294967297, 2305843009213693953] {
+            assert!(!is_probable_prime(&BigUint::from(composite), &mut rng), "{}", composite);
+        }
+    }
+
+    #[test]
+    fn test_prime_structure() {
+        let mut rng = StdRng::seed_from_u64(5);
+
+        for bits in [8, 16, 64, 256] {
+            let (p, factors) = generate_prime_number(bits, bits, &mut rng).unwrap();
+            assert_eq!(p.bits(), bits as u64);
+            assert!(is_probable_prime(&p, &mut rng));
+
+            // p - 1 is the product of powers of the returned primes
+            let mut rest = &p - 1u32;
+            for factor in factors.iter() {
+                assert!(is_probable_prime(factor, &mut rng), "{}", factor);
+                assert!((&rest % factor).is_zero(), "{} does not divide p - 1", factor);
+                while (&rest % factor).is_zero() {
+                    rest /= factor;
+                }
+            }
+            assert!(rest.is_one());
+        }
+
+        assert_eq!(small_prime_factors(1), Vec::<u64>::new());
+        assert_eq!(small_prime_factors(360), vec![2, 3, 5]);
+        assert_eq!(small_prime_factors(4294967291), vec![4294967291]);
+    }
+
+    #[test]
+    fn test_2048_bit_keys() {
+        let mut rng = StdRng::seed_from_u64(2048);
+        let (p, g, private_key, public_key) = genereate_keys_with_rng(2048, 2048, &mut rng).unwrap();
+        assert_eq!(p.bits(), 2048);
+        assert_eq!(g.modpow(&private_key, &p), public_key);
+
+        // A message far above the old u32/u64 limits
+        let hex_num = hex_to_number(hex::encode([0xa5u8; 200])).unwrap();
+        let (a, b) = encode_with_rng(&hex_num, &p, &g, &public_key, &mut rng).unwrap();
+        assert_eq!(decode(&a, &b, &p, &private_key), hex_num);
+
+        let (r, s) = sign_with_rng(&hex_num, &p, &g, &private_key, &mut rng).unwrap();
+        assert!(verify_sign(&hex_num, &p, &g, &r, &s, &public_key));
+        assert!(!verify_sign(&(&hex_num + 1u32), &p, &g, &r, &s, &public_key));
+        assert!(!verify_sign(&hex_num, &p, &g, &r, &(&s + 1u32), &public_key));
+        assert!(!verify_sign(&hex_num, &p, &g, &p, &s, &public_key));
+    }
+
+    #[test]
+    fn test_safe_prime() {
+        let mut rng = StdRng::seed_from_u64(22);
+        assert_eq!(sieve_primes(30), vec![3, 5, 7, 11, 13, 17, 19, 23, 29]);
+
+        // Small lengths, compared with trial division
+        let is_prime = |n: u64| n > 1 && (2..n).take_while(|d| d * d <= n).all(|d| !n.is_multiple_of(d));
+        for bits in 3..=16 {
+            let (p, q) = generate_safe_prime(bits, &mut rng).unwrap();
+            let (p, q) = (p.to_u64().unwrap(), q.to_u64().unwrap());
+            assert_eq!(64 - p.leading_zeros() as usize, bits);
+            assert!(p == 2 * q + 1 && is_prime(p) && is_prime(q), "{} = 2 * {} + 1", p, q);
+        }
+
+        let (p, q) = generate_safe_prime(512, &mut rng).unwrap();
+        assert_eq!(p.bits(), 512);
+        assert_eq!(p, &q * 2u32 + 1u32);
+        assert!(is_probable_prime(&p, &mut rng) && is_probable_prime(&q, &mut rng));
+
+        assert!(matches!(generate_safe_prime(2, &mut rng), Err(Error::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn test_safe_prime_keys() {
+        let mut rng = StdRng::seed_from_u64(23);
+        let (p, g, private_key, public_key) = genereate_safe_prime_keys_with_rng(256, &mut rng).unwrap();
+        let q = (&p - 1u32) / 2u32;
+        let one = BigUint::one();
+        assert!(g.modpow(&BigUint::from(2u32), &p) != one && g.modpow(&q, &p) != one);
+        assert_eq!(g.modpow(&private_key, &p), public_key);
+
+        let hex_num = hex_to_number("1a2b".to_string()).unwrap();
+        let (a, b) = encode_with_rng(&hex_num, &p, &g, &public_key, &mut rng).unwrap();
+        assert_eq!(decode(&a, &b, &p, &private_key), hex_num);
+        let (r, s) = sign_with_rng(&hex_num, &p, &g, &private_key, &mut rng).unwrap();
+        assert!(verify_sign(&hex_num, &p, &g, &r, &s, &public_key));
+
+        // Order 2 (p - 1) and order q (a square) are not generators
+        let factors = [BigUint::from(2u32), q];
+        assert!(!is_primitive_root(&(&p - 1u32), &p, &factors));
+        assert!(!is_primitive_root(&g.modpow(&BigUint::from(2u32), &p), &p, &factors));
+    }
+
+    #[test]
+    fn test_schnorr_group() {
+        let mut rng = StdRng::seed_from_u64(24);
+        let (p, q, g, private_key, public_key) = genereate_schnorr_keys_with_rng(1024, &mut rng).unwrap();
+        assert_eq!(p.bits(), 1024);
+        assert_eq!(q.bits(), SCHNORR_Q_BITS as u64);
+        assert!(is_probable_prime(&p, &mut rng) && is_probable_prime(&q, &mut rng));
+        assert!(((&p - 1u32) % &q).is_zero());
+        assert!(!g.is_one() && g.modpow(&q, &p).is_one());
+        assert!(private_key < q);
+        assert_eq!(g.modpow(&private_key, &p), public_key);
+
+        // Exponents are below q, 256 bits instead of 1024
+        let hex_num = hex_to_number(hex::encode("Hello World!")).unwrap();
+        let (a, b) = encode_with_order(&hex_num, &p, &q, &g, &public_key, &mut rng).unwrap();
+        assert_eq!(decode_with_order(&a, &b, &p, &q, &private_key), hex_num);
+        assert_eq!(decode(&a, &b, &p, &private_key), hex_num);
+
+        let (r, s) = sign_with_order(&hex_num, &p, &q, &g, &private_key, &mut rng).unwrap();
+        assert!(s < q);
+        assert!(verify_sign(&hex_num, &p, &g, &r, &s, &public_key));
+        assert!(!verify_sign(&(&hex_num + 1u32), &p, &g, &r, &s, &public_key));
+
+        assert!(matches!(generate_schnorr_group(257, 256, &mut rng), Err(Error::InvalidArgument(_))));
+        assert!(matches!(generate_schnorr_group(64, 1, &mut rng), Err(Error::InvalidArgument(_))));
+        let (p, q, g) = generate_schnorr_group(64, 16, &mut rng).unwrap();
+        assert!(((&p - 1u32) % &q).is_zero() && g.modpow(&q, &p).is_one());
+    }
+
+    #[test]
+    fn test_seeded_rng() {
+        // The same seed gives the same keys, ciphertext and signature
+        let hex_num = hex_to_number("1a2b".to_string()).unwrap();
+        let run = |seed: u64| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let (p, g, private_key, public_key) = genereate_keys_with_rng(16, 16, &mut rng).unwrap();
+            let ciphertext = encode_with_rng(&hex_num, &p, &g, &public_key, &mut rng).unwrap();
+            let signature = sign_with_rng(&hex_num, &p, &g, &private_key, &mut rng).unwrap();
+            ((p, g, private_key, public_key), ciphertext, signature)
+        };
+
+        let ((p, g, private_key, public_key), (a, b), (r, s)) = run(2020);
+        assert_eq!(run(2020), ((p.clone(), g.clone(), private_key.clone(), public_key.clone()), (a.clone(), b.clone()), (r.clone(), s.clone())));
+        assert_eq!(
+            [&p, &g, &private_key, &public_key, &a, &b, &r, &s].map(|value| value.to_string()),
+            EXPECTED_SEEDED
+        );
+
+        assert_eq!(decode(&a, &b, &p, &private_key), hex_num);
+        assert!(verify_sign(&hex_num, &p, &g, &r, &s, &public_key));
+        assert_ne!(run(2021).0, (p, g, private_key, public_key));
+
+        // Deterministic DRBG as the source
+        let mut drbg = HashDrbg::<Sha256Realization32>::instantiate(&[0x42; 32], &[0x24; 16], b"elgamal").unwrap();
+        let (p, g, private_key, public_key) = genereate_keys_with_rng(16, 16, &mut drbg).unwrap();
+        assert_eq!([&p, &g, &private_key, &public_key].map(|value| value.to_string()), EXPECTED_DRBG);
+    }
 }
 
 // cargo test -- elgamal --nocapture
\ No newline at end of file
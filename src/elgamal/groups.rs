@@ -0,0 +1,548 @@
+use std::fmt;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use num_bigint::{BigUint, RandBigInt};
+use num_traits::Num;
+use rand::{CryptoRng, RngCore};
+use rand::rngs::OsRng;
+use crate::elgamal::elgamal::{decode_with_order, encode_with_order, sign_with_order, verify_sign};
+use crate::error::Error;
+
+// Parsed p and q of every group, in the order of 'GroupId::ALL', filled on the first use
+static GROUP_PARAMETERS: [OnceLock<(BigUint, BigUint)>; 10] = [const { OnceLock::new() }; 10];
+
+// Named finite-field groups of RFC 3526 and RFC 7919
+// Every p = 2q + 1 is a safe prime and g = 2 generates the subgroup of prime order q
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GroupId {
+    Modp2048,
+    Modp3072,
+    Modp4096,
+    Modp6144,
+    Modp8192,
+    Ffdhe2048,
+    Ffdhe3072,
+    Ffdhe4096,
+    Ffdhe6144,
+    Ffdhe8192,
+}
+
+impl GroupId {
+    pub const ALL: [GroupId; 10] = [
+        GroupId::Modp2048,
+        GroupId::Modp3072,
+        GroupId::Modp4096,
+        GroupId::Modp6144,
+        GroupId::Modp8192,
+        GroupId::Ffdhe2048,
+        GroupId::Ffdhe3072,
+        GroupId::Ffdhe4096,
+        GroupId::Ffdhe6144,
+        GroupId::Ffdhe8192,
+    ];
+
+    // Group names as in OpenSSL
+    pub fn name(&self) -> &'static str {
+        match self {
+            GroupId::Modp2048 => "modp_2048",
+            GroupId::Modp3072 => "modp_3072",
+            GroupId::Modp4096 => "modp_4096",
+            GroupId::Modp6144 => "modp_6144",
+            GroupId::Modp8192 => "modp_8192",
+            GroupId::Ffdhe2048 => "ffdhe2048",
+            GroupId::Ffdhe3072 => "ffdhe3072",
+            GroupId::Ffdhe4096 => "ffdhe4096",
+            GroupId::Ffdhe6144 => "ffdhe6144",
+            GroupId::Ffdhe8192 => "ffdhe8192",
+        }
+    }
+
+    pub fn bits(&self) -> usize {
+        match self {
+            GroupId::Modp2048 | GroupId::Ffdhe2048 => 2048,
+            GroupId::Modp3072 | GroupId::Ffdhe3072 => 3072,
+            GroupId::Modp4096 | GroupId::Ffdhe4096 => 4096,
+            GroupId::Modp6144 | GroupId::Ffdhe6144 => 6144,
+            GroupId::Modp8192 | GroupId::Ffdhe8192 => 8192,
+        }
+    }
+
+    pub fn prime(&self) -> &'static BigUint {
+        &self.parameters().0
+    }
+
+    pub fn generator(&self) -> BigUint {
+        BigUint::from(2u32)
+    }
+
+    // q = (p - 1) / 2, the order of g
+    pub fn order(&self) -> &'static BigUint {
+        &self.parameters().1
+    }
+
+    // The hex constant is parsed once per group, the values are shared by all calls
+    fn parameters(&self) -> &'static (BigUint, BigUint) {
+        GROUP_PARAMETERS[*self as usize].get_or_init(|| {
+            let hex: String = self.prime_hex().chars().filter(|c| !c.is_whitespace()).collect();
+            let p = BigUint::from_str_radix(&hex, 16).unwrap();
+            let q = (&p - 1u32) >> 1;
+            (p, q)
+        })
+    }
+
+    fn prime_hex(&self) -> &'static str {
+        match self {
+            GroupId::Modp2048 => MODP_2048,
+            GroupId::Modp3072 => MODP_3072,
+            GroupId::Modp4096 => MODP_4096,
+            GroupId::Modp6144 => MODP_6144,
+            GroupId::Modp8192 => MODP_8192,
+            GroupId::Ffdhe2048 => FFDHE_2048,
+            GroupId::Ffdhe3072 => FFDHE_3072,
+            GroupId::Ffdhe4096 => FFDHE_4096,
+            GroupId::Ffdhe6144 => FFDHE_6144,
+            GroupId::Ffdhe8192 => FFDHE_8192,
+        }
+    }
+
+    // Returns private key, public key: x = [2, q), y = g^x mod p
+    pub fn generate_keys(&self) -> (BigUint, BigUint) {
+        self.generate_keys_with_rng(&mut OsRng)
+    }
+
+    pub fn generate_keys_with_rng<R: RngCore + CryptoRng>(&self, rng: &mut R) -> (BigUint, BigUint) {
+        let private_key = rng.gen_biguint_range(&BigUint::from(2u32), self.order());
+        let public_key = self.generator().modpow(&private_key, self.prime());
+
+        (private_key, public_key)
+    }
+
+    pub fn encode(&self, hex_num: &BigUint, public_key: &BigUint) -> Result<(BigUint, BigUint), Error> {
+        self.encode_with_rng(hex_num, public_key, &mut OsRng)
+    }
+
+    pub fn encode_with_rng<R: RngCore + CryptoRng>(&self, hex_num: &BigUint, public_key: &BigUint, rng: &mut R) -> Result<(BigUint, BigUint), Error> {
+        encode_with_order(hex_num, self.prime(), self.order(), &self.generator(), public_key, rng)
+    }
+
+    pub fn decode(&self, a: &BigUint, b: &BigUint, private_key: &BigUint) -> BigUint {
+        decode_with_order(a, b, self.prime(), self.order(), private_key)
+    }
+
+    pub fn sign(&self, hex_num: &BigUint, private_key: &BigUint) -> Result<(BigUint, BigUint), Error> {
+        self.sign_with_rng(hex_num, private_key, &mut OsRng)
+    }
+
+    pub fn sign_with_rng<R: RngCore + CryptoRng>(&self, hex_num: &BigUint, private_key: &BigUint, rng: &mut R) -> Result<(BigUint, BigUint), Error> {
+        sign_with_order(hex_num, self.prime(), self.order(), &self.generator(), private_key, rng)
+    }
+
+    pub fn verify_sign(&self, hex_num: &BigUint, r: &BigUint, s: &BigUint, public_key: &BigUint) -> bool {
+        verify_sign(hex_num, self.prime(), &self.generator(), r, s, public_key)
+    }
+}
+
+impl fmt::Display for GroupId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for GroupId {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        GroupId::ALL
+            .into_iter()
+            .find(|group| group.name() == name)
+            .ok_or_else(|| Error::InvalidArgument(format!("Unknown group: {}", name)))
+    }
+}
+
+// RFC 3526 group 14, 2048-bit MODP: 2^2048 - 2^1984 - 1 + 2^64 * ([2^1918 pi] + X)
+const MODP_2048: &str = "
+    FFFFFFFF FFFFFFFF C90FDAA2 2168C234 C4C6628B 80DC1CD1
+    29024E08 8A67CC74 020BBEA6 3B139B22 514A0879 8E3404DD
+    EF9519B3 CD3A431B 302B0A6D F25F1437 4FE1356D 6D51C245
+    E485B576 625E7EC6 F44C42E9 A637ED6B 0BFF5CB6 F406B7ED
+    EE386BFB 5A899FA5 AE9F2411 7C4B1FE6 49286651 ECE45B3D
+    C2007CB8 A163BF05 98DA4836 1C55D39A 69163FA8 FD24CF5F
+    83655D23 DCA3AD96 1C62F356 208552BB 9ED52907 7096966D
+    670C354E 4ABC9804 F1746C08 CA18217C 32905E46 2E36CE3B
+    E39E772C 180E8603 9B2783A2 EC07A28F B5C55DF0 6F4C52C9
+    DE2BCBF6 95581718 3995497C EA956AE5 15D22618 98FA0510
+    15728E5A 8AACAA68 FFFFFFFF FFFFFFFF";
+
+// RFC 3526 group 15, 3072-bit MODP: 2^3072 - 2^3008 - 1 + 2^64 * ([2^2942 pi] + X)
+const MODP_3072: &str = "
+    FFFFFFFF FFFFFFFF C90FDAA2 2168C234 C4C6628B 80DC1CD1
+    29024E08 8A67CC74 020BBEA6 3B139B22 514A0879 8E3404DD
+    EF9519B3 CD3A431B 302B0A6D F25F1437 4FE1356D 6D51C245
+    E485B576 625E7EC6 F44C42E9 A637ED6B 0BFF5CB6 F406B7ED
+    EE386BFB 5A899FA5 AE9F2411 7C4B1FE6 49286651 ECE45B3D
+    C2007CB8 A163BF05 98DA4836 1C55D39A 69163FA8 FD24CF5F
+    83655D23 DCA3AD96 1C62F356 208552BB 9ED52907 7096966D
+    670C354E 4ABC9804 F1746C08 CA18217C 32905E46 2E36CE3B
+    E39E772C 180E8603 9B2783A2 EC07A28F B5C55DF0 6F4C52C9
+    DE2BCBF6 95581718 3995497C EA956AE5 15D22618 98FA0510
+    15728E5A 8AAAC42D AD33170D 04507A33 A85521AB DF1CBA64
+    ECFB8504 58DBEF0A 8AEA7157 5D060C7D B3970F85 A6E1E4C7
+    ABF5AE8C DB0933D7 1E8C94E0 4A25619D CEE3D226 1AD2EE6B
+    F12FFA06 D98A0864 D8760273 3EC86A64 521F2B18 177B200C
+    BBE11757 7A615D6C 770988C0 BAD946E2 08E24FA0 74E5AB31
+    43DB5BFC E0FD108E 4B82D120 A93AD2CA FFFFFFFF FFFFFFFF";
+
+// RFC 3526 group 16, 4096-bit MODP: 2^4096 - 2^4032 - 1 + 2^64 * ([2^3966 pi] + X)
+const MODP_4096: &str = "
+    FFFFFFFF FFFFFFFF C90FDAA2 2168C234 C4C6628B 80DC1CD1
+    29024E08 8A67CC74 020BBEA6 3B139B22 514A0879 8E3404DD
+    EF9519B3 CD3A431B 302B0A6D F25F1437 4FE1356D 6D51C245
+    E485B576 625E7EC6 F44C42E9 A637ED6B 0BFF5CB6 F406B7ED
+    EE386BFB 5A899FA5 AE9F2411 7C4B1FE6 49286651 ECE45B3D
+    C2007CB8 A163BF05 98DA4836 1C55D39A 69163FA8 FD24CF5F
+    83655D23 DCA3AD96 1C62F356 208552BB 9ED52907 7096966D
+    670C354E 4ABC9804 F1746C08 CA18217C 32905E46 2E36CE3B
+    E39E772C 180E8603 9B2783A2 EC07A28F B5C55DF0 6F4C52C9
+    DE2BCBF6 95581718 3995497C EA956AE5 15D22618 98FA0510
+    15728E5A 8AAAC42D AD33170D 04507A33 A85521AB DF1CBA64
+    ECFB8504 58DBEF0A 8AEA7157 5D060C7D B3970F85 A6E1E4C7
+    ABF5AE8C DB0933D7 1E8C94E0 4A25619D CEE3D226 1AD2EE6B
+    F12FFA06 D98A0864 D8760273 3EC86A64 521F2B18 177B200C
+    BBE11757 7A615D6C 770988C0 BAD946E2 08E24FA0 74E5AB31
+    43DB5BFC E0FD108E 4B82D120 A9210801 1A723C12 A787E6D7
+    88719A10 BDBA5B26 99C32718 6AF4E23C 1A946834 B6150BDA
+    2583E9CA 2AD44CE8 DBBBC2DB 04DE8EF9 2E8EFC14 1FBECAA6
+    287C5947 4E6BC05D 99B2964F A090C3A2 233BA186 515BE7ED
+    1F612970 CEE2D7AF B81BDD76 2170481C D0069127 D5B05AA9
+    93B4EA98 8D8FDDC1 86FFB7DC 90A6C08F 4DF435C9 34063199
+    FFFFFFFF FFFFFFFF";
+
+// RFC 3526 group 17, 6144-bit MODP: 2^6144 - 2^6080 - 1 + 2^64 * ([2^6014 pi] + X)
+const MODP_6144: &str = "
+    FFFFFFFF FFFFFFFF C90FDAA2 2168C234 C4C6628B 80DC1CD1
+    29024E08 8A67CC74 020BBEA6 3B139B22 514A0879 8E3404DD
+    EF9519B3 CD3A431B 302B0A6D F25F1437 4FE1356D 6D51C245
+    E485B576 625E7EC6 F44C42E9 A637ED6B 0BFF5CB6 F406B7ED
+    EE386BFB 5A899FA5 AE9F2411 7C4B1FE6 49286651 ECE45B3D
+    C2007CB8 A163BF05 98DA4836 1C55D39A 69163FA8 FD24CF5F
+    83655D23 DCA3AD96 1C62F356 208552BB 9ED52907 7096966D
+    670C354E 4ABC9804 F1746C08 CA18217C 32905E46 2E36CE3B
+    E39E772C 180E8603 9B2783A2 EC07A28F B5C55DF0 6F4C52C9
+    DE2BCBF6 95581718 3995497C EA956AE5 15D22618 98FA0510
+    15728E5A 8AAAC42D AD33170D 04507A33 A85521AB DF1CBA64
+    ECFB8504 58DBEF0A 8AEA7157 5D060C7D B3970F85 A6E1E4C7
+    ABF5AE8C DB0933D7 1E8C94E0 4A25619D CEE3D226 1AD2EE6B
+    F12FFA06 D98A0864 D8760273 3EC86A64 521F2B18 177B200C
+    BBE11757 7A615D6C 770988C0 BAD946E2 08E24FA0 74E5AB31
+    43DB5BFC E0FD108E 4B82D120 A9210801 1A723C12 A787E6D7
+    88719A10 BDBA5B26 99C32718 6AF4E23C 1A946834 B6150BDA
+    2583E9CA 2AD44CE8 DBBBC2DB 04DE8EF9 2E8EFC14 1FBECAA6
+    287C5947 4E6BC05D 99B2964F A090C3A2 233BA186 515BE7ED
+    1F612970 CEE2D7AF B81BDD76 2170481C D0069127 D5B05AA9
+    93B4EA98 8D8FDDC1 86FFB7DC 90A6C08F 4DF435C9 34028492
+    36C3FAB4 D27C7026 C1D4DCB2 602646DE C9751E76 3DBA37BD
+    F8FF9406 AD9E530E E5DB382F 413001AE B06A53ED 9027D831
+    179727B0 865A8918 DA3EDBEB CF9B14ED 44CE6CBA CED4BB1B
+    DB7F1447 E6CC254B 33205151 2BD7AF42 6FB8F401 378CD2BF
+    5983CA01 C64B92EC F032EA15 D1721D03 F482D7CE 6E74FEF6
+    D55E702F 46980C82 B5A84031 900B1C9E 59E7C97F BEC7E8F3
+    23A97A7E 36CC88BE 0F1D45B7 FF585AC5 4BD407B2 2B4154AA
+    CC8F6D7E BF48E1D8 14CC5ED2 0F8037E0 A79715EE F29BE328
+    06A1D58B B7C5DA76 F550AA3D 8A1FBFF0 EB19CCB1 A313D55C
+    DA56C9EC 2EF29632 387FE8D7 6E3C0468 043E8F66 3F4860EE
+    12BF2D5B 0B7474D6 E694F91E 6DCC4024 FFFFFFFF FFFFFFFF";
+
+// RFC 3526 group 18, 8192-bit MODP: 2^8192 - 2^8128 - 1 + 2^64 * ([2^8062 pi] + X)
+const MODP_8192: &str = "
+    FFFFFFFF FFFFFFFF C90FDAA2 2168C234 C4C6628B 80DC1CD1
+    29024E08 8A67CC74 020BBEA6 3B139B22 514A0879 8E3404DD
+    EF9519B3 CD3A431B 302B0A6D F25F1437 4FE1356D 6D51C245
+    E485B576 625E7EC6 F44C42E9 A637ED6B 0BFF5CB6 F406B7ED
+    EE386BFB 5A899FA5 AE9F2411 7C4B1FE6 49286651 ECE45B3D
+    C2007CB8 A163BF05 98DA4836 1C55D39A 69163FA8 FD24CF5F
+    83655D23 DCA3AD96 1C62F356 208552BB 9ED52907 7096966D
+    670C354E 4ABC9804 F1746C08 CA18217C 32905E46 2E36CE3B
+    E39E772C 180E8603 9B2783A2 EC07A28F B5C55DF0 6F4C52C9
+    DE2BCBF6 95581718 3995497C EA956AE5 15D22618 98FA0510
+    15728E5A 8AAAC42D AD33170D 04507A33 A85521AB DF1CBA64
+    ECFB8504 58DBEF0A 8AEA7157 5D060C7D B3970F85 A6E1E4C7
+    ABF5AE8C DB0933D7 1E8C94E0 4A25619D CEE3D226 1AD2EE6B
+    F12FFA06 D98A0864 D8760273 3EC86A64 521F2B18 177B200C
+    BBE11757 7A615D6C 770988C0 BAD946E2 08E24FA0 74E5AB31
+    43DB5BFC E0FD108E 4B82D120 A9210801 1A723C12 A787E6D7
+    88719A10 BDBA5B26 99C32718 6AF4E23C 1A946834 B6150BDA
+    2583E9CA 2AD44CE8 DBBBC2DB 04DE8EF9 2E8EFC14 1FBECAA6
+    287C5947 4E6BC05D 99B2964F A090C3A2 233BA186 515BE7ED
+    1F612970 CEE2D7AF B81BDD76 2170481C D0069127 D5B05AA9
+    93B4EA98 8D8FDDC1 86FFB7DC 90A6C08F 4DF435C9 34028492
+    36C3FAB4 D27C7026 C1D4DCB2 602646DE C9751E76 3DBA37BD
+    F8FF9406 AD9E530E E5DB382F 413001AE B06A53ED 9027D831
+    179727B0 865A8918 DA3EDBEB CF9B14ED 44CE6CBA CED4BB1B
+    DB7F1447 E6CC254B 33205151 2BD7AF42 6FB8F401 378CD2BF
+    5983CA01 C64B92EC F032EA15 D1721D03 F482D7CE 6E74FEF6
+    D55E702F 46980C82 B5A84031 900B1C9E 59E7C97F BEC7E8F3
+    23A97A7E 36CC88BE 0F1D45B7 FF585AC5 4BD407B2 2B4154AA
+    CC8F6D7E BF48E1D8 14CC5ED2 0F8037E0 A79715EE F29BE328
+    06A1D58B B7C5DA76 F550AA3D 8A1FBFF0 EB19CCB1 A313D55C
+    DA56C9EC 2EF29632 387FE8D7 6E3C0468 043E8F66 3F4860EE
+    12BF2D5B 0B7474D6 E694F91E 6DBE1159 74A3926F 12FEE5E4
+    38777CB6 A932DF8C D8BEC4D0 73B931BA 3BC832B6 8D9DD300
+    741FA7BF 8AFC47ED 2576F693 6BA42466 3AAB639C 5AE4F568
+    3423B474 2BF1C978 238F16CB E39D652D E3FDB8BE FC848AD9
+    22222E04 A4037C07 13EB57A8 1A23F0C7 3473FC64 6CEA306B
+    4BCBC886 2F8385DD FA9D4B7F A2C087E8 79683303 ED5BDD3A
+    062B3CF5 B3A278A6 6D2A13F8 3F44F82D DF310EE0 74AB6A36
+    4597E899 A0255DC1 64F31CC5 0846851D F9AB4819 5DED7EA1
+    B1D510BD 7EE74D73 FAF36BC3 1ECFA268 359046F4 EB879F92
+    4009438B 481C6CD7 889A002E D5EE382B C9190DA6 FC026E47
+    9558E447 5677E9AA 9E3050E2 765694DF C81F56E8 80B96E71
+    60C980DD 98EDD3DF FFFFFFFF FFFFFFFF";
+
+// RFC 7919 ffdhe2048: 2^2048 - 2^1984 + ([2^1918 e] + X) * 2^64 - 1
+const FFDHE_2048: &str = "
+    FFFFFFFF FFFFFFFF ADF85458 A2BB4A9A AFDC5620 273D3CF1
+    D8B9C583 CE2D3695 A9E13641 146433FB CC939DCE 249B3EF9
+    7D2FE363 630C75D8 F681B202 AEC4617A D3DF1ED5 D5FD6561
+    2433F51F 5F066ED0 85636555 3DED1AF3 B557135E 7F57C935
+    984F0C70 E0E68B77 E2A689DA F3EFE872 1DF158A1 36ADE735
+    30ACCA4F 483A797A BC0AB182 B324FB61 D108A94B B2C8E3FB
+    B96ADAB7 60D7F468 1D4F42A3 DE394DF4 AE56EDE7 6372BB19
+    0B07A7C8 EE0A6D70 9E02FCE1 CDF7E2EC C03404CD 28342F61
+    9172FE9C E98583FF 8E4F1232 EEF28183 C3FE3B1B 4C6FAD73
+    3BB5FCBC 2EC22005 C58EF183 7D1683B2 C6F34A26 C1B2EFFA
+    886B4238 61285C97 FFFFFFFF FFFFFFFF";
+
+// RFC 7919 ffdhe3072: 2^3072 - 2^3008 + ([2^2942 e] + X) * 2^64 - 1
+const FFDHE_3072: &str = "
+    FFFFFFFF FFFFFFFF ADF85458 A2BB4A9A AFDC5620 273D3CF1
+    D8B9C583 CE2D3695 A9E13641 146433FB CC939DCE 249B3EF9
+    7D2FE363 630C75D8 F681B202 AEC4617A D3DF1ED5 D5FD6561
+    2433F51F 5F066ED0 85636555 3DED1AF3 B557135E 7F57C935
+    984F0C70 E0E68B77 E2A689DA F3EFE872 1DF158A1 36ADE735
+    30ACCA4F 483A797A BC0AB182 B324FB61 D108A94B B2C8E3FB
+    B96ADAB7 60D7F468 1D4F42A3 DE394DF4 AE56EDE7 6372BB19
+    0B07A7C8 EE0A6D70 9E02FCE1 CDF7E2EC C03404CD 28342F61
+    9172FE9C E98583FF 8E4F1232 EEF28183 C3FE3B1B 4C6FAD73
+    3BB5FCBC 2EC22005 C58EF183 7D1683B2 C6F34A26 C1B2EFFA
+    886B4238 611FCFDC DE355B3B 6519035B BC34F4DE F99C0238
+    61B46FC9 D6E6C907 7AD91D26 91F7F7EE 598CB0FA C186D91C
+    AEFE1309 85139270 B4130C93 BC437944 F4FD4452 E2D74DD3
+    64F2E21E 71F54BFF 5CAE82AB 9C9DF69E E86D2BC5 22363A0D
+    ABC52197 9B0DEADA 1DBF9A42 D5C4484E 0ABCD06B FA53DDEF
+    3C1B20EE 3FD59D7C 25E41D2B 66C62E37 FFFFFFFF FFFFFFFF";
+
+// RFC 7919 ffdhe4096: 2^4096 - 2^4032 + ([2^3966 e] + X) * 2^64 - 1
+const FFDHE_4096: &str = "
+    FFFFFFFF FFFFFFFF ADF85458 A2BB4A9A AFDC5620 273D3CF1
+    D8B9C583 CE2D3695 A9E13641 146433FB CC939DCE 249B3EF9
+    7D2FE363 630C75D8 F681B202 AEC4617A D3DF1ED5 D5FD6561
+    2433F51F 5F066ED0 85636555 3DED1AF3 B557135E 7F57C935
+    984F0C70 E0E68B77 E2A689DA F3EFE872 1DF158A1 36ADE735
+    30ACCA4F 483A797A BC0AB182 B324FB61 D108A94B B2C8E3FB
+    B96ADAB7 60D7F468 1D4F42A3 DE394DF4 AE56EDE7 6372BB19
+    0B07A7C8 EE0A6D70 9E02FCE1 CDF7E2EC C03404CD 28342F61
+    9172FE9C E98583FF 8E4F1232 EEF28183 C3FE3B1B 4C6FAD73
+    3BB5FCBC 2EC22005 C58EF183 7D1683B2 C6F34A26 C1B2EFFA
+    886B4238 611FCFDC DE355B3B 6519035B BC34F4DE F99C0238
+    61B46FC9 D6E6C907 7AD91D26 91F7F7EE 598CB0FA C186D91C
+    AEFE1309 85139270 B4130C93 BC437944 F4FD4452 E2D74DD3
+    64F2E21E 71F54BFF 5CAE82AB 9C9DF69E E86D2BC5 22363A0D
+    ABC52197 9B0DEADA 1DBF9A42 D5C4484E 0ABCD06B FA53DDEF
+    3C1B20EE 3FD59D7C 25E41D2B 669E1EF1 6E6F52C3 164DF4FB
+    7930E9E4 E58857B6 AC7D5F42 D69F6D18 7763CF1D 55034004
+    87F55BA5 7E31CC7A 7135C886 EFB4318A ED6A1E01 2D9E6832
+    A907600A 918130C4 6DC778F9 71AD0038 092999A3 33CB8B7A
+    1A1DB93D 7140003C 2A4ECEA9 F98D0ACC 0A8291CD CEC97DCF
+    8EC9B55A 7F88A46B 4DB5A851 F44182E1 C68A007E 5E655F6A
+    FFFFFFFF FFFFFFFF";
+
+// RFC 7919 ffdhe6144: 2^6144 - 2^6080 + ([2^6014 e] + X) * 2^64 - 1
+const FFDHE_6144: &str = "
+    FFFFFFFF FFFFFFFF ADF85458 A2BB4A9A AFDC5620 273D3CF1
+    D8B9C583 CE2D3695 A9E13641 146433FB CC939DCE 249B3EF9
+    7D2FE363 630C75D8 F681B202 AEC4617A D3DF1ED5 D5FD6561
+    2433F51F 5F066ED0 85636555 3DED1AF3 B557135E 7F57C935
+    984F0C70 E0E68B77 E2A689DA F3EFE872 1DF158A1 36ADE735
+    30ACCA4F 483A797A BC0AB182 B324FB61 D108A94B B2C8E3FB
+    B96ADAB7 60D7F468 1D4F42A3 DE394DF4 AE56EDE7 6372BB19
+    0B07A7C8 EE0A6D70 9E02FCE1 CDF7E2EC C03404CD 28342F61
+    9172FE9C E98583FF 8E4F1232 EEF28183 C3FE3B1B 4C6FAD73
+    3BB5FCBC 2EC22005 C58EF183 7D1683B2 C6F34A26 C1B2EFFA
+    886B4238 611FCFDC DE355B3B 6519035B BC34F4DE F99C0238
+    61B46FC9 D6E6C907 7AD91D26 91F7F7EE 598CB0FA C186D91C
+    AEFE1309 85139270 B4130C93 BC437944 F4FD4452 E2D74DD3
+    64F2E21E 71F54BFF 5CAE82AB 9C9DF69E E86D2BC5 22363A0D
+    ABC52197 9B0DEADA 1DBF9A42 D5C4484E 0ABCD06B FA53DDEF
+    3C1B20EE 3FD59D7C 25E41D2B 669E1EF1 6E6F52C3 164DF4FB
+    7930E9E4 E58857B6 AC7D5F42 D69F6D18 7763CF1D 55034004
+    87F55BA5 7E31CC7A 7135C886 EFB4318A ED6A1E01 2D9E6832
+    A907600A 918130C4 6DC778F9 71AD0038 092999A3 33CB8B7A
+    1A1DB93D 7140003C 2A4ECEA9 F98D0ACC 0A8291CD CEC97DCF
+    8EC9B55A 7F88A46B 4DB5A851 F44182E1 C68A007E 5E0DD902
+    0BFD64B6 45036C7A 4E677D2C 38532A3A 23BA4442 CAF53EA6
+    3BB45432 9B7624C8 917BDD64 B1C0FD4C B38E8C33 4C701C3A
+    CDAD0657 FCCFEC71 9B1F5C3E 4E46041F 388147FB 4CFDB477
+    A52471F7 A9A96910 B855322E DB6340D8 A00EF092 350511E3
+    0ABEC1FF F9E3A26E 7FB29F8C 183023C3 587E38DA 0077D9B4
+    763E4E4B 94B2BBC1 94C6651E 77CAF992 EEAAC023 2A281BF6
+    B3A739C1 22611682 0AE8DB58 47A67CBE F9C9091B 462D538C
+    D72B0374 6AE77F5E 62292C31 1562A846 505DC82D B854338A
+    E49F5235 C95B9117 8CCF2DD5 CACEF403 EC9D1810 C6272B04
+    5B3B71F9 DC6B80D6 3FDD4A8E 9ADB1E69 62A69526 D43161C1
+    A41D570D 7938DAD4 A40E329C D0E40E65 FFFFFFFF FFFFFFFF";
+
+// RFC 7919 ffdhe8192: 2^8192 - 2^8128 + ([2^8062 e] + X) * 2^64 - 1
+const FFDHE_8192: &str = "
+    FFFFFFFF FFFFFFFF ADF85458 A2BB4A9A AFDC5620 273D3CF1
+    D8B9C583 CE2D3695 A9E13641 146433FB CC939DCE 249B3EF9
+    7D2FE363 630C75D8 F681B202 AEC4617A D3DF1ED5 D5FD6561
+    2433F51F 5F066ED0 85636555 3DED1AF3 B557135E 7F57C935
+    984F0C70 E0E68B77 E2A689DA F3EFE872 1DF158A1 36ADE735
+    30ACCA4F 483A797A BC0AB182 B324FB61 D108A94B B2C8E3FB
+    B96ADAB7 60D7F468 1D4F42A3 DE394DF4 AE56EDE7 6372BB19
+    0B07A7C8 EE0A6D70 9E02FCE1 CDF7E2EC C03404CD 28342F61
+    9172FE9C E98583FF 8E4F1232 EEF28183 C3FE3B1B 4C6FAD73
+    3BB5FCBC 2EC22005 C58EF183 7D1683B2 C6F34A26 C1B2EFFA
+    886B4238 611FCFDC DE355B3B 6519035B BC34F4DE F99C0238
+    61B46FC9 D6E6C907 7AD91D26 91F7F7EE 598CB0FA C186D91C
+    AEFE1309 85139270 B4130C93 BC437944 F4FD4452 E2D74DD3
+    64F2E21E 71F54BFF 5CAE82AB 9C9DF69E E86D2BC5 22363A0D
+    ABC52197 9B0DEADA 1DBF9A42 D5C4484E 0ABCD06B FA53DDEF
+    3C1B20EE 3FD59D7C 25E41D2B 669E1EF1 6E6F52C3 164DF4FB
+    7930E9E4 E58857B6 AC7D5F42 D69F6D18 7763CF1D 55034004
+    87F55BA5 7E31CC7A 7135C886 EFB4318A ED6A1E01 2D9E6832
+    A907600A 918130C4 6DC778F9 71AD0038 092999A3 33CB8B7A
+    1A1DB93D 7140003C 2A4ECEA9 F98D0ACC 0A8291CD CEC97DCF
+    8EC9B55A 7F88A46B 4DB5A851 F44182E1 C68A007E 5E0DD902
+    0BFD64B6 45036C7A 4E677D2C 38532A3A 23BA4442 CAF53EA6
+    3BB45432 9B7624C8 917BDD64 B1C0FD4C B38E8C33 4C701C3A
+    CDAD0657 FCCFEC71 9B1F5C3E 4E46041F 388147FB 4CFDB477
+    A52471F7 A9A96910 B855322E DB6340D8 A00EF092 350511E3
+    0ABEC1FF F9E3A26E 7FB29F8C 183023C3 587E38DA 0077D9B4
+    763E4E4B 94B2BBC1 94C6651E 77CAF992 EEAAC023 2A281BF6
+    B3A739C1 22611682 0AE8DB58 47A67CBE F9C9091B 462D538C
+    D72B0374 6AE77F5E 62292C31 1562A846 505DC82D B854338A
+    E49F5235 C95B9117 8CCF2DD5 CACEF403 EC9D1810 C6272B04
+    5B3B71F9 DC6B80D6 3FDD4A8E 9ADB1E69 62A69526 D43161C1
+    A41D570D 7938DAD4 A40E329C CFF46AAA 36AD004C F600C838
+    1E425A31 D951AE64 FDB23FCE C9509D43 687FEB69 EDD1CC5E
+    0B8CC3BD F64B10EF 86B63142 A3AB8829 555B2F74 7C932665
+    CB2C0F1C C01BD702 29388839 D2AF05E4 54504AC7 8B758282
+    2846C0BA 35C35F5C 59160CC0 46FD8251 541FC68C 9C86B022
+    BB709987 6A460E74 51A8A931 09703FEE 1C217E6C 3826E52C
+    51AA691E 0E423CFC 99E9E316 50C1217B 624816CD AD9A95F9
+    D5B80194 88D9C0A0 A1FE3075 A577E231 83F81D4A 3F2FA457
+    1EFC8CE0 BA8A4FE8 B6855DFE 72B0A66E DED2FBAB FBE58A30
+    FAFABE1C 5D71A87E 2F741EF8 C1FE86FE A6BBFDE5 30677F0D
+    97D11D49 F7A8443D 0822E506 A9F4614E 011E2A94 838FF88C
+    D68C8BB7 C5C6424C FFFFFFFF FFFFFFFF";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use digest::Digest;
+    use num_traits::One;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use crate::elgamal::elgamal::hex_to_number;
+    use crate::sha2::sha2::Sha256Realization32;
+
+    // SHA-256 of the big-endian bytes of every prime, from the OpenSSL copies of the groups
+    const PRIME_DIGESTS: [(&str, &str); 10] = [
+        ("modp_2048", "d66436f79bbd6b2e38c0ffbd079be904d2641415e2e67140e09448be9a60890e"),
+        ("modp_3072", "48cf8b092fbce4359d9871abf74f98e25b6163379eaa15cd9087e800c6d1c55c"),
+        ("modp_4096", "4ee95187682bcb230ad26a95205f6920e84708f6251b3894329b09ec23919e33"),
+        ("modp_6144", "d1bfe6d0925ce7e4da262b62861514a7755e35831e429f343e7b864848657efd"),
+        ("modp_8192", "39ab4feab950a3128fb71accb9fc3965d857012e081998a85996e3ea8b3c3bcf"),
+        ("ffdhe2048", "9cd3b7f336872f46c09428d1bbc19877a4d440512cda8d1c1cf0cd6e33698966"),
+        ("ffdhe3072", "0eaf67db3a839156d5013494a5318a772b5697d270d721f37f092efc69ea5a17"),
+        ("ffdhe4096", "4648414224ac881b3d0dc59b466f96d06a558278776807797ecf1f66ff397b3e"),
+        ("ffdhe6144", "227ac9066b3ddd9e193670cda2388fa884f65ba0cf98b742d1fe77a6687c79c7"),
+        ("ffdhe8192", "770b14efaf6f049929c523113b3fa99a8d11dab1b18af3609590122075d19833"),
+    ];
+
+    // ffdhe2048 values printed by test_data/ffdhe2048.py (plain Python integers), message "Hello World!"
+    const FFDHE_2048_PRIVATE_KEY: &str = "6b5a1bace5f4aa67907c7dc44f2611304dc392f0db38efeaf7f89cc7da3762d4";
+    const FFDHE_2048_PUBLIC_KEY: &str = "23227ac79c3320072bc94906c95e163e22c5735462f0520a433ccbc72e20c969671b285a006f4ac19e6426cf5b3628e9e3778f7c85fd98ff231e11470f5d72ea1578e10a59ca2387d0b593a442d1f3b395240e179b53de8819cdb64f7d31e804e1eb837b622018a34dfe4fda182b08b2e955e0844ef725117c554207bf5eb1484964ec9b06c7666bb1873e783b0a2ce7ce11f34fd6ac2a71057ef38193bd93880143499968f14995f5b17ebfde11f13dfe5286aa76edd259b495d9283935608d927cad7e401b331feab3552b1b83ad1c68d6a73df6dcf85e0594ffd81e5f75a50045ce9b4ed184c28ad913a286349e7db3408596ecacc2552b95e9e32cd127b";
+    const FFDHE_2048_CIPHERTEXT: [&str; 2] = [
+        "918f5796014689eb11c7f385e615ce094a59705e9e6dd0d3af07db4f72b40d6f7ee8a35c5a590d7eb8eb1bb2a355e9fb5eaa23eaca63d24c859c57745bb83ac0ed6a99cd8556285003136c49537ff8090032fa4cb0451852bb259343eea43a21c801f939231d29b7a1cb782dd58567ca115e23bedf1f9f90b6a960609f7b3f96aebc25d1fab8ca8d858f9538295655b6c46c9d6bcff59b056050807026b0f70a0fa398b5622aa0d6e56c68f4dbce99ea939d27cf97e9fd1f0da524d90a79e7524f162d62ee3c1e18cf6e8864548dc2bbad5872229e4853d165f9a633f63701a4525cf1c07a77d0392c6c62acae5a7abffa00cb52ef39ad219109e2f7fbb9f7a9",
+        "f0e347b4913244ff1c95ea0a355f66b4e8771fb39bd7e4f3fd58dd57906d9a15dd74541e4f7fc4fdb943949f8be23849c1834a73e4d3ee3f02fb2a6a42616c2134f8bd6eb9b7f4177e11d7f9c7dabf8ef506322ca00fedf07064b6b8679337c5e77d62eb0c0659f4406c0878c62f0b860539869f1323af937d5a19d4fe4813780dc67e24743ab5349fa195397685eb44fc78a9e3f4a7b2c7bd830d9b684907fd23ab3087ddcc9157f4fa14647e0d613491f5a9abe106997809349adde7e414de091d280a0f1358ce10ed636150ee439c5230f7f114d039e8606edb80c678129cd3dd7b80131af6f9e10d94f9cc1952dfdd51e555ce07197eb21746df1c211676",
+    ];
+    const FFDHE_2048_SIGNATURE: [&str; 2] = [
+        "11f63b6a7c339adb9fad77b4d1d877a3fec41d3136714578f41d4c068aabc240759c07ddd8c6b4f5dd4321c5c8dc16bda821100cbb7eb0dd2ab859e463b08af0a8e8709e65614bf3b2449425a83ee23de0eb456af16b77a4cea1dea28891c774984c9cb25b466d4a8c4eeed52612bfdb7b42038f6a77e72cfacf6f689aa0fdae471151c08e1a9b5451361c8b2e8e1ab14e3dd3af34ac2ace3cf3d1fd6b71d3dffccca9d3fcd42eb9248e3d752c8a2a891857283ca7cfdbe0322136b1838826ccbb44b919bfb8f88db5aaf21b4b47f370563e0e768088b67eaca267fb05d785cf2d9cf832803efdf0e06da5070c3e0e0cccb4e9693ecb2af5286a44a8b70d02c0",
+        "17ceddc3a8b02e59b0f7516bbc4629c219c395e5ec16b6e8957e6a31504127fd5b4463ca89c3684ec9a1a6fb8e1c73c9d07a8c107b5f5a008dd9cd0918c64caab61b6351cb8978b8f3ae9cfaa3a35d1800396ac61240637023887811c3cf1fcddd6b93fb83f3cb2b2749abc9e1538acf1c37a8b8d0ea85cdc53f5826bd345437ac4bbe5f217b9285cbcb113585c830d3c238f40e13cb0dd5161f1e15e1151191ed608ed360272d58a476d649dbcc4c93bc3306d9aad7b06304005b6eef3067e029dd4d51063577ca95607c6808c8864291b89149be5d3b713458dcabb9b0f52f66df970e3ef019057389281730a56f0d6280ccff5ab5cb9d5f3aec1072805c39",
+    ];
+
+    fn number(hex: &str) -> BigUint {
+        BigUint::from_str_radix(hex, 16).unwrap()
+    }
+
+    #[test]
+    fn test_group_parameters() {
+        for (group, (name, digest)) in GroupId::ALL.into_iter().zip(PRIME_DIGESTS) {
+            assert_eq!(group.name(), name);
+            assert_eq!(group.to_string().parse::<GroupId>(), Ok(group));
+
+            let p = group.prime();
+            assert_eq!(p.bits(), group.bits() as u64);
+            assert_eq!(hex::encode(<Sha256Realization32 as Digest>::digest(p.to_bytes_be())), digest, "{}", name);
+
+            // The top and the low 64 bits are ones in both RFC constructions
+            let ones = (BigUint::one() << 64) - 1u32;
+            assert_eq!(p >> (group.bits() - 64), ones);
+            assert_eq!(p & &ones, ones);
+            assert_eq!(&(group.order() * 2u32 + 1u32), p);
+            // Parsed once, every call returns the same value
+            assert!(std::ptr::eq(group.prime(), p));
+        }
+
+        assert!(matches!("modp_1024".parse::<GroupId>(), Err(Error::InvalidArgument(_))));
+        assert!(matches!("FFDHE2048".parse::<GroupId>(), Err(Error::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn test_group_generator_order() {
+        // g^q = 1 and g != 1, so g = 2 has the prime order q (p is the RFC prime, checked by the digests above)
+        for group in GroupId::ALL {
+            assert!(group.generator().modpow(group.order(), group.prime()).is_one(), "{}", group);
+        }
+    }
+
+    #[test]
+    fn test_group_interoperability() {
+        let group: GroupId = "ffdhe2048".parse().unwrap();
+        let hex_num = hex_to_number(hex::encode("Hello World!")).unwrap();
+        let private_key = number(FFDHE_2048_PRIVATE_KEY);
+        let public_key = number(FFDHE_2048_PUBLIC_KEY);
+        assert_eq!(group.generator().modpow(&private_key, group.prime()), public_key);
+
+        let [a, b] = FFDHE_2048_CIPHERTEXT.map(number);
+        assert_eq!(group.decode(&a, &b, &private_key), hex_num);
+
+        let [r, s] = FFDHE_2048_SIGNATURE.map(number);
+        assert!(group.verify_sign(&hex_num, &r, &s, &public_key));
+        assert!(!group.verify_sign(&hex_num, &r, &s, &(&public_key + 1u32)));
+        assert!(!GroupId::Modp2048.verify_sign(&hex_num, &r, &s, &public_key));
+    }
+
+    #[test]
+    fn test_group_round_trip() {
+        let mut rng = StdRng::seed_from_u64(3526);
+        let hex_num = hex_to_number(hex::encode("Hello World!")).unwrap();
+
+        for group in [GroupId::Modp2048, GroupId::Ffdhe2048, GroupId::Ffdhe3072] {
+            let (private_key, public_key) = group.generate_keys_with_rng(&mut rng);
+            assert!(&private_key < group.order());
+
+            let (a, b) = group.encode_with_rng(&hex_num, &public_key, &mut rng).unwrap();
+            assert_eq!(group.decode(&a, &b, &private_key), hex_num);
+
+            let (r, s) = group.sign_with_rng(&hex_num, &private_key, &mut rng).unwrap();
+            assert!(group.verify_sign(&hex_num, &r, &s, &public_key));
+            assert!(!group.verify_sign(&(&hex_num + 1u32), &r, &s, &public_key));
+        }
+    }
+}
+
+// cargo test -- groups --nocapture
\ No newline at end of file
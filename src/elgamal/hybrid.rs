@@ -0,0 +1,303 @@
+use num_bigint::{BigInt, BigUint, RandBigInt};
+use num_traits::One;
+use rand::{CryptoRng, RngCore};
+use rand::rngs::OsRng;
+use crate::elgamal::keys::{PrivateKey, PublicKey};
+use crate::error::Error;
+use crate::hmac::hmac::Hmac;
+use crate::kdf::kdf::shared_secret_to_key;
+use crate::sha2::sha2::Sha256Realization32;
+
+// Hybrid ElGamal encryption of byte messages (KEM/DEM):
+// KEM: k = [2, order), A = g^k mod p goes into the container, Z = y^k = A^x mod p is the shared secret
+// KDF: HKDF-SHA256(salt = A, IKM = Z, info = 'KDF_INFO') -> encryption key || MAC key, A and Z are 'p'-sized big-endian
+// DEM: payload = message ^ HMAC-SHA256(encryption key, 0) || HMAC-SHA256(encryption key, 1) || ... (64-bit big-endian counter)
+//      tag = HMAC-SHA256(MAC key, header || payload), encrypt-then-MAC
+//
+// Container: MAGIC || VERSION || key length (u16, big-endian) || A || payload || tag
+
+pub const MAGIC: [u8; 4] = *b"EGHY";
+pub const VERSION: u8 = 1;
+
+const KDF_INFO: &[u8] = b"elgamal hybrid v1";
+const KEY_LENGTH: usize = 32;
+const TAG_LENGTH: usize = 32;
+const HEADER_LENGTH: usize = MAGIC.len() + 1 + 2;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HybridCiphertext {
+    version: u8,
+    encapsulated_key: Vec<u8>,
+    payload: Vec<u8>,
+    tag: Vec<u8>,
+}
+
+impl HybridCiphertext {
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    // A = g^k mod p, big-endian with the byte length of p
+    pub fn encapsulated_key(&self) -> &[u8] {
+        &self.encapsulated_key
+    }
+
+    // Encrypted message, the same length as the message
+    pub fn payload(&self) -> &[u8] {
+        &self.payload
+    }
+
+    pub fn tag(&self) -> &[u8] {
+        &self.tag
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = header(self.version, &self.encapsulated_key);
+        bytes.extend_from_slice(&self.payload);
+        bytes.extend_from_slice(&self.tag);
+        bytes
+    }
+
+    // Checks the layout and the version, the tag is checked by 'decrypt'
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() < HEADER_LENGTH + TAG_LENGTH || bytes[..MAGIC.len()] != MAGIC {
+            return Err(Error::InvalidArgument("Not a hybrid ElGamal ciphertext".to_string()));
+        }
+
+        let version = bytes[MAGIC.len()];
+        if version != VERSION {
+            return Err(Error::UnsupportedVersion { version });
+        }
+
+        let key_length = u16::from_be_bytes([bytes[MAGIC.len() + 1], bytes[MAGIC.len() + 2]]) as usize;
+        if key_length == 0 || bytes.len() < HEADER_LENGTH + key_length + TAG_LENGTH {
+            return Err(Error::InvalidArgument("Hybrid ElGamal ciphertext is truncated".to_string()));
+        }
+
+        let (encapsulated_key, rest) = bytes[HEADER_LENGTH..].split_at(key_length);
+        let (payload, tag) = rest.split_at(rest.len() - TAG_LENGTH);
+
+        Ok(HybridCiphertext {
+            version,
+            encapsulated_key: encapsulated_key.to_vec(),
+            payload: payload.to_vec(),
+            tag: tag.to_vec(),
+        })
+    }
+}
+
+pub fn encrypt(public_key: &PublicKey, message: &[u8]) -> Result<HybridCiphertext, Error> {
+    encrypt_with_rng(public_key, message, &mut OsRng)
+}
+
+pub fn encrypt_with_rng<R: RngCore + CryptoRng>(public_key: &PublicKey, message: &[u8], rng: &mut R) -> Result<HybridCiphertext, Error> {
+    let parameters = public_key.parameters();
+    let p = parameters.p();
+    let key_length = field_size(p)?;
+
+    // k = [2, order)
+    let k = rng.gen_biguint_range(&BigUint::from(2u32), parameters.order());
+    let encapsulated_key = to_field_bytes(&parameters.g().modpow(&k, p), key_length);
+    let shared_secret = public_key.y().modpow(&k, p);
+
+    let (encryption_key, mac_key) = derive_keys(&shared_secret, &encapsulated_key)?;
+    let payload = apply_keystream(&encryption_key, message);
+    let tag = Hmac::<Sha256Realization32>::new(&mac_key)
+        .update(&header(VERSION, &encapsulated_key))
+        .update(&payload)
+        .finalize();
+
+    Ok(HybridCiphertext { version: VERSION, encapsulated_key, payload, tag })
+}
+
+// The tag is checked before anything is decrypted
+pub fn decrypt(private_key: &PrivateKey, ciphertext: &HybridCiphertext) -> Result<Vec<u8>, Error> {
+    if ciphertext.version != VERSION {
+        return Err(Error::UnsupportedVersion { version: ciphertext.version });
+    }
+
+    let parameters = private_key.parameters();
+    let p = parameters.p();
+    if ciphertext.encapsulated_key.len() != field_size(p)? {
+        return Err(Error::InvalidArgument("Encapsulated key length doesn't match p".to_string()));
+    }
+
+    // 1 < A < p - 1 and A is in the subgroup of g
+    let encapsulated_key = BigUint::from_bytes_be(&ciphertext.encapsulated_key);
+    if encapsulated_key <= BigUint::one() || encapsulated_key >= p - 1u32 || !encapsulated_key.modpow(parameters.order(), p).is_one() {
+        return Err(Error::InvalidArgument("Encapsulated key is not in the subgroup of g".to_string()));
+    }
+
+    let shared_secret = encapsulated_key.modpow(private_key.x(), p);
+    let (encryption_key, mac_key) = derive_keys(&shared_secret, &ciphertext.encapsulated_key)?;
+
+    let authentic = Hmac::<Sha256Realization32>::new(&mac_key)
+        .update(&header(ciphertext.version, &ciphertext.encapsulated_key))
+        .update(&ciphertext.payload)
+        .verify(&ciphertext.tag);
+    if !authentic {
+        return Err(Error::AuthenticationFailed);
+    }
+
+    Ok(apply_keystream(&encryption_key, &ciphertext.payload))
+}
+
+fn header(version: u8, encapsulated_key: &[u8]) -> Vec<u8> {
+    let mut header = MAGIC.to_vec();
+    header.push(version);
+    header.extend_from_slice(&(encapsulated_key.len() as u16).to_be_bytes());
+    header.extend_from_slice(encapsulated_key);
+    header
+}
+
+// Byte length of p, the key length field has 16 bits
+fn field_size(p: &BigUint) -> Result<usize, Error> {
+    let size = p.bits().div_ceil(8) as usize;
+    if size > u16::MAX as usize {
+        return Err(Error::InvalidArgument(format!("p is too big for the container: {} bytes", size)));
+    }
+
+    Ok(size)
+}
+
+fn to_field_bytes(value: &BigUint, size: usize) -> Vec<u8> {
+    let bytes = value.to_bytes_be();
+    let mut field = vec![0u8; size - bytes.len()];
+    field.extend_from_slice(&bytes);
+    field
+}
+
+// Encryption key, MAC key
+fn derive_keys(shared_secret: &BigUint, encapsulated_key: &[u8]) -> Result<(Vec<u8>, Vec<u8>), Error> {
+    let shared_secret = BigInt::from(shared_secret.clone());
+    let mut keys = shared_secret_to_key::<Sha256Realization32>(&shared_secret, encapsulated_key.len(), encapsulated_key, KDF_INFO, 2 * KEY_LENGTH)?;
+    let mac_key = keys.split_off(KEY_LENGTH);
+
+    Ok((keys, mac_key))
+}
+
+// XOR with the HMAC-SHA256 blocks of the counter, the same call encrypts and decrypts
+fn apply_keystream(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut prf = Hmac::<Sha256Realization32>::new(key);
+    let mut output = Vec::with_capacity(data.len());
+
+    for (counter, chunk) in data.chunks(KEY_LENGTH).enumerate() {
+        let block = prf.update(&(counter as u64).to_be_bytes()).finalize();
+        output.extend(chunk.iter().zip(block.iter()).map(|(byte, key_byte)| byte ^ key_byte));
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_bigint::BigUint;
+    use num_traits::Num;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use crate::elgamal::groups::GroupId;
+    use crate::elgamal::keys::DomainParameters;
+
+    // Container printed by test_data/hybrid.py (hashlib and hmac): ffdhe2048, the private key of the 'groups' tests
+    const FFDHE_2048_PRIVATE_KEY: &str = "6b5a1bace5f4aa67907c7dc44f2611304dc392f0db38efeaf7f89cc7da3762d4";
+    const REFERENCE_CONTAINER: &str = "4547485901010004763ff6f653e8ce5c135fcf1f2699c50207844f7ab944e9499db23fced8f6780c34bb58c095f803622fd64c4ced7f81ceda42241fdefda6f9a125fe4576b9f4d56571b9b80385e9ae5373bcc2f0a913354e94747f816a72d3341652627adb9e82a81391a89fb7d9d45f27bf0c7d069555d93efb91ef8f75307103986046659b87b0159ca55f7b648d7378c342c7a392d6ad047cd2c6a8ad411c8cff30f6dbbad12f829f6c68817650ba51d07c0cfbbf69322afd032b40fab396e19957091c3f40b1a62a6214ecf6cae848cb0542e95679a98c45f7f70ada35205da913f93e00f5d300882577624c8c804442b95a608fce4e99ef04420cd4bd9ad9107589e6778c43e256da7adca6045558699b09c3cbe489535aed0343a0bd1cdf470c5d940db89d7d73655ecbd4ef48986358ea6f4b81dc0e374a6b6ea4716dc20d910eb22f53a6c8ce1f82514dde7c620955f0f2241b5501be483676dc4bbafc175ffa0e3caab621eb0a73c4636d6743ead483c538283ff25209092b1ca7a1";
+
+    fn ffdhe_2048_private_key() -> PrivateKey {
+        let x = BigUint::from_str_radix(FFDHE_2048_PRIVATE_KEY, 16).unwrap();
+        PrivateKey::new(DomainParameters::from_group(GroupId::Ffdhe2048), x).unwrap()
+    }
+
+    #[test]
+    fn test_hybrid_reference_container() {
+        let bytes = hex::decode(REFERENCE_CONTAINER).unwrap();
+        let ciphertext = HybridCiphertext::from_bytes(&bytes).unwrap();
+        assert_eq!(ciphertext.version(), VERSION);
+        assert_eq!(ciphertext.encapsulated_key().len(), 256);
+        assert_eq!(ciphertext.to_bytes(), bytes);
+
+        let message = decrypt(&ffdhe_2048_private_key(), &ciphertext).unwrap();
+        assert_eq!(message, b"The quick brown fox jumps over the lazy dog. ".repeat(2));
+    }
+
+    #[test]
+    fn test_hybrid_round_trip() {
+        let mut rng = StdRng::seed_from_u64(25);
+        let private_key = ffdhe_2048_private_key();
+        let public_key = private_key.public_key();
+
+        for length in [0, 1, 31, 32, 33, 10000] {
+            let message: Vec<u8> = (0..length).map(|index| (index * 7 % 251) as u8).collect();
+            let bytes = encrypt_with_rng(&public_key, &message, &mut rng).unwrap().to_bytes();
+
+            // MAGIC || VERSION || 256 || A || payload || tag
+            assert_eq!(bytes.len(), HEADER_LENGTH + 256 + length + TAG_LENGTH);
+            assert_eq!(bytes[..HEADER_LENGTH], [b'E', b'G', b'H', b'Y', 1, 1, 0]);
+            if length >= 32 {
+                assert_ne!(bytes[HEADER_LENGTH + 256..HEADER_LENGTH + 256 + 32], message[..32]);
+            }
+
+            let ciphertext = HybridCiphertext::from_bytes(&bytes).unwrap();
+            assert_eq!(decrypt(&private_key, &ciphertext).unwrap(), message);
+        }
+
+        // The same seed gives the same container, a new ephemeral key changes everything
+        let encrypt_seeded = |seed: u64| encrypt_with_rng(&public_key, b"Hello World!", &mut StdRng::seed_from_u64(seed)).unwrap();
+        assert_eq!(encrypt_seeded(1), encrypt_seeded(1));
+        assert_ne!(encrypt_seeded(1).payload(), encrypt_seeded(2).payload());
+
+        // Schnorr group parameters
+        let parameters = DomainParameters::generate_schnorr(512, &mut rng).unwrap();
+        let private_key = PrivateKey::generate_with_rng(parameters, &mut rng);
+        let ciphertext = encrypt(&private_key.public_key(), b"Hello World!").unwrap();
+        assert_eq!(ciphertext.encapsulated_key().len(), 64);
+        assert_eq!(decrypt(&private_key, &ciphertext).unwrap(), b"Hello World!");
+    }
+
+    #[test]
+    fn test_hybrid_tampering() {
+        let private_key = ffdhe_2048_private_key();
+        let bytes = hex::decode(REFERENCE_CONTAINER).unwrap();
+        let open = |bytes: &[u8]| HybridCiphertext::from_bytes(bytes).and_then(|ciphertext| decrypt(&private_key, &ciphertext));
+
+        let modified = |index: usize, value: u8| {
+            let mut bytes = bytes.clone();
+            bytes[index] = value;
+            bytes
+        };
+        let flipped = |index: usize| modified(index, bytes[index] ^ 1);
+
+        assert!(matches!(open(&flipped(0)), Err(Error::InvalidArgument(_))));
+        assert_eq!(open(&modified(4, 2)), Err(Error::UnsupportedVersion { version: 2 }));
+        assert!(matches!(open(&flipped(6)), Err(Error::InvalidArgument(_))));
+        // Encapsulated key, payload and tag
+        assert!(open(&flipped(HEADER_LENGTH + 100)).is_err());
+        for index in [HEADER_LENGTH + 256, bytes.len() - TAG_LENGTH - 1, bytes.len() - TAG_LENGTH, bytes.len() - 1] {
+            assert_eq!(open(&flipped(index)), Err(Error::AuthenticationFailed), "{}", index);
+        }
+
+        // Truncated containers
+        assert!(matches!(open(&bytes[..HEADER_LENGTH + 10]), Err(Error::InvalidArgument(_))));
+        assert_eq!(open(&bytes[..bytes.len() - 1]), Err(Error::AuthenticationFailed));
+
+        // Encapsulated key 1 and p - 1 (order 2, not in the subgroup)
+        let p = GroupId::Ffdhe2048.prime();
+        for value in [BigUint::one(), p - 1u32] {
+            let mut bytes = bytes.clone();
+            bytes[HEADER_LENGTH..HEADER_LENGTH + 256].copy_from_slice(&to_field_bytes(&value, 256));
+            assert!(matches!(open(&bytes), Err(Error::InvalidArgument(_))));
+        }
+
+        // Another private key in the same group
+        let mut rng = StdRng::seed_from_u64(26);
+        let other_key = PrivateKey::generate_with_rng(DomainParameters::from_group(GroupId::Ffdhe2048), &mut rng);
+        let ciphertext = HybridCiphertext::from_bytes(&bytes).unwrap();
+        assert_eq!(decrypt(&other_key, &ciphertext), Err(Error::AuthenticationFailed));
+
+        // Another group
+        let other_key = PrivateKey::generate_with_rng(DomainParameters::from_group(GroupId::Modp3072), &mut rng);
+        assert!(matches!(decrypt(&other_key, &ciphertext), Err(Error::InvalidArgument(_))));
+    }
+}
+
+// cargo test -- hybrid --nocapture
\ No newline at end of file
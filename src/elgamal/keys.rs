@@ -0,0 +1,424 @@
+use num_bigint::{BigUint, RandBigInt};
+use num_traits::{One, Zero};
+use rand::{CryptoRng, RngCore};
+use rand::rngs::OsRng;
+use crate::elgamal::elgamal::{
+    decode_with_order, encode_with_order, generate_prime_number, generate_primitive_root, generate_safe_prime,
+    generate_schnorr_group, is_probable_prime, sign_with_order, verify_sign, MIN_ORDER, SCHNORR_Q_BITS
+};
+use crate::elgamal::groups::GroupId;
+use crate::error::Error;
+
+// p, g and the order of g with its distinct prime factors:
+// p - 1 for a primitive root, q for a Schnorr group or a named group
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DomainParameters {
+    p: BigUint,
+    g: BigUint,
+    order: BigUint,
+    order_factors: Vec<BigUint>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PublicKey {
+    parameters: DomainParameters,
+    y: BigUint,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PrivateKey {
+    parameters: DomainParameters,
+    x: BigUint,
+}
+
+// a = g^k mod p, b = y^k * m mod p
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Ciphertext {
+    a: BigUint,
+    b: BigUint,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Signature {
+    r: BigUint,
+    s: BigUint,
+}
+
+impl DomainParameters {
+    // Checks that p is prime, 1 < g < p - 1, the order is at least 'MIN_ORDER' and divides p - 1,
+    // 'order_factors' are the prime factors of the order and g has exactly this order
+    pub fn new(p: BigUint, g: BigUint, order: BigUint, order_factors: Vec<BigUint>) -> Result<Self, Error> {
+        DomainParameters::new_with_rng(p, g, order, order_factors, &mut OsRng)
+    }
+
+    // The same, Miller-Rabin bases for p and the factors come from 'rng'
+    pub fn new_with_rng<R: RngCore + CryptoRng>(p: BigUint, g: BigUint, order: BigUint, mut order_factors: Vec<BigUint>, rng: &mut R) -> Result<Self, Error> {
+        let one = BigUint::one();
+        let p_minus_one = &p - 1u32;
+
+        if p < BigUint::from(5u32) || !is_probable_prime(&p, rng) {
+            return Err(Error::InvalidArgument("p is not a prime above 3".to_string()));
+        }
+        if g <= one || g >= p_minus_one {
+            return Err(Error::InvalidArgument("g is not in (1, p - 1)".to_string()));
+        }
+        if order < BigUint::from(MIN_ORDER) {
+            return Err(Error::InvalidArgument(format!("The order is below {}, too small to sign with", MIN_ORDER)));
+        }
+        if !(&p_minus_one % &order).is_zero() {
+            return Err(Error::InvalidArgument("The order does not divide p - 1".to_string()));
+        }
+
+        order_factors.sort();
+        order_factors.dedup();
+        let mut rest = order.clone();
+        for factor in order_factors.iter() {
+            if factor <= &one || !(&rest % factor).is_zero() || !is_probable_prime(factor, rng) {
+                return Err(Error::InvalidArgument(format!("{} is not a prime factor of the order", factor)));
+            }
+            while (&rest % factor).is_zero() {
+                rest /= factor;
+            }
+        }
+        if !rest.is_one() {
+            return Err(Error::InvalidArgument("The order has prime factors that are not listed".to_string()));
+        }
+
+        // g^order = 1 and g^(order / f) != 1 for every prime factor f
+        if !g.modpow(&order, &p).is_one() || order_factors.iter().any(|factor| g.modpow(&(&order / factor), &p).is_one()) {
+            return Err(Error::InvalidArgument("g does not have the given order".to_string()));
+        }
+
+        Ok(DomainParameters { p, g, order, order_factors })
+    }
+
+    // g of prime order q
+    pub fn with_prime_order(p: BigUint, g: BigUint, q: BigUint) -> Result<Self, Error> {
+        DomainParameters::with_prime_order_with_rng(p, g, q, &mut OsRng)
+    }
+
+    pub fn with_prime_order_with_rng<R: RngCore + CryptoRng>(p: BigUint, g: BigUint, q: BigUint, rng: &mut R) -> Result<Self, Error> {
+        DomainParameters::new_with_rng(p, g, q.clone(), vec![q], rng)
+    }
+
+    // Trusted, not validated: the RFC constants are fixed, their digests and g^q = 1 are checked by the tests
+    // of 'groups.rs'. Miller-Rabin on the 8192-bit p and q would take minutes, use 'with_prime_order' to run it anyway.
+    pub fn from_group(group: GroupId) -> Self {
+        DomainParameters {
+            p: group.prime().clone(),
+            g: group.generator(),
+            order: group.order().clone(),
+            order_factors: vec![group.order().clone()],
+        }
+    }
+
+    // p = 2hq + 1 with a primitive root, see 'genereate_keys_with_rng'
+    pub fn generate<R: RngCore + CryptoRng>(p_bits_from: usize, p_bits_to: usize, rng: &mut R) -> Result<Self, Error> {
+        let (p, order_factors) = generate_prime_number(p_bits_from, p_bits_to, rng)?;
+        let g = generate_primitive_root(&p, &order_factors, rng)?;
+
+        Ok(DomainParameters { order: &p - 1u32, p, g, order_factors })
+    }
+
+    // Safe prime p = 2q + 1 with a primitive root
+    pub fn generate_safe_prime<R: RngCore + CryptoRng>(p_bits: usize, rng: &mut R) -> Result<Self, Error> {
+        let (p, q) = generate_safe_prime(p_bits, rng)?;
+        let order_factors = vec![BigUint::from(2u32), q];
+        let g = generate_primitive_root(&p, &order_factors, rng)?;
+
+        Ok(DomainParameters { order: &p - 1u32, p, g, order_factors })
+    }
+
+    // Schnorr group, g has the prime order q of 'SCHNORR_Q_BITS' bits
+    pub fn generate_schnorr<R: RngCore + CryptoRng>(p_bits: usize, rng: &mut R) -> Result<Self, Error> {
+        let (p, q, g) = generate_schnorr_group(p_bits, SCHNORR_Q_BITS, rng)?;
+
+        Ok(DomainParameters { p, g, order: q.clone(), order_factors: vec![q] })
+    }
+
+    pub fn p(&self) -> &BigUint {
+        &self.p
+    }
+
+    pub fn g(&self) -> &BigUint {
+        &self.g
+    }
+
+    pub fn order(&self) -> &BigUint {
+        &self.order
+    }
+
+    pub fn order_factors(&self) -> &[BigUint] {
+        &self.order_factors
+    }
+}
+
+impl From<GroupId> for DomainParameters {
+    fn from(group: GroupId) -> Self {
+        DomainParameters::from_group(group)
+    }
+}
+
+impl PublicKey {
+    // 1 < y < p - 1 and y lies in the subgroup generated by g: y^order = 1
+    pub fn new(parameters: DomainParameters, y: BigUint) -> Result<Self, Error> {
+        if y <= BigUint::one() || y >= &parameters.p - 1u32 {
+            return Err(Error::InvalidArgument("y is not in (1, p - 1)".to_string()));
+        }
+        if !y.modpow(&parameters.order, &parameters.p).is_one() {
+            return Err(Error::InvalidArgument("y is not in the subgroup of g".to_string()));
+        }
+
+        Ok(PublicKey { parameters, y })
+    }
+
+    pub fn parameters(&self) -> &DomainParameters {
+        &self.parameters
+    }
+
+    pub fn y(&self) -> &BigUint {
+        &self.y
+    }
+
+    // The message number must be in (0, p)
+    pub fn encrypt(&self, hex_num: &BigUint) -> Result<Ciphertext, Error> {
+        self.encrypt_with_rng(hex_num, &mut OsRng)
+    }
+
+    pub fn encrypt_with_rng<R: RngCore + CryptoRng>(&self, hex_num: &BigUint, rng: &mut R) -> Result<Ciphertext, Error> {
+        let parameters = &self.parameters;
+        let (a, b) = encode_with_order(hex_num, &parameters.p, &parameters.order, &parameters.g, &self.y, rng)?;
+        Ok(Ciphertext { a, b })
+    }
+
+    pub fn verify(&self, hex_num: &BigUint, signature: &Signature) -> bool {
+        verify_sign(hex_num, &self.parameters.p, &self.parameters.g, &signature.r, &signature.s, &self.y)
+    }
+}
+
+impl PrivateKey {
+    // 0 < x < order
+    pub fn new(parameters: DomainParameters, x: BigUint) -> Result<Self, Error> {
+        if x.is_zero() || x >= parameters.order {
+            return Err(Error::InvalidArgument("x is not in (0, order)".to_string()));
+        }
+
+        Ok(PrivateKey { parameters, x })
+    }
+
+    pub fn generate(parameters: DomainParameters) -> Self {
+        PrivateKey::generate_with_rng(parameters, &mut OsRng)
+    }
+
+    // x = [2, order)
+    pub fn generate_with_rng<R: RngCore + CryptoRng>(parameters: DomainParameters, rng: &mut R) -> Self {
+        let x = rng.gen_biguint_range(&BigUint::from(2u32), &parameters.order);
+        PrivateKey { parameters, x }
+    }
+
+    pub fn parameters(&self) -> &DomainParameters {
+        &self.parameters
+    }
+
+    pub fn x(&self) -> &BigUint {
+        &self.x
+    }
+
+    // y = g^x mod p
+    pub fn public_key(&self) -> PublicKey {
+        let y = self.parameters.g.modpow(&self.x, &self.parameters.p);
+        PublicKey { parameters: self.parameters.clone(), y }
+    }
+
+    pub fn decrypt(&self, ciphertext: &Ciphertext) -> BigUint {
+        let parameters = &self.parameters;
+        decode_with_order(&ciphertext.a, &ciphertext.b, &parameters.p, &parameters.order, &self.x)
+    }
+
+    pub fn sign(&self, hex_num: &BigUint) -> Result<Signature, Error> {
+        self.sign_with_rng(hex_num, &mut OsRng)
+    }
+
+    pub fn sign_with_rng<R: RngCore + CryptoRng>(&self, hex_num: &BigUint, rng: &mut R) -> Result<Signature, Error> {
+        let parameters = &self.parameters;
+        let (r, s) = sign_with_order(hex_num, &parameters.p, &parameters.order, &parameters.g, &self.x, rng)?;
+        Ok(Signature { r, s })
+    }
+}
+
+impl Ciphertext {
+    // 0 < a < p and 0 < b < p
+    pub fn new(parameters: &DomainParameters, a: BigUint, b: BigUint) -> Result<Self, Error> {
+        if a.is_zero() || a >= parameters.p || b.is_zero() || b >= parameters.p {
+            return Err(Error::InvalidArgument("Ciphertext components are not in (0, p)".to_string()));
+        }
+
+        Ok(Ciphertext { a, b })
+    }
+
+    pub fn a(&self) -> &BigUint {
+        &self.a
+    }
+
+    pub fn b(&self) -> &BigUint {
+        &self.b
+    }
+}
+
+impl Signature {
+    // 0 < r < p and 0 < s < order
+    pub fn new(parameters: &DomainParameters, r: BigUint, s: BigUint) -> Result<Self, Error> {
+        if r.is_zero() || r >= parameters.p || s.is_zero() || s >= parameters.order {
+            return Err(Error::InvalidArgument("Signature components are out of range".to_string()));
+        }
+
+        Ok(Signature { r, s })
+    }
+
+    pub fn r(&self) -> &BigUint {
+        &self.r
+    }
+
+    pub fn s(&self) -> &BigUint {
+        &self.s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use crate::elgamal::elgamal::{encode_with_rng, genereate_keys_with_rng, hex_to_number, sign_with_rng};
+
+    fn number(value: u32) -> BigUint {
+        BigUint::from(value)
+    }
+
+    fn assert_invalid<T: std::fmt::Debug>(result: Result<T, Error>) {
+        assert!(matches!(result, Err(Error::InvalidArgument(_))), "{:?}", result);
+    }
+
+    #[test]
+    fn test_domain_parameters_validation() {
+        // Z_23*: 5 is a primitive root, 2 has the order 11
+        let parameters = DomainParameters::new(number(23), number(5), number(22), vec![number(11), number(2)]).unwrap();
+        assert_eq!(parameters.order_factors(), [number(2), number(11)]);
+        assert!(DomainParameters::with_prime_order(number(23), number(2), number(11)).is_ok());
+
+        // p is not prime
+        assert_invalid(DomainParameters::new(number(21), number(5), number(20), vec![number(2), number(5)]));
+        assert_invalid(DomainParameters::with_prime_order(number(3), number(2), number(2)));
+        // g is out of range
+        assert_invalid(DomainParameters::new(number(23), number(1), number(22), vec![number(2), number(11)]));
+        assert_invalid(DomainParameters::new(number(23), number(22), number(22), vec![number(2), number(11)]));
+        // The order does not divide p - 1
+        assert_invalid(DomainParameters::new(number(23), number(5), number(21), vec![number(3), number(7)]));
+        // The factors are incomplete or not prime
+        assert_invalid(DomainParameters::new(number(23), number(5), number(22), vec![number(2)]));
+        assert_invalid(DomainParameters::new(number(23), number(5), number(22), vec![number(22)]));
+        // g has another order
+        assert_invalid(DomainParameters::with_prime_order(number(23), number(5), number(11)));
+        assert_invalid(DomainParameters::new(number(23), number(2), number(22), vec![number(2), number(11)]));
+        // Orders below 7 are too small to sign with: in Z_7* the order 3 has the single nonce k = 2
+        assert_invalid(DomainParameters::with_prime_order(number(7), number(2), number(3)));
+
+        // Carmichael number 561 = 3 * 11 * 17 is refused with any source of the Miller-Rabin bases
+        let mut rng = StdRng::seed_from_u64(561);
+        assert_invalid(DomainParameters::new_with_rng(number(561), number(5), number(560), vec![number(2), number(5), number(7)], &mut rng));
+
+        // The named group passes the full validation
+        let group = GroupId::Ffdhe2048;
+        let validated = DomainParameters::with_prime_order_with_rng(group.prime().clone(), group.generator(), group.order().clone(), &mut rng).unwrap();
+        assert_eq!(validated, DomainParameters::from(group));
+    }
+
+    #[test]
+    fn test_keys_validation() {
+        let parameters = DomainParameters::with_prime_order(number(23), number(2), number(11)).unwrap();
+
+        // y = 4 = 2^2 is in the subgroup, 5 is not
+        assert!(PublicKey::new(parameters.clone(), number(4)).is_ok());
+        assert_invalid(PublicKey::new(parameters.clone(), number(5)));
+        assert_invalid(PublicKey::new(parameters.clone(), number(1)));
+        assert_invalid(PublicKey::new(parameters.clone(), number(22)));
+        assert_invalid(PublicKey::new(parameters.clone(), number(30)));
+
+        assert_eq!(PrivateKey::new(parameters.clone(), number(2)).unwrap().public_key().y(), &number(4));
+        assert_invalid(PrivateKey::new(parameters.clone(), number(0)));
+        assert_invalid(PrivateKey::new(parameters.clone(), number(11)));
+
+        assert!(Ciphertext::new(&parameters, number(1), number(22)).is_ok());
+        assert_invalid(Ciphertext::new(&parameters, number(0), number(3)));
+        assert_invalid(Ciphertext::new(&parameters, number(3), number(23)));
+
+        assert!(Signature::new(&parameters, number(22), number(10)).is_ok());
+        assert_invalid(Signature::new(&parameters, number(23), number(3)));
+        assert_invalid(Signature::new(&parameters, number(3), number(11)));
+
+        let public_key = PublicKey::new(parameters, number(4)).unwrap();
+        assert_invalid(public_key.encrypt(&number(0)));
+        assert_invalid(public_key.encrypt(&number(23)));
+    }
+
+    #[test]
+    fn test_typed_round_trip() {
+        let mut rng = StdRng::seed_from_u64(24);
+        let hex_num = hex_to_number("1a2b".to_string()).unwrap();
+
+        let all_parameters = [
+            DomainParameters::generate(32, 32, &mut rng).unwrap(),
+            DomainParameters::generate_safe_prime(128, &mut rng).unwrap(),
+            DomainParameters::generate_schnorr(512, &mut rng).unwrap(),
+            DomainParameters::from_group(GroupId::Modp2048),
+        ];
+        for parameters in all_parameters {
+            // Generated parameters pass the validation
+            let revalidated = DomainParameters::new_with_rng(
+                parameters.p().clone(),
+                parameters.g().clone(),
+                parameters.order().clone(),
+                parameters.order_factors().to_vec(),
+                &mut rng
+            );
+            assert_eq!(revalidated.as_ref(), Ok(&parameters));
+
+            let private_key = PrivateKey::generate_with_rng(parameters.clone(), &mut rng);
+            let public_key = PublicKey::new(parameters.clone(), private_key.public_key().y().clone()).unwrap();
+
+            let ciphertext = public_key.encrypt_with_rng(&hex_num, &mut rng).unwrap();
+            let ciphertext = Ciphertext::new(&parameters, ciphertext.a().clone(), ciphertext.b().clone()).unwrap();
+            assert_eq!(private_key.decrypt(&ciphertext), hex_num);
+
+            let signature = private_key.sign_with_rng(&hex_num, &mut rng).unwrap();
+            let signature = Signature::new(&parameters, signature.r().clone(), signature.s().clone()).unwrap();
+            assert!(public_key.verify(&hex_num, &signature));
+            assert!(!public_key.verify(&(&hex_num + 1u32), &signature));
+        }
+    }
+
+    #[test]
+    fn test_typed_matches_tuple_api() {
+        // The same seed gives the same values in both APIs
+        let hex_num = hex_to_number("1a2b".to_string()).unwrap();
+
+        let mut rng = StdRng::seed_from_u64(2020);
+        let (p, g, x, y) = genereate_keys_with_rng(16, 16, &mut rng).unwrap();
+        let (a, b) = encode_with_rng(&hex_num, &p, &g, &y, &mut rng).unwrap();
+        let (r, s) = sign_with_rng(&hex_num, &p, &g, &x, &mut rng).unwrap();
+
+        let mut rng = StdRng::seed_from_u64(2020);
+        let parameters = DomainParameters::generate(16, 16, &mut rng).unwrap();
+        let private_key = PrivateKey::generate_with_rng(parameters, &mut rng);
+        let public_key = private_key.public_key();
+        let ciphertext = public_key.encrypt_with_rng(&hex_num, &mut rng).unwrap();
+        let signature = private_key.sign_with_rng(&hex_num, &mut rng).unwrap();
+
+        assert_eq!((public_key.parameters().p(), public_key.parameters().g()), (&p, &g));
+        assert_eq!((private_key.x(), public_key.y()), (&x, &y));
+        assert_eq!((ciphertext.a(), ciphertext.b()), (&a, &b));
+        assert_eq!((signature.r(), signature.s()), (&r, &s));
+    }
+}
+
+// cargo test -- keys --nocapture
\ No newline at end of file
@@ -0,0 +1,60 @@
+#!/usr/bin/env python3
+# ElGamal over the RFC 7919 ffdhe2048 group (g = 2, order q = (p - 1) / 2) with Python integers only,
+# written separately from the Rust code. Prints the key, the ciphertext and the signature of
+# 'test_group_interoperability' for the message "Hello World!": python3 ffdhe2048.py
+# The private key and the nonces are SHA-256 counters over fixed labels, so the output never changes.
+import hashlib
+
+# RFC 7919, appendix A.1
+P = int("""
+    FFFFFFFF FFFFFFFF ADF85458 A2BB4A9A AFDC5620 273D3CF1
+    D8B9C583 CE2D3695 A9E13641 146433FB CC939DCE 249B3EF9
+    7D2FE363 630C75D8 F681B202 AEC4617A D3DF1ED5 D5FD6561
+    2433F51F 5F066ED0 85636555 3DED1AF3 B557135E 7F57C935
+    984F0C70 E0E68B77 E2A689DA F3EFE872 1DF158A1 36ADE735
+    30ACCA4F 483A797A BC0AB182 B324FB61 D108A94B B2C8E3FB
+    B96ADAB7 60D7F468 1D4F42A3 DE394DF4 AE56EDE7 6372BB19
+    0B07A7C8 EE0A6D70 9E02FCE1 CDF7E2EC C03404CD 28342F61
+    9172FE9C E98583FF 8E4F1232 EEF28183 C3FE3B1B 4C6FAD73
+    3BB5FCBC 2EC22005 C58EF183 7D1683B2 C6F34A26 C1B2EFFA
+    886B4238 61285C97 FFFFFFFF FFFFFFFF""".replace(" ", "").replace("\n", ""), 16)
+G = 2
+Q = (P - 1) // 2
+
+
+# A number in [2, Q) from SHA-256(label || counter), the next counter when it doesn't fit
+def number(label):
+    counter = 0
+    while True:
+        value = int.from_bytes(hashlib.sha256(label + counter.to_bytes(4, "big")).digest(), "big")
+        if 2 <= value < Q:
+            return value
+        counter += 1
+
+
+def main():
+    assert pow(G, Q, P) == 1
+    m = int.from_bytes(b"Hello World!", "big")
+
+    x = number(b"ffdhe2048 private key")
+    y = pow(G, x, P)
+
+    # a = g^k, b = y^k * m, m = b * a^(q - x)
+    k = number(b"ffdhe2048 encryption nonce")
+    a, b = pow(G, k, P), pow(y, k, P) * m % P
+    assert b * pow(a, Q - x, P) % P == m
+
+    # r = g^k, s = (m - x*r) * k^(-1) mod q, y^r * r^s = g^m
+    k = number(b"ffdhe2048 signature nonce")
+    r = pow(G, k, P)
+    s = (m - x * r) * pow(k, -1, Q) % Q
+    assert s != 0 and pow(y, r, P) * pow(r, s, P) % P == pow(G, m, P)
+
+    print("private key", format(x, "x"))
+    print("public key", format(y, "x"))
+    print("ciphertext", format(a, "x"), format(b, "x"))
+    print("signature", format(r, "x"), format(s, "x"))
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,75 @@
+#!/usr/bin/env python3
+# Hybrid ElGamal container of 'hybrid.rs' (KEM/DEM, version 1) over Python's hashlib and hmac,
+# written separately from the Rust code. Prints 'REFERENCE_CONTAINER' of 'test_hybrid_reference_container':
+# python3 hybrid.py. The group and the private key are those of 'ffdhe2048.py'.
+import hashlib
+import hmac
+
+from ffdhe2048 import G, P, number
+
+MAGIC = b"EGHY"
+VERSION = 1
+KDF_INFO = b"elgamal hybrid v1"
+KEY_LENGTH = 32
+FIELD_SIZE = (P.bit_length() + 7) // 8
+MESSAGE = b"The quick brown fox jumps over the lazy dog. " * 2
+
+
+def hmac_sha256(key, data):
+    return hmac.new(key, data, hashlib.sha256).digest()
+
+
+# RFC 5869
+def hkdf(salt, input_key_material, info, length):
+    key = hmac_sha256(salt, input_key_material)
+    output, block, counter = b"", b"", 1
+    while len(output) < length:
+        block = hmac_sha256(key, block + info + bytes([counter]))
+        output += block
+        counter += 1
+    return output[:length]
+
+
+# Encryption key, MAC key: HKDF-SHA256(salt = A, IKM = Z)
+def derive_keys(shared_secret, encapsulated_key):
+    keys = hkdf(encapsulated_key, shared_secret.to_bytes(FIELD_SIZE, "big"), KDF_INFO, 2 * KEY_LENGTH)
+    return keys[:KEY_LENGTH], keys[KEY_LENGTH:]
+
+
+# XOR with HMAC-SHA256(key, 64-bit big-endian counter)
+def apply_keystream(key, data):
+    output = b""
+    for counter, start in enumerate(range(0, len(data), KEY_LENGTH)):
+        block = hmac_sha256(key, counter.to_bytes(8, "big"))
+        output += bytes(byte ^ key_byte for byte, key_byte in zip(data[start:start + KEY_LENGTH], block))
+    return output
+
+
+def header(encapsulated_key):
+    return MAGIC + bytes([VERSION]) + len(encapsulated_key).to_bytes(2, "big") + encapsulated_key
+
+
+def encrypt(y, message, k):
+    encapsulated_key = pow(G, k, P).to_bytes(FIELD_SIZE, "big")
+    encryption_key, mac_key = derive_keys(pow(y, k, P), encapsulated_key)
+    payload = apply_keystream(encryption_key, message)
+    return header(encapsulated_key) + payload + hmac_sha256(mac_key, header(encapsulated_key) + payload)
+
+
+def decrypt(x, container):
+    encapsulated_key = container[7:7 + FIELD_SIZE]
+    payload, tag = container[7 + FIELD_SIZE:-KEY_LENGTH], container[-KEY_LENGTH:]
+    encryption_key, mac_key = derive_keys(pow(int.from_bytes(encapsulated_key, "big"), x, P), encapsulated_key)
+    assert hmac.compare_digest(tag, hmac_sha256(mac_key, header(encapsulated_key) + payload))
+    return apply_keystream(encryption_key, payload)
+
+
+def main():
+    x = number(b"ffdhe2048 private key")
+    container = encrypt(pow(G, x, P), MESSAGE, number(b"hybrid nonce"))
+    assert decrypt(x, container) == MESSAGE
+    print(container.hex())
+
+
+if __name__ == "__main__":
+    main()
@@ -1,12 +1,16 @@
-use num_bigint::{BigUint,BigInt};
+use num_bigint::{BigUint,BigInt,RandBigInt};
+use num_traits::Zero;
 use hex;
-
-#[cfg(test)]
+use rand::{CryptoRng, RngCore};
 use std::collections::HashMap;
+use crate::error::Error;
 
-pub fn hex_to_number(hex: String) -> BigInt {
-    let hex: Vec<u8> = hex::decode(hex).expect("Decoding failed");
-    BigInt::from(BigUint::from_bytes_be(&hex))
+// Random nonces tried by 'sign_with_rng' before it gives up
+const SIGN_ATTEMPTS: usize = 1000;
+
+pub fn hex_to_number(hex: String) -> Result<BigInt, Error> {
+    let hex: Vec<u8> = hex::decode(hex)?;
+    Ok(BigInt::from(BigUint::from_bytes_be(&hex)))
 }
 
 pub fn calculate_inverse_modulo(k: BigInt, p: BigInt) -> BigInt {
@@ -33,30 +37,26 @@ pub fn extended_euclidean_algorithm(a: BigInt, b: BigInt) -> (BigInt, BigInt, Bi
 }
 
 // y^2 = x^3 + ax + b (mod p)
-#[cfg(test)]
-#[derive(Clone)]
-struct ECurve {
-    a: BigInt,
-    b: BigInt,
-    p: BigInt,
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ECurve {
+    pub a: BigInt,
+    pub b: BigInt,
+    pub p: BigInt,
 }
 
-#[cfg(test)]
 impl ECurve {
     pub fn create(a: BigInt, b: BigInt, p: BigInt) -> Self {
         Self { a, b, p }
     }
 }
 
-#[cfg(test)]
-#[derive(Clone)]
-struct ECPoint {
-    x: BigInt,
-    y: BigInt,
-    curve: ECurve,
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ECPoint {
+    pub x: BigInt,
+    pub y: BigInt,
+    pub curve: ECurve,
 }
 
-#[cfg(test)]
 impl ECPoint {
     pub fn create(x: BigInt, y: BigInt, curve: ECurve) -> Self {
         Self { x, y, curve }
@@ -78,7 +78,8 @@ impl ECPoint {
         left.eq(&right)
     }
 
-    pub fn add_point(&self, point: &ECPoint) -> Self {
+    // Fails when the sum isn't on the curve: one of the points isn't on it, or P + (-P) (the point at infinity)
+    pub fn add_point(&self, point: &ECPoint) -> Result<Self, Error> {
         let one: BigInt = BigInt::from(1);
 
         let p = &self.curve.p;
@@ -103,17 +104,20 @@ impl ECPoint {
 
         let new_point = ECPoint::create(x3, y3, self.curve.clone());
         if !new_point.is_point_on_curve() {
-            panic!("New point isn't on curve")
+            return Err(Error::PointNotOnCurve);
         }
 
-        new_point
+        Ok(new_point)
     }
 
-    pub fn multiply_point(&self, scalar: BigInt) -> Self {
+    pub fn multiply_point(&self, scalar: BigInt) -> Result<Self, Error> {
         let one: BigInt = BigInt::from(1);
 
+        if scalar < one {
+            return Err(Error::InvalidArgument(format!("Scalar must be positive, got: {}", scalar)));
+        }
         if scalar == one {
-            return self.clone();
+            return Ok(self.clone());
         }
 
         let mut multiply_result = self.clone();
@@ -125,7 +129,7 @@ impl ECPoint {
         let mut multiplier = BigInt::from(2);
     
         while multiplier <= scalar {
-            multiply_result = multiply_result.add_point(&multiply_result);
+            multiply_result = multiply_result.add_point(&multiply_result)?;
 
             multiply_results.insert(multiplier.clone(), multiply_result.clone());
 
@@ -138,30 +142,47 @@ impl ECPoint {
 
         multiplier = multiply_results_sorted[0].0.clone();
         for (key, point) in multiply_results_sorted.iter().skip(1) {
-            let multiplier_new = &multiplier + key.clone();
+            let multiplier_new = &multiplier + *key;
             if multiplier_new > scalar {
                 continue;
             }
 
-            multiply_result = multiply_result.add_point(&point);
+            multiply_result = multiply_result.add_point(&point)?;
             multiplier = multiplier_new;
         }
     
-        multiply_result
+        Ok(multiply_result)
     }
 
-    pub fn sign(&self, d: BigInt, k: BigInt, z: BigInt) -> (BigInt, BigInt) {
+    pub fn sign(&self, d: BigInt, k: BigInt, z: BigInt) -> Result<(BigInt, BigInt), Error> {
         let one: BigInt = BigInt::from(1);
         let g = self.clone();
 
-        let kg = g.multiply_point(k.clone());
+        let kg = g.multiply_point(k.clone())?;
         let r = kg.x.modpow(&one, &g.curve.p);
         let k_pow_minus_one = calculate_inverse_modulo(k.clone(), g.curve.p.clone());
         let s = (k_pow_minus_one * (z + &r * d)) % g.curve.p;
 
-        return (r, s)
+        Ok((r, s))
     }
 
+    // 'sign' with a random nonce k from [1, p), drawn again when r or s is zero or k*G is the point
+    // at infinity (the order of G may be below p). Like 'sign', s is taken modulo p.
+    pub fn sign_with_rng<R: RngCore + CryptoRng>(&self, d: BigInt, z: BigInt, rng: &mut R) -> Result<(BigInt, BigInt), Error> {
+        let mut last_error = Error::InvalidArgument(format!("No nonce gives non-zero r and s in {} attempts", SIGN_ATTEMPTS));
+        for _ in 0..SIGN_ATTEMPTS {
+            let k = rng.gen_bigint_range(&BigInt::from(1), &self.curve.p);
+            match self.sign(d.clone(), k, z.clone()) {
+                Ok((r, s)) if !r.is_zero() && !s.is_zero() => return Ok((r, s)),
+                Ok(_) => {}
+                Err(error) => last_error = error,
+            }
+        }
+
+        Err(last_error)
+    }
+
+    // A signature that leads off the curve (or to the point at infinity) is invalid
     pub fn sign_verify(&self, r: BigInt, s: BigInt, z: BigInt, q: ECPoint) -> bool {
         let g = self.clone();
 
@@ -169,18 +190,23 @@ impl ECPoint {
         let u1 = (z * &w) % &g.curve.p;
         let u2 = (&r * &w) % &g.curve.p;
 
-        let u1g = g.multiply_point(u1.clone());
-        let u2q = q.multiply_point(u2.clone());
-
-        let signature_point = u1g.add_point(&u2q);
+        let signature_point = g.multiply_point(u1.clone())
+            .and_then(|u1g| q.multiply_point(u2.clone()).and_then(|u2q| u1g.add_point(&u2q)));
 
-        return r == signature_point.x % &g.curve.p;
+        match signature_point {
+            Ok(signature_point) => r == signature_point.x % &g.curve.p,
+            Err(_) => false,
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use crate::kdf::kdf::shared_secret_to_key;
+    use crate::sha1::sha1::Sha1Realization32;
 
     #[test]
     fn test_points() {
@@ -193,41 +219,52 @@ mod tests {
         // P(3,10); Q(9,7); P+Q = R(17,20)
         let p = ECPoint::create(BigInt::from(3), BigInt::from(10), curve.clone());
         let q = ECPoint::create(BigInt::from(9), BigInt::from(7), curve.clone());
-        let r = p.add_point(&q);
+        let r = p.add_point(&q).unwrap();
         assert_eq!(r.x, BigInt::from(17));
         assert_eq!(r.y, BigInt::from(20));
 
         // P(12,19); Q(5,4); P+Q = R(12,4)
         let p = ECPoint::create(BigInt::from(12), BigInt::from(19), curve.clone());
         let q = ECPoint::create(BigInt::from(5), BigInt::from(4), curve.clone());
-        let r = p.add_point(&q);
+        let r = p.add_point(&q).unwrap();
         assert_eq!(r.x, BigInt::from(12));
         assert_eq!(r.y, BigInt::from(4));
 
         // P(5,4); P*P = R(17,20)
         let p = ECPoint::create(BigInt::from(5), BigInt::from(4), curve.clone());
-        let p2 = p.add_point(&p);
+        let p2 = p.add_point(&p).unwrap();
         assert_eq!(p2.x, BigInt::from(17));
         assert_eq!(p2.y, BigInt::from(20));
 
         // P(5,4); 2*P+P = R(13,16)
-        let p3 = p.add_point(&p2);
+        let p3 = p.add_point(&p2).unwrap();
         assert_eq!(p3.x, BigInt::from(13));
         assert_eq!(p3.y, BigInt::from(16));
 
         // P(5,4); 2*P = R(17,20)
-        let p_mul_2 = p.multiply_point(BigInt::from(2));
+        let p_mul_2 = p.multiply_point(BigInt::from(2)).unwrap();
         assert_eq!(p_mul_2.x, BigInt::from(17));
         assert_eq!(p_mul_2.y, BigInt::from(20));
 
         // P(5,4); 3*P = R(13,16)
-        let p_mul_3 = p.multiply_point(BigInt::from(3));
+        let p_mul_3 = p.multiply_point(BigInt::from(3)).unwrap();
         assert_eq!(p_mul_3.x, BigInt::from(13));
         assert_eq!(p_mul_3.y, BigInt::from(16));
 
         // Check is P on curve
         let p = ECPoint::create(BigInt::from(4), BigInt::from(4), curve.clone());
         assert_eq!(p.is_point_on_curve(), false);
+
+        // Points off the curve, P + (-P) and a zero scalar are errors instead of panics
+        let q = ECPoint::create(BigInt::from(5), BigInt::from(4), curve.clone());
+        assert_eq!(p.add_point(&q), Err(Error::PointNotOnCurve));
+        let minus_q = ECPoint::create(BigInt::from(5), BigInt::from(19), curve.clone());
+        assert_eq!(q.add_point(&minus_q), Err(Error::PointNotOnCurve));
+        assert!(matches!(q.multiply_point(BigInt::from(0)), Err(Error::InvalidArgument(_))));
+
+        assert_eq!(hex_to_number("1a2b".to_string()), Ok(BigInt::from(0x1a2b)));
+        assert!(matches!(hex_to_number("1a2".to_string()), Err(Error::InvalidHex(_))));
+        assert!(matches!(hex_to_number("zz".to_string()), Err(Error::InvalidHex(_))));
     }
 
     #[test]
@@ -245,25 +282,31 @@ mod tests {
         let user_b_private_key = BigInt::from(7);
 
         // <user_a_private_key>*G = <user_a_public_key>(13,22)
-        let user_a_public_key = g.multiply_point(user_a_private_key.clone());
+        let user_a_public_key = g.multiply_point(user_a_private_key.clone()).unwrap();
         assert_eq!(user_a_public_key.x, BigInt::from(13));
         assert_eq!(user_a_public_key.y, BigInt::from(22));
 
         // <user_b_private_key>*G = <user_b_public_key>(13,22)
-        let user_b_public_key = g.multiply_point(user_b_private_key.clone());
+        let user_b_public_key = g.multiply_point(user_b_private_key.clone()).unwrap();
         assert_eq!(user_b_public_key.x, BigInt::from(17));
         assert_eq!(user_b_public_key.y, BigInt::from(8));
 
         // Main assertion
         // <user_b_public_key>*<user_a_private_key> = <user_a_private_key_diffie>(15,5)
-        let user_a_private_key_diffie = user_b_public_key.multiply_point(user_a_private_key);
+        let user_a_private_key_diffie = user_b_public_key.multiply_point(user_a_private_key).unwrap();
         assert_eq!(user_a_private_key_diffie.x, BigInt::from(15));
         assert_eq!(user_a_private_key_diffie.y, BigInt::from(5));
 
         // <user_A_public_key>*<user_b_private_key> = <user_b_private_key_diffie>(15,5)
-        let user_b_private_key_diffie = user_a_public_key.multiply_point(user_b_private_key);
+        let user_b_private_key_diffie = user_a_public_key.multiply_point(user_b_private_key).unwrap();
         assert_eq!(user_b_private_key_diffie.x, BigInt::from(15));
         assert_eq!(user_b_private_key_diffie.y, BigInt::from(5));
+
+        // Both sides derive the same symmetric key from the shared x coordinate
+        let field_size = curve.p.bits().div_ceil(8) as usize;
+        let user_a_key = shared_secret_to_key::<Sha1Realization32>(&user_a_private_key_diffie.x, field_size, &[], b"ecdh", 16).unwrap();
+        let user_b_key = shared_secret_to_key::<Sha1Realization32>(&user_b_private_key_diffie.x, field_size, &[], b"ecdh", 16).unwrap();
+        assert_eq!(user_a_key, user_b_key);
     }
 
     #[test]
@@ -282,19 +325,52 @@ mod tests {
         let k = BigInt::from(19);
         //// End
 
-        let hex_num = hex_to_number(hex_string);
+        let hex_num = hex_to_number(hex_string).unwrap();
 
-        let public_key: ECPoint = g.multiply_point(private_key.clone());
+        let public_key: ECPoint = g.multiply_point(private_key.clone()).unwrap();
         assert_eq!(public_key.x, BigInt::from(13));
         assert_eq!(public_key.y, BigInt::from(22));
 
-        let (r, s) = g.sign(private_key, k, hex_num.clone());
+        let (r, s) = g.sign(private_key, k, hex_num.clone()).unwrap();
         assert_eq!(r, BigInt::from(9));
         assert_eq!(s, BigInt::from(19));
 
         let is_valid = g.sign_verify(r, s, hex_num.clone(), public_key);
         assert_eq!(is_valid, true);
     }
+
+    #[test]
+    fn test_ecdsa_sign_with_rng() {
+        // Curve and keys of 'test_ecdsa_sign', the nonce comes from a seeded generator
+        let curve = ECurve::create(BigInt::from(-2), BigInt::from(15), BigInt::from(23));
+        let g = ECPoint::create(BigInt::from(4), BigInt::from(5), curve.clone());
+        let private_key = BigInt::from(3);
+        let public_key = g.multiply_point(private_key.clone()).unwrap();
+        let hex_num = hex_to_number(hex::encode("Hello World!")).unwrap();
+
+        let mut rng = StdRng::seed_from_u64(2020);
+        let (r, s) = g.sign_with_rng(private_key.clone(), hex_num.clone(), &mut rng).unwrap();
+        assert_eq!((r.clone(), s.clone()), (BigInt::from(16), BigInt::from(1)));
+        assert!(g.sign_verify(r.clone(), s.clone(), hex_num.clone(), public_key.clone()));
+
+        // Same seed, same signature; other nonces give other valid signatures
+        let mut rng = StdRng::seed_from_u64(2020);
+        assert_eq!(g.sign_with_rng(private_key.clone(), hex_num.clone(), &mut rng).unwrap(), (r, s));
+        for _ in 0..20 {
+            let (r, s) = g.sign_with_rng(private_key.clone(), hex_num.clone(), &mut rng).unwrap();
+            assert!(g.sign_verify(r, s, hex_num.clone(), public_key.clone()));
+        }
+
+        // G(13,16) on y^2 = x^3 + x + 1 (mod 23) has order 7: k = 7 and 14 reach the point at infinity
+        let curve = ECurve::create(BigInt::from(1), BigInt::from(1), BigInt::from(23));
+        let g = ECPoint::create(BigInt::from(13), BigInt::from(16), curve);
+        assert_eq!(g.multiply_point(BigInt::from(7)), Err(Error::PointNotOnCurve));
+        for seed in 0..200 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let (r, s) = g.sign_with_rng(private_key.clone(), hex_num.clone(), &mut rng).unwrap();
+            assert!(!r.is_zero() && !s.is_zero());
+        }
+    }
 }
 
 // cargo test -- ec_wrapper --nocapture
\ No newline at end of file
@@ -0,0 +1,100 @@
+// Errors of the crate's public APIs: bad input is reported to the caller instead of a panic
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    // Odd length or a non-hex character
+    InvalidHex(hex::FromHexError),
+    // Argument out of its range: iteration count, key length, scalar, ...
+    InvalidArgument(String),
+    // File or directory that can't be read, 'message' is the text of the io::Error
+    Io { path: PathBuf, message: String },
+    // Malformed text or serialized input: UUID, otpauth URI, base32, CAVP response file, ...
+    InvalidFormat(String),
+    // Midstate of a message with trailing bits (not a whole number of bytes)
+    UnalignedMessage { trailing_bits: u8 },
+    // Result of a point operation isn't on the curve, e.g. P + (-P) is the point at infinity
+    PointNotOnCurve,
+    PrimitiveRootNotFound,
+    // Hash_DRBG made 'interval' requests since the last reseed
+    ReseedRequired { interval: u64 },
+    // Entropy source of a DRBG failed to give the input
+    EntropySourceFailed(String),
+    // Version byte of a serialized ciphertext that isn't supported
+    UnsupportedVersion { version: u8 },
+    // Tag of an authenticated ciphertext doesn't match: wrong key or modified data
+    AuthenticationFailed,
+    // fips140 checks: the count of zeros, the longest series, the count of the series of one length
+    // ('length' is the last length of the limits for the longer series) and the poker coefficient
+    MonobitTest { zeros: u16, range: [u16; 2] },
+    LongRunTest { length: u16, max: u16 },
+    RunsTest { length: u16, count: u16, range: [u16; 2] },
+    PokerTest { coefficient: f32, range: [f32; 2] },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidHex(error) => write!(f, "Decoding failed: {}", error),
+            Error::InvalidArgument(message) => write!(f, "{}", message),
+            Error::InvalidFormat(message) => write!(f, "{}", message),
+            Error::Io { path, message } => write!(f, "Can't read {}: {}", path.display(), message),
+            Error::UnalignedMessage { trailing_bits } => write!(f, "Midstate of a message with {} trailing bits can't be exported", trailing_bits),
+            Error::PointNotOnCurve => write!(f, "New point isn't on curve"),
+            Error::PrimitiveRootNotFound => write!(f, "Primitive root isn't found"),
+            Error::ReseedRequired { interval } => write!(f, "Reseed is required after {} requests", interval),
+            Error::EntropySourceFailed(message) => write!(f, "Entropy source failed: {}", message),
+            Error::UnsupportedVersion { version } => write!(f, "Unsupported ciphertext version: {}", version),
+            Error::AuthenticationFailed => write!(f, "Ciphertext authentication failed"),
+            Error::MonobitTest { zeros, range } => write!(f, "Too many monobits: {}. Available from {} to {} monobits", zeros, range[0], range[1]),
+            Error::LongRunTest { length, max } => write!(f, "Too big series: {}. Max available: {}", length, max),
+            Error::RunsTest { length, count, range } => write!(f, "Series #{} isn't in range [{},{}], entry count: {}", length, range[0], range[1], count),
+            Error::PokerTest { coefficient, range } => write!(f, "Poker coefficient isn't in range: [{},{}]. Actual value: {}", range[0], range[1], coefficient),
+        }
+    }
+}
+
+impl Error {
+    pub fn io(path: &Path, error: io::Error) -> Self {
+        Error::Io { path: path.to_path_buf(), message: error.to_string() }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::InvalidHex(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<hex::FromHexError> for Error {
+    fn from(error: hex::FromHexError) -> Self {
+        Error::InvalidHex(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_messages() {
+        let error = Error::from(hex::decode("abc").unwrap_err());
+        assert_eq!(error.to_string(), "Decoding failed: Odd number of digits");
+        assert!(std::error::Error::source(&error).is_some());
+
+        assert_eq!(Error::PointNotOnCurve.to_string(), "New point isn't on curve");
+        assert_eq!(Error::ReseedRequired { interval: 10 }.to_string(), "Reseed is required after 10 requests");
+        assert_eq!(Error::UnsupportedVersion { version: 2 }.to_string(), "Unsupported ciphertext version: 2");
+        assert_eq!(Error::AuthenticationFailed.to_string(), "Ciphertext authentication failed");
+        assert_eq!(Error::MonobitTest { zeros: 20000, range: [9654, 10346] }.to_string(), "Too many monobits: 20000. Available from 9654 to 10346 monobits");
+        assert_eq!(Error::RunsTest { length: 6, count: 12, range: [90, 350] }.to_string(), "Series #6 isn't in range [90,350], entry count: 12");
+
+        let error = Error::io(Path::new("missing.rsp"), io::Error::new(io::ErrorKind::NotFound, "not found"));
+        assert_eq!(error.to_string(), "Can't read missing.rsp: not found");
+    }
+}
@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 use num_traits::pow;
+use crate::error::Error;
 
 pub fn fips140(
     bits: &Vec<u8>,
@@ -7,11 +8,16 @@ pub fn fips140(
     max_series_count: HashMap<u16, [u16; 2]>,
     max_serries_length: u16,
     max_pocker_coef_range: [f32; 2]
-) -> bool {
+) -> Result<(), Error> {
+    // The counts and the limits are u16
+    if bits.len() > u16::MAX as usize {
+        return Err(Error::InvalidArgument(format!("At most {} bits are supported, got: {}", u16::MAX, bits.len())));
+    }
+
     let zero_monobits = calc_zero_monobit(&bits);
     // Check monobits
     if !(max_monobits[0] <= zero_monobits && zero_monobits <= max_monobits[1]) {
-        panic!("Too many monobits: {}. Available from {} to {} monobits", zero_monobits, max_monobits[0], max_monobits[1])
+        return Err(Error::MonobitTest { zeros: zero_monobits, range: max_monobits });
     }
     // END
 
@@ -19,18 +25,20 @@ pub fn fips140(
     let (series_count, biggest_serries_length) = calc_series_count(&bits);
 
     if biggest_serries_length > max_serries_length {
-        panic!("Too big series: {}. Max available: {}", biggest_serries_length, max_serries_length);
+        return Err(Error::LongRunTest { length: biggest_serries_length, max: max_serries_length });
     }
     // END
 
     // START check series count, without last
     let mut keys = max_series_count.keys().collect::<Vec<_>>();
     keys.sort();
-    let repetition_count_to_sum_from = *keys.last().unwrap();
+    let Some(&&repetition_count_to_sum_from) = keys.last() else {
+        return Err(Error::InvalidArgument("Series count limits are empty".to_string()));
+    };
 
     let mut other_repetition_count = 0;
     for (repetition_count, entrances_count) in &series_count {
-        if repetition_count >= repetition_count_to_sum_from {
+        if *repetition_count >= repetition_count_to_sum_from {
             other_repetition_count += entrances_count;
             continue;
         }
@@ -38,10 +46,7 @@ pub fn fips140(
         let valid_entrance_range = max_series_count.get(repetition_count).unwrap_or(&[0,0]);
 
         if !(valid_entrance_range[0] <= *entrances_count && *entrances_count <= valid_entrance_range[1]) {
-            panic!(
-                "Series #{} isn't in range [{},{}], entry count: {}",
-                repetition_count, valid_entrance_range[0], valid_entrance_range[1], entrances_count
-            )
+            return Err(Error::RunsTest { length: *repetition_count, count: *entrances_count, range: *valid_entrance_range });
         }
     }
     // END
@@ -49,18 +54,18 @@ pub fn fips140(
     // START check last series
     let valid_entrance_range = max_series_count.get(&repetition_count_to_sum_from).unwrap_or(&[0,0]);
     if !(valid_entrance_range[0] <= other_repetition_count && other_repetition_count <= valid_entrance_range[1]) {
-        panic!("Last series isn't in range [{},{}], entry count: {}", valid_entrance_range[0], valid_entrance_range[1], other_repetition_count)
+        return Err(Error::RunsTest { length: repetition_count_to_sum_from, count: other_repetition_count, range: *valid_entrance_range });
     }
     // END
 
     // START check Pocker coefficient
     let pocker_coef = calc_pocker_coeff(&bits, 4);
     if !(max_pocker_coef_range[0] <= pocker_coef && pocker_coef <= max_pocker_coef_range[1]) {
-        panic!("Pocker coefficient isn't in range: [{},{}]. Actual value: {}", max_pocker_coef_range[0], max_pocker_coef_range[1], pocker_coef);
+        return Err(Error::PokerTest { coefficient: pocker_coef, range: max_pocker_coef_range });
     }
     // END
 
-    return true;
+    Ok(())
 }
 
 fn calc_zero_monobit(bits: &Vec<u8>) -> u16 {
@@ -205,17 +210,12 @@ mod tests {
 
         //     let max_series_count_clone = max_series_count.clone();
 
-        //     let result = std::panic::catch_unwind(|| {
-        //         fips140(&bits, [9654,10346], max_series_count_clone, 13, [1.03,57.4]);
-        //     });
+        //     let result = fips140(&bits, [9654,10346], max_series_count_clone, 13, [1.03,57.4]);
         
-        //     match result {
-        //         Ok(_) => {
-        //             println!("Function call completed successfully.");
-        //             println!("{}", hex);
-        //             break;
-        //         }
-        //         Err(e) => {}
+        //     if result.is_ok() {
+        //         println!("Function call completed successfully.");
+        //         println!("{}", hex);
+        //         break;
         //     }
         // }
 
@@ -226,7 +226,7 @@ mod tests {
 
         let result = fips140(&bits, [9654,10346], max_series_count, 13, [1.03,57.4]);
         assert_eq!(bits.len(), 20000);
-        assert_eq!(result, true);
+        assert_eq!(result, Ok(()));
 
     }
 
@@ -297,7 +297,35 @@ mod tests {
         max_series_count.insert(2, [0,100]);
         max_series_count.insert(3, [1,3]);
 
-        let res: bool = fips140(&vec![1,0,1,1,0,1,0,0,0,0,1,0,0,0,1,1,1,1,1,1,1,0], [1,100], max_series_count, 120, [1.0,40.0]);
-        assert_eq!(res, true);
+        let res = fips140(&vec![1,0,1,1,0,1,0,0,0,0,1,0,0,0,1,1,1,1,1,1,1,0], [1,100], max_series_count, 120, [1.0,40.0]);
+        assert_eq!(res, Ok(()));
+    }
+
+    #[test]
+    fn test_fip140_failures() {
+        let bits = vec![1,0,1,1,0,1,0,0,0,0,1,0,0,0,1,1,1,1,1,1,1,0];
+        let mut max_series_count: HashMap<u16, [u16; 2]> = HashMap::new();
+        max_series_count.insert(1, [0,100]);
+        max_series_count.insert(2, [0,100]);
+        max_series_count.insert(3, [1,3]);
+
+        // Every check reports what failed instead of a panic
+        assert_eq!(fips140(&bits, [1,5], max_series_count.clone(), 120, [1.0,40.0]), Err(Error::MonobitTest { zeros: 10, range: [1,5] }));
+        assert_eq!(fips140(&bits, [1,100], max_series_count.clone(), 3, [1.0,40.0]), Err(Error::LongRunTest { length: 7, max: 3 }));
+        assert!(matches!(fips140(&bits, [1,100], max_series_count.clone(), 120, [30.0,40.0]), Err(Error::PokerTest { .. })));
+
+        let mut strict_series_count = max_series_count.clone();
+        strict_series_count.insert(1, [0,1]);
+        assert!(matches!(fips140(&bits, [1,100], strict_series_count, 120, [1.0,40.0]), Err(Error::RunsTest { length: 1, range: [0,1], .. })));
+
+        // Limits for the longer series
+        let mut strict_series_count = max_series_count.clone();
+        strict_series_count.insert(3, [5,6]);
+        assert!(matches!(fips140(&bits, [1,100], strict_series_count, 120, [1.0,40.0]), Err(Error::RunsTest { length: 3, range: [5,6], .. })));
+
+        assert!(matches!(fips140(&bits, [1,100], HashMap::new(), 120, [1.0,40.0]), Err(Error::InvalidArgument(_))));
+
+        // 70,000 zeros would overflow the u16 counters
+        assert!(matches!(fips140(&vec![0u8; 70000], [1,100], max_series_count, 120, [1.0,40.0]), Err(Error::InvalidArgument(_))));
     }
 }
\ No newline at end of file
@@ -0,0 +1,181 @@
+use digest::Digest;
+use digest::core_api::BlockSizeUser;
+
+const IPAD: u8 = 0x36;
+const OPAD: u8 = 0x5c;
+
+// HMAC(K, m) = H((K' ^ opad) || H((K' ^ ipad) || m)), RFC 2104
+// Where K': key padded with zeros to the block size, keys longer than the block are hashed first
+// Works with any hash implementing the 'digest' traits: Sha1Realization32, the SHA-2 realizations or library hashes
+#[derive(Clone)]
+pub struct Hmac<D: Digest + BlockSizeUser + Clone> {
+    inner: D,
+    inner_keyed: D,
+    outer_keyed: D,
+}
+
+impl<D: Digest + BlockSizeUser + Clone> Hmac<D> {
+    pub fn new(key: &[u8]) -> Self {
+        let block_size = D::block_size();
+
+        let mut padded_key = vec![0u8; block_size];
+        if key.len() > block_size {
+            let hashed_key = D::digest(key);
+            padded_key[..hashed_key.len()].copy_from_slice(&hashed_key);
+        } else {
+            padded_key[..key.len()].copy_from_slice(key);
+        }
+
+        let mut inner_keyed = D::new();
+        inner_keyed.update(padded_key.iter().map(|byte| byte ^ IPAD).collect::<Vec<u8>>());
+
+        let mut outer_keyed = D::new();
+        outer_keyed.update(padded_key.iter().map(|byte| byte ^ OPAD).collect::<Vec<u8>>());
+
+        Hmac { inner: inner_keyed.clone(), inner_keyed, outer_keyed }
+    }
+
+    // One-shot tag for a message
+    pub fn mac(key: &[u8], message: &[u8]) -> Vec<u8> {
+        Hmac::<D>::new(key).update(message).finalize()
+    }
+
+    pub fn update(&mut self, data: &[u8]) -> &mut Self {
+        self.inner.update(data);
+        self
+    }
+
+    // Returns the tag, after that the HMAC is ready for a new message with the same key
+    pub fn finalize(&mut self) -> Vec<u8> {
+        let inner = std::mem::replace(&mut self.inner, self.inner_keyed.clone());
+
+        let mut outer = self.outer_keyed.clone();
+        outer.update(inner.finalize());
+
+        outer.finalize().to_vec()
+    }
+
+    // Compares the tag in constant time
+    pub fn verify(&mut self, tag: &[u8]) -> bool {
+        constant_time_eq(&self.finalize(), tag)
+    }
+
+    // Compares the leftmost bytes of the tag in constant time. RFC 2104 recommends
+    // not less than half of the hash output and not less than 80 bits.
+    pub fn verify_truncated(&mut self, tag: &[u8]) -> bool {
+        let expected = self.finalize();
+
+        if tag.len() > expected.len() || tag.len() < 10 || tag.len() * 2 < expected.len() {
+            return false;
+        }
+
+        constant_time_eq(&expected[..tag.len()], tag)
+    }
+}
+
+// Time doesn't depend on the position of the first differing byte, only on the length
+pub fn constant_time_eq(left: &[u8], right: &[u8]) -> bool {
+    if left.len() != right.len() {
+        return false;
+    }
+
+    let difference = left.iter().zip(right.iter()).fold(0u8, |acc, (l, r)| acc | (l ^ r));
+
+    std::hint::black_box(difference) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sha1::sha1::Sha1Realization32;
+    use crate::sha2::sha2::{Sha256Realization32, Sha512Realization64};
+    use crate::elgamal::elgamal::encode;
+    use num_bigint::BigUint;
+
+    type HmacSha1 = Hmac<Sha1Realization32>;
+
+    #[test]
+    fn test_hmac_sha1_rfc_2202() {
+        let cases: Vec<(Vec<u8>, Vec<u8>, &str)> = vec![
+            (vec![0x0b; 20], b"Hi There".to_vec(), "b617318655057264e28bc0b6fb378c8ef146be00"),
+            (b"Jefe".to_vec(), b"what do ya want for nothing?".to_vec(), "effcdf6ae5eb2fa2d27416d5f184df9c259a7c79"),
+            (vec![0xaa; 20], vec![0xdd; 50], "125d7342b9ac11cd91a39af48aa17b4f63f175d3"),
+            ((0x01..=0x19).collect(), vec![0xcd; 50], "4c9007f4026250c6bc8414f9bf50c86c2d7235da"),
+            (vec![0x0c; 20], b"Test With Truncation".to_vec(), "4c1a03424b55e07fe7f27be1d58bb9324a9a5a04"),
+            (vec![0xaa; 80], b"Test Using Larger Than Block-Size Key - Hash Key First".to_vec(), "aa4ae5e15272d00e95705637ce8a3b55ed402112"),
+            (
+                vec![0xaa; 80],
+                b"Test Using Larger Than Block-Size Key and Larger Than One Block-Size Data".to_vec(),
+                "e8e99d0f45237d786d6bbaa7965c7808bbff1a91"
+            ),
+        ];
+
+        for (key, data, expected) in cases.iter() {
+            assert_eq!(hex::encode(HmacSha1::mac(key, data)), *expected);
+            assert!(HmacSha1::new(key).update(data).verify(&hex::decode(expected).unwrap()));
+        }
+
+        // Test case 5, truncated to 96 bits
+        assert!(HmacSha1::new(&[0x0c; 20]).update(b"Test With Truncation").verify_truncated(&hex::decode("4c1a03424b55e07fe7f27be1").unwrap()));
+    }
+
+    #[test]
+    fn test_hmac_incremental_and_reuse() {
+        let mut hmac = HmacSha1::new(b"Jefe");
+        hmac.update(b"what do ya ").update(b"want ").update(b"for nothing?");
+        assert_eq!(hex::encode(hmac.finalize()), "effcdf6ae5eb2fa2d27416d5f184df9c259a7c79");
+
+        // Same key, next message
+        hmac.update(b"what do ya want for nothing?");
+        assert_eq!(hex::encode(hmac.finalize()), "effcdf6ae5eb2fa2d27416d5f184df9c259a7c79");
+    }
+
+    #[test]
+    fn test_hmac_verify_rejects() {
+        let tag = HmacSha1::mac(b"Jefe", b"what do ya want for nothing?");
+
+        let mut wrong_tag = tag.clone();
+        wrong_tag[19] ^= 1;
+        assert!(!HmacSha1::new(b"Jefe").update(b"what do ya want for nothing?").verify(&wrong_tag));
+        assert!(!HmacSha1::new(b"jefe").update(b"what do ya want for nothing?").verify(&tag));
+        assert!(!HmacSha1::new(b"Jefe").update(b"what do ya want for nothing!").verify(&tag));
+
+        // Too short or too long tags
+        assert!(!HmacSha1::new(b"Jefe").update(b"what do ya want for nothing?").verify(&tag[..19]));
+        assert!(!HmacSha1::new(b"Jefe").update(b"what do ya want for nothing?").verify_truncated(&tag[..9]));
+        assert!(!HmacSha1::new(b"Jefe").update(b"what do ya want for nothing?").verify_truncated(&[tag.clone(), vec![0]].concat()));
+
+        assert!(constant_time_eq(b"", b""));
+        assert!(!constant_time_eq(b"a", b"ab"));
+    }
+
+    #[test]
+    fn test_hmac_sha2() {
+        // RFC 4231 test case 2 and 6
+        assert_eq!(
+            hex::encode(Hmac::<Sha256Realization32>::mac(b"Jefe", b"what do ya want for nothing?")),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        assert_eq!(
+            hex::encode(Hmac::<Sha512Realization64>::mac(&[0xaa; 131], b"Test Using Larger Than Block-Size Key - Hash Key First")),
+            "80b24263c7c1a3ebb71493c1dd7be8b49b46d1f41b4aeec1121b013783f8f3526b56d037e05f2598bd0fd2215d6a1e5295e64f73f63f0aec8b915a985d786598"
+        );
+    }
+
+    #[test]
+    fn test_hmac_elgamal_ciphertext() {
+        // Encrypt-then-MAC over the (a, b) pair of the ElGamal ciphertext
+        let p = BigUint::from(23u32);
+        let g = BigUint::from(5u32);
+        let public_key = BigUint::from(17u32);
+        let key = b"shared authentication key";
+
+        let (a, b) = encode(&BigUint::from(3u32), &p, &g, &public_key).unwrap();
+        let ciphertext = [a.to_bytes_be(), b.to_bytes_be()].concat();
+        let tag = HmacSha1::mac(key, &ciphertext);
+        assert!(HmacSha1::new(key).update(&ciphertext).verify(&tag));
+
+        let tampered = [a.to_bytes_be(), ((b + 1u32) % &p).to_bytes_be()].concat();
+        assert!(!HmacSha1::new(key).update(&tampered).verify(&tag));
+    }
+}
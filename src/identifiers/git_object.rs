@@ -0,0 +1,209 @@
+// Git object IDs: SHA-1 of "<type> <content length>\0<content>", the same as 'git hash-object'
+use std::fmt;
+use crate::sha1::sha1::{Sha1Realization32, DIGEST_SIZE};
+
+pub type ObjectId = [u8; DIGEST_SIZE];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ObjectKind {
+    Blob,
+    Tree,
+    Commit,
+}
+
+impl fmt::Display for ObjectKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ObjectKind::Blob => "blob",
+            ObjectKind::Tree => "tree",
+            ObjectKind::Commit => "commit",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// Modes of the tree entries as git writes them (a directory is "40000", without the leading zero)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileMode {
+    Regular,
+    Executable,
+    Symlink,
+    Directory,
+    Submodule,
+}
+
+impl FileMode {
+    fn as_str(&self) -> &'static str {
+        match self {
+            FileMode::Regular => "100644",
+            FileMode::Executable => "100755",
+            FileMode::Symlink => "120000",
+            FileMode::Directory => "40000",
+            FileMode::Submodule => "160000",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TreeEntry {
+    pub mode: FileMode,
+    pub name: String,
+    pub id: ObjectId,
+}
+
+// Author or committer line: "Name <email> 1700000000 +0200"
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Signature {
+    pub name: String,
+    pub email: String,
+    // Seconds since the Unix epoch
+    pub timestamp: i64,
+    // Time zone offset from UTC in minutes, e.g. -330 for -0530
+    pub offset_minutes: i32,
+}
+
+impl fmt::Display for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.offset_minutes < 0 { '-' } else { '+' };
+        let offset = self.offset_minutes.abs();
+        write!(f, "{} <{}> {} {}{:02}{:02}", self.name, self.email, self.timestamp, sign, offset / 60, offset % 60)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Commit {
+    pub tree: ObjectId,
+    pub parents: Vec<ObjectId>,
+    pub author: Signature,
+    pub committer: Signature,
+    // Message as stored by git, usually ends with '\n'
+    pub message: String,
+}
+
+// Object with the git header: "<type> <content length>\0<content>"
+pub fn object_bytes(kind: ObjectKind, content: &[u8]) -> Vec<u8> {
+    let mut bytes = format!("{} {}\0", kind, content.len()).into_bytes();
+    bytes.extend_from_slice(content);
+    bytes
+}
+
+pub fn object_id(kind: ObjectKind, content: &[u8]) -> ObjectId {
+    Sha1Realization32::new()
+        .update(&format!("{} {}\0", kind, content.len()))
+        .update_bytes(content)
+        .finalize()
+        .digest()
+}
+
+pub fn blob_id(content: &[u8]) -> ObjectId {
+    object_id(ObjectKind::Blob, content)
+}
+
+// Tree content: "<mode> <name>\0<20-byte id>" for every entry. Git sorts the entries by name,
+// directory names are compared as if they end with '/'.
+pub fn tree_content(entries: &[TreeEntry]) -> Vec<u8> {
+    let sort_key = |entry: &TreeEntry| {
+        let mut key = entry.name.as_bytes().to_vec();
+        if entry.mode == FileMode::Directory {
+            key.push(b'/');
+        }
+        key
+    };
+
+    let mut sorted: Vec<&TreeEntry> = entries.iter().collect();
+    sorted.sort_by_key(|entry| sort_key(entry));
+
+    let mut content = Vec::new();
+    for entry in sorted {
+        content.extend_from_slice(format!("{} {}\0", entry.mode.as_str(), entry.name).as_bytes());
+        content.extend_from_slice(&entry.id);
+    }
+
+    content
+}
+
+pub fn tree_id(entries: &[TreeEntry]) -> ObjectId {
+    object_id(ObjectKind::Tree, &tree_content(entries))
+}
+
+pub fn commit_content(commit: &Commit) -> Vec<u8> {
+    let mut content = format!("tree {}\n", hex::encode(commit.tree));
+    for parent in commit.parents.iter() {
+        content += &format!("parent {}\n", hex::encode(parent));
+    }
+    content += &format!("author {}\ncommitter {}\n\n{}", commit.author, commit.committer, commit.message);
+
+    content.into_bytes()
+}
+
+pub fn commit_id(commit: &Commit) -> ObjectId {
+    object_id(ObjectKind::Commit, &commit_content(commit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Expected IDs are from 'git hash-object', 'git write-tree' and 'git commit-tree'
+    fn id(hex: &str) -> ObjectId {
+        let mut id = [0u8; DIGEST_SIZE];
+        id.copy_from_slice(&hex::decode(hex).unwrap());
+        id
+    }
+
+    #[test]
+    fn test_git_blob_id() {
+        assert_eq!(hex::encode(blob_id(b"")), "e69de29bb2d1d6434b8b29ae775ad8c2e48c5391");
+        assert_eq!(hex::encode(blob_id(b"hello world\n")), "3b18e512dba79e4c8300dd08aeb37f8e728b8dad");
+        assert_eq!(hex::encode(blob_id(b"what is up, doc?")), "bd9dbf5aae1a3862dd1526723246b20206e5fc37");
+
+        assert_eq!(object_bytes(ObjectKind::Blob, b"abc"), b"blob 3\0abc");
+        let hashed_bytes = Sha1Realization32::new().update_bytes(&object_bytes(ObjectKind::Blob, b"abc")).finalize().digest();
+        assert_eq!(blob_id(b"abc"), hashed_bytes);
+    }
+
+    #[test]
+    fn test_git_tree_id() {
+        assert_eq!(hex::encode(tree_id(&[])), "4b825dc642cb6eb9a060e54bf8d69288fbee4904");
+
+        let src = tree_id(&[TreeEntry { mode: FileMode::Regular, name: "main.rs".to_string(), id: blob_id(b"fn main() {}\n") }]);
+        assert_eq!(hex::encode(src), "5d90422423db5ef6b431e8b9e60e0baf04b8742a");
+
+        // Entries in any order, "src.txt" goes before the directory "src"
+        let entries = [
+            TreeEntry { mode: FileMode::Directory, name: "src".to_string(), id: src },
+            TreeEntry { mode: FileMode::Executable, name: "run.sh".to_string(), id: blob_id(b"#!/bin/sh\necho hi\n") },
+            TreeEntry { mode: FileMode::Regular, name: "src.txt".to_string(), id: blob_id(b"x") },
+            TreeEntry { mode: FileMode::Symlink, name: "link".to_string(), id: blob_id(b"hello.txt") },
+            TreeEntry { mode: FileMode::Regular, name: "hello.txt".to_string(), id: id("3b18e512dba79e4c8300dd08aeb37f8e728b8dad") },
+        ];
+        assert_eq!(hex::encode(tree_id(&entries)), "b2afc6a518177f076dfa5e1e538db726e6eb1ffa");
+    }
+
+    #[test]
+    fn test_git_commit_id() {
+        let alice = Signature { name: "Alice Example".to_string(), email: "alice@example.com".to_string(), timestamp: 1700000000, offset_minutes: 120 };
+        let bob = Signature { name: "Bob Example".to_string(), email: "bob@example.com".to_string(), timestamp: 1700000100, offset_minutes: -330 };
+        assert_eq!(bob.to_string(), "Bob Example <bob@example.com> 1700000100 -0530");
+
+        let first = Commit {
+            tree: id("b2afc6a518177f076dfa5e1e538db726e6eb1ffa"),
+            parents: Vec::new(),
+            author: alice.clone(),
+            committer: bob,
+            message: "Initial commit\n".to_string(),
+        };
+        assert_eq!(hex::encode(commit_id(&first)), "08dba0dbe9f5c35a5cc66861556a984d94e423b6");
+
+        let alice = Signature { timestamp: 1700000200, offset_minutes: 0, ..alice };
+        let second = Commit {
+            tree: tree_id(&[]),
+            parents: vec![commit_id(&first)],
+            author: alice.clone(),
+            committer: alice,
+            message: "Second\n\nBody line\n".to_string(),
+        };
+        assert!(String::from_utf8(commit_content(&second)).unwrap().contains("\nparent 08dba0dbe9f5c35a5cc66861556a984d94e423b6\n"));
+        assert_eq!(hex::encode(commit_id(&second)), "ba3bb2908d867dc363efb171c580ff124a6745bc");
+    }
+}
@@ -0,0 +1,89 @@
+// Name-based UUIDs (RFC 4122, version 5): SHA-1 of namespace UUID || name, the first 16 bytes
+// with the version and variant bits set
+use std::fmt;
+use crate::error::Error;
+use crate::sha1::sha1::Sha1Realization32;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Uuid(pub [u8; 16]);
+
+// Predefined namespaces from RFC 4122, appendix C
+pub const NAMESPACE_DNS: Uuid = Uuid([0x6b, 0xa7, 0xb8, 0x10, 0x9d, 0xad, 0x11, 0xd1, 0x80, 0xb4, 0x00, 0xc0, 0x4f, 0xd4, 0x30, 0xc8]);
+pub const NAMESPACE_URL: Uuid = Uuid([0x6b, 0xa7, 0xb8, 0x11, 0x9d, 0xad, 0x11, 0xd1, 0x80, 0xb4, 0x00, 0xc0, 0x4f, 0xd4, 0x30, 0xc8]);
+pub const NAMESPACE_OID: Uuid = Uuid([0x6b, 0xa7, 0xb8, 0x12, 0x9d, 0xad, 0x11, 0xd1, 0x80, 0xb4, 0x00, 0xc0, 0x4f, 0xd4, 0x30, 0xc8]);
+pub const NAMESPACE_X500: Uuid = Uuid([0x6b, 0xa7, 0xb8, 0x14, 0x9d, 0xad, 0x11, 0xd1, 0x80, 0xb4, 0x00, 0xc0, 0x4f, 0xd4, 0x30, 0xc8]);
+
+impl Uuid {
+    pub fn new_v5(namespace: &Uuid, name: &[u8]) -> Uuid {
+        let digest = Sha1Realization32::new().update_bytes(&namespace.0).update_bytes(name).finalize().digest();
+
+        let mut bytes = [0u8; 16];
+        bytes.copy_from_slice(&digest[..16]);
+        // Version 5 in the high nibble of byte 6, variant '10' in the high bits of byte 8
+        bytes[6] = (bytes[6] & 0x0f) | 0x50;
+        bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+        Uuid(bytes)
+    }
+
+    // "6ba7b810-9dad-11d1-80b4-00c04fd430c8", any case
+    pub fn parse(text: &str) -> Result<Uuid, Error> {
+        let groups: Vec<&str> = text.split('-').collect();
+        let group_lengths: Vec<usize> = groups.iter().map(|group| group.len()).collect();
+        if group_lengths != [8, 4, 4, 4, 12] {
+            return Err(Error::InvalidFormat(format!("UUID must look like xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx, got: {}", text)));
+        }
+
+        let mut bytes = [0u8; 16];
+        hex::decode_to_slice(groups.concat(), &mut bytes)?;
+
+        Ok(Uuid(bytes))
+    }
+
+    pub fn version(&self) -> u8 {
+        self.0[6] >> 4
+    }
+}
+
+impl fmt::Display for Uuid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let hex = hex::encode(self.0);
+        write!(f, "{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uuid_v5() {
+        // Example from RFC 9562 (the update of RFC 4122), appendix A.4
+        let uuid = Uuid::new_v5(&NAMESPACE_DNS, b"www.example.com");
+        assert_eq!(uuid.to_string(), "2ed6657d-e927-568b-95e1-2665a8aea6a2");
+        assert_eq!(uuid.version(), 5);
+        assert_eq!(uuid.0[8] >> 6, 0b10);
+
+        // The same values as Python 'uuid.uuid5'
+        assert_eq!(Uuid::new_v5(&NAMESPACE_DNS, b"python.org").to_string(), "886313e1-3b8a-5372-9b90-0c9aee199e5d");
+        assert_eq!(Uuid::new_v5(&NAMESPACE_URL, b"https://www.rust-lang.org/").to_string(), "6bb70201-305f-585c-97a4-816d5df638c6");
+        assert_eq!(Uuid::new_v5(&NAMESPACE_OID, b"1.3.6.1").to_string(), "1447fa61-5277-5fef-a9b3-fbc6e44f4af3");
+        assert_eq!(Uuid::new_v5(&NAMESPACE_X500, b"cn=John Doe").to_string(), "6b28d549-d26e-5bfc-ae5e-9a39af63dc3f");
+
+        // A generated UUID can be a namespace itself
+        let namespace = Uuid::new_v5(&NAMESPACE_URL, b"https://example.com/users");
+        assert_ne!(Uuid::new_v5(&namespace, b"alice"), Uuid::new_v5(&namespace, b"bob"));
+        assert_eq!(Uuid::new_v5(&namespace, b"alice"), Uuid::new_v5(&namespace, b"alice"));
+    }
+
+    #[test]
+    fn test_uuid_parse() {
+        assert_eq!(Uuid::parse("6ba7b810-9dad-11d1-80b4-00c04fd430c8").unwrap(), NAMESPACE_DNS);
+        assert_eq!(Uuid::parse("6BA7B811-9DAD-11D1-80B4-00C04FD430C8").unwrap(), NAMESPACE_URL);
+        assert_eq!(Uuid::parse(&NAMESPACE_X500.to_string()).unwrap(), NAMESPACE_X500);
+
+        assert!(matches!(Uuid::parse("6ba7b8109dad11d180b400c04fd430c8"), Err(Error::InvalidFormat(_))));
+        assert!(matches!(Uuid::parse("6ba7b810-9dad-11d1-80b4-00c04fd430c"), Err(Error::InvalidFormat(_))));
+        assert!(matches!(Uuid::parse("6ba7b810-9dad-11d1-80b4-00c04fd430cg"), Err(Error::InvalidHex(_))));
+    }
+}
@@ -0,0 +1,182 @@
+use digest::Digest;
+use digest::core_api::BlockSizeUser;
+use num_bigint::{BigInt, Sign};
+use crate::error::Error;
+use crate::hmac::hmac::Hmac;
+use crate::sha1::sha1::Sha1Realization32;
+
+// PBKDF2 (RFC 8018): DK = T1 || T2 || ... , Ti = U1 ^ U2 ^ ... ^ Uc
+// U1 = PRF(password, salt || INT(i)), Uj = PRF(password, Uj-1), where PRF is HMAC over 'D'
+pub fn pbkdf2<D: Digest + BlockSizeUser + Clone>(password: &[u8], salt: &[u8], iterations: u32, key_length: usize) -> Result<Vec<u8>, Error> {
+    let hash_length = <D as Digest>::output_size();
+
+    if iterations == 0 {
+        return Err(Error::InvalidArgument("Iteration count must be positive".to_string()));
+    }
+    if key_length == 0 || key_length as u64 > u32::MAX as u64 * hash_length as u64 {
+        let message = format!("Derived key length must be in range [1, {}], got: {}", u32::MAX as u64 * hash_length as u64, key_length);
+        return Err(Error::InvalidArgument(message));
+    }
+
+    let mut prf = Hmac::<D>::new(password);
+    let mut key: Vec<u8> = Vec::with_capacity(key_length);
+
+    let mut block_index: u32 = 1;
+    while key.len() < key_length {
+        let mut u = prf.update(salt).update(&block_index.to_be_bytes()).finalize();
+        let mut t = u.clone();
+
+        for _ in 1..iterations {
+            u = prf.update(&u).finalize();
+            t.iter_mut().zip(u.iter()).for_each(|(t_byte, u_byte)| *t_byte ^= u_byte);
+        }
+
+        key.extend_from_slice(&t);
+        block_index += 1;
+    }
+
+    key.truncate(key_length);
+    Ok(key)
+}
+
+pub fn pbkdf2_hmac_sha1(password: &[u8], salt: &[u8], iterations: u32, key_length: usize) -> Result<Vec<u8>, Error> {
+    pbkdf2::<Sha1Realization32>(password, salt, iterations, key_length)
+}
+
+// HKDF-Extract (RFC 5869): PRK = HMAC(salt, IKM), empty salt is replaced with HashLen zeros
+pub fn hkdf_extract<D: Digest + BlockSizeUser + Clone>(salt: &[u8], input_key_material: &[u8]) -> Vec<u8> {
+    if salt.is_empty() {
+        return Hmac::<D>::mac(&vec![0u8; <D as Digest>::output_size()], input_key_material);
+    }
+
+    Hmac::<D>::mac(salt, input_key_material)
+}
+
+// HKDF-Expand (RFC 5869): OKM = T(1) || T(2) || ... , T(i) = HMAC(PRK, T(i-1) || info || i)
+pub fn hkdf_expand<D: Digest + BlockSizeUser + Clone>(pseudo_random_key: &[u8], info: &[u8], length: usize) -> Result<Vec<u8>, Error> {
+    let hash_length = <D as Digest>::output_size();
+
+    if length > 255 * hash_length {
+        return Err(Error::InvalidArgument(format!("Output length must be not bigger than {}, got: {}", 255 * hash_length, length)));
+    }
+
+    let mut prf = Hmac::<D>::new(pseudo_random_key);
+    let mut output: Vec<u8> = Vec::with_capacity(length);
+    let mut t: Vec<u8> = vec![];
+
+    let mut counter: u8 = 1;
+    while output.len() < length {
+        t = prf.update(&t).update(info).update(&[counter]).finalize();
+        output.extend_from_slice(&t);
+        counter = counter.wrapping_add(1);
+    }
+
+    output.truncate(length);
+    Ok(output)
+}
+
+pub fn hkdf<D: Digest + BlockSizeUser + Clone>(salt: &[u8], input_key_material: &[u8], info: &[u8], length: usize) -> Result<Vec<u8>, Error> {
+    let pseudo_random_key = hkdf_extract::<D>(salt, input_key_material);
+    hkdf_expand::<D>(&pseudo_random_key, info, length)
+}
+
+// Key material from a Diffie-Hellman shared secret (e.g. the x coordinate of the ECDH point).
+// The secret is encoded as a big-endian number of 'field_size' bytes, like the field elements in SEC 1,
+// so both sides get the same input whatever the leading zeros.
+pub fn shared_secret_to_key<D: Digest + BlockSizeUser + Clone>(
+    shared_secret: &BigInt,
+    field_size: usize,
+    salt: &[u8],
+    info: &[u8],
+    length: usize
+) -> Result<Vec<u8>, Error> {
+    let (sign, bytes) = shared_secret.to_bytes_be();
+    if sign == Sign::Minus {
+        return Err(Error::InvalidArgument("Shared secret can't be negative".to_string()));
+    }
+    if bytes.len() > field_size {
+        return Err(Error::InvalidArgument(format!("Shared secret doesn't fit in {} bytes", field_size)));
+    }
+
+    let mut input_key_material = vec![0u8; field_size - bytes.len()];
+    input_key_material.extend_from_slice(&bytes);
+
+    hkdf::<D>(salt, &input_key_material, info, length)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sha2::sha2::Sha256Realization32;
+
+    #[test]
+    fn test_pbkdf2_hmac_sha1_rfc_6070() {
+        assert_eq!(hex::encode(pbkdf2_hmac_sha1(b"password", b"salt", 1, 20).unwrap()), "0c60c80f961f0e71f3a9b524af6012062fe037a6");
+        assert_eq!(hex::encode(pbkdf2_hmac_sha1(b"password", b"salt", 2, 20).unwrap()), "ea6c014dc72d6f8ccd1ed92ace1d41f0d8de8957");
+        assert_eq!(hex::encode(pbkdf2_hmac_sha1(b"password", b"salt", 4096, 20).unwrap()), "4b007901b765489abead49d926f721d065a429c1");
+        assert_eq!(
+            hex::encode(pbkdf2_hmac_sha1(b"passwordPASSWORDpassword", b"saltSALTsaltSALTsaltSALTsaltSALTsalt", 4096, 25).unwrap()),
+            "3d2eec4fe41c849b80c8d83662c0e44a8b291a964cf2f07038"
+        );
+        assert_eq!(hex::encode(pbkdf2_hmac_sha1(b"pass\0word", b"sa\0lt", 4096, 16).unwrap()), "56fa6aa75548099dcc37d7f03425e0c3");
+        // 16777216 iterations vector is skipped, it takes too long for a unit test
+    }
+
+    #[test]
+    fn test_pbkdf2_zero_iterations() {
+        let result = pbkdf2_hmac_sha1(b"password", b"salt", 0, 20);
+        assert_eq!(result, Err(Error::InvalidArgument("Iteration count must be positive".to_string())));
+        assert!(pbkdf2_hmac_sha1(b"password", b"salt", 1, 0).is_err());
+    }
+
+    #[test]
+    fn test_hkdf_rfc_5869() {
+        let salt: Vec<u8> = (0x00..=0x0c).collect();
+        let info: Vec<u8> = (0xf0..=0xf9).collect();
+
+        // Test case 1, SHA-256
+        let pseudo_random_key = hkdf_extract::<Sha256Realization32>(&salt, &[0x0b; 22]);
+        assert_eq!(hex::encode(&pseudo_random_key), "077709362c2e32df0ddc3f0dc47bba6390b6c73bb50f9c3122ec844ad7c2b3e5");
+        assert_eq!(
+            hex::encode(hkdf_expand::<Sha256Realization32>(&pseudo_random_key, &info, 42).unwrap()),
+            "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865"
+        );
+
+        // Test case 4, SHA-1
+        let pseudo_random_key = hkdf_extract::<Sha1Realization32>(&salt, &[0x0b; 11]);
+        assert_eq!(hex::encode(&pseudo_random_key), "9b6c18c432a7bf8f0e71c8eb88f4b30baa2ba243");
+        assert_eq!(
+            hex::encode(hkdf_expand::<Sha1Realization32>(&pseudo_random_key, &info, 42).unwrap()),
+            "085a01ea1b10f36933068b56efa5ad81a4f14b822f5b091568a9cdd4f155fda2c22e422478d305f3f896"
+        );
+
+        // Test case 7, SHA-1 without salt and info
+        assert_eq!(
+            hex::encode(hkdf::<Sha1Realization32>(&[], &[0x0c; 22], &[], 42).unwrap()),
+            "2c91117204d745f3500d636a62f64f0ab3bae548aa53d423b0d1f27ebba6f5e5673a081d70cce7acfc48"
+        );
+    }
+
+    #[test]
+    fn test_hkdf_expand_too_long() {
+        let result = hkdf_expand::<Sha1Realization32>(&[0; 20], &[], 255 * 20 + 1);
+        assert_eq!(result.unwrap_err().to_string(), "Output length must be not bigger than 5100, got: 5101");
+        assert_eq!(hkdf_expand::<Sha1Realization32>(&[0; 20], &[], 255 * 20).unwrap().len(), 5100);
+    }
+
+    #[test]
+    fn test_shared_secret_to_key() {
+        // x coordinate of the shared point from the ECDH test in 'ec_wrapper'
+        let key = shared_secret_to_key::<Sha1Realization32>(&BigInt::from(15), 1, &[], b"ecdh test", 16).unwrap();
+        assert_eq!(hex::encode(key), "cce289216d1e400a3629af10fe8c5420");
+
+        // Leading zeros are part of the input
+        let short = shared_secret_to_key::<Sha1Realization32>(&BigInt::from(15), 1, &[], b"", 16).unwrap();
+        let padded = shared_secret_to_key::<Sha1Realization32>(&BigInt::from(15), 32, &[], b"", 16).unwrap();
+        assert_ne!(short, padded);
+        assert_eq!(padded, hkdf::<Sha1Realization32>(&[], &[vec![0u8; 31], vec![15]].concat(), b"", 16).unwrap());
+
+        assert!(shared_secret_to_key::<Sha1Realization32>(&BigInt::from(-15), 1, &[], b"", 16).is_err());
+        assert!(shared_secret_to_key::<Sha1Realization32>(&BigInt::from(256), 1, &[], b"", 16).is_err());
+    }
+}
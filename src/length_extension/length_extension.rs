@@ -0,0 +1,165 @@
+// Length-extension attack on SHA-1 used as 'H(secret || message)'. The digest is the full chaining state
+// after the padded message, so hashing can be continued from it without knowing the secret:
+// H(secret || message || glue padding || suffix) is computed from H(secret || message) and the length only.
+use crate::error::Error;
+use crate::md::block_buffer::BlockBuffer;
+use crate::sha1::sha1::{Sha1Realization32, Sha1State, BLOCK_SIZE, DIGEST_SIZE};
+
+const LENGTH_SIZE: usize = 8;
+
+pub struct Sha1Forgery {
+    // Padding that the hash added after the original message, it becomes a part of the forged message
+    pub glue_padding: Vec<u8>,
+    // Bytes to append to the original message: glue padding || suffix
+    pub extension: Vec<u8>,
+    // Valid digest of original message || extension
+    pub digest: [u8; DIGEST_SIZE],
+}
+
+// Padding bytes that SHA-1 (SHA-256 pads the same way) appends to a message of 'message_length' bytes.
+// The bytes come from the padding routine used by 'finalize', not from a separate implementation.
+pub fn glue_padding(message_length: u64) -> Vec<u8> {
+    BlockBuffer::<BLOCK_SIZE>::padding(message_length as u128, LENGTH_SIZE)
+}
+
+// 'known_digest' - SHA-1 of the unknown data (secret || message), 'original_length' - its length in bytes.
+// The secret length is usually guessed, so it's a part of 'original_length'.
+pub fn forge_sha1(known_digest: &[u8; DIGEST_SIZE], original_length: u64, suffix: &[u8]) -> Result<Sha1Forgery, Error> {
+    let glue_padding = glue_padding(original_length);
+    let padded_length = match original_length.checked_add(glue_padding.len() as u64) {
+        Some(length) if length.checked_add(suffix.len() as u64).is_some_and(|length| length <= u64::MAX / 8) => length,
+        _ => return Err(Error::InvalidArgument(format!("Message is too long for SHA-1: {} bytes", original_length))),
+    };
+
+    // The digest is h0..h4 in big-endian, the padded message ends on a block boundary, so nothing is pending
+    let mut chaining_values = [0u32; 5];
+    for (h, chunk_bytes) in chaining_values.iter_mut().zip(known_digest.chunks_exact(4)) {
+        *h = u32::from_be_bytes([chunk_bytes[0], chunk_bytes[1], chunk_bytes[2], chunk_bytes[3]]);
+    }
+    let state = Sha1State { chaining_values, pending: Vec::new(), length: padded_length };
+
+    let digest = Sha1Realization32::from_state(&state)?.update_bytes(suffix).finalize().digest();
+    let extension = [glue_padding.as_slice(), suffix].concat();
+
+    Ok(Sha1Forgery { glue_padding, extension, digest })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hmac::hmac::{constant_time_eq, Hmac};
+
+    // Signs requests with SHA-1(secret || message), the construction broken by length extension
+    struct VulnerableServer {
+        secret: Vec<u8>,
+    }
+
+    impl VulnerableServer {
+        fn sign(&self, message: &[u8]) -> [u8; DIGEST_SIZE] {
+            Sha1Realization32::new().update_bytes(&self.secret).update_bytes(message).finalize().digest()
+        }
+
+        fn verify(&self, message: &[u8], tag: &[u8]) -> bool {
+            constant_time_eq(&self.sign(message), tag)
+        }
+    }
+
+    // The same server with HMAC-SHA1 tags
+    struct HmacServer {
+        secret: Vec<u8>,
+    }
+
+    impl HmacServer {
+        fn sign(&self, message: &[u8]) -> Vec<u8> {
+            Hmac::<Sha1Realization32>::mac(&self.secret, message)
+        }
+
+        fn verify(&self, message: &[u8], tag: &[u8]) -> bool {
+            Hmac::<Sha1Realization32>::new(&self.secret).update(message).verify(tag)
+        }
+    }
+
+    const MESSAGE: &[u8] = b"user=alice&role=user";
+    const SUFFIX: &[u8] = b"&role=admin";
+
+    #[test]
+    fn test_glue_padding() {
+        // "abc": 0x80, 52 zero bytes and the length 24 bits
+        let padding = glue_padding(3);
+        assert_eq!(padding.len(), 61);
+        assert_eq!(padding[0], 0x80);
+        assert!(padding[1..53].iter().all(|byte| *byte == 0));
+        assert_eq!(padding[53..], 24u64.to_be_bytes());
+
+        // The length field still fits in the block, doesn't fit, block boundary
+        assert_eq!(glue_padding(55).len(), 9);
+        assert_eq!(glue_padding(56).len(), 72);
+        assert_eq!(glue_padding(64).len(), 64);
+        assert_eq!(glue_padding(0)[56..], [0; 8]);
+
+        for length in 0..200u64 {
+            assert_eq!((length + glue_padding(length).len() as u64) % 64, 0);
+        }
+    }
+
+    #[test]
+    fn test_forge_matches_real_hash() {
+        let secret = b"0123456789abcdef";
+        let known_digest = Sha1Realization32::new().update_bytes(secret).update_bytes(MESSAGE).finalize().digest();
+
+        let forgery = forge_sha1(&known_digest, (secret.len() + MESSAGE.len()) as u64, SUFFIX).unwrap();
+        assert_eq!(forgery.extension, [forgery.glue_padding.as_slice(), SUFFIX].concat());
+
+        let expected = Sha1Realization32::new()
+            .update_bytes(secret)
+            .update_bytes(MESSAGE)
+            .update_bytes(&forgery.extension)
+            .finalize()
+            .digest();
+        assert_eq!(forgery.digest, expected);
+
+        // Empty suffix and data that ends on a block boundary
+        let known_digest = Sha1Realization32::new().update_bytes(&[0x61; 64]).finalize().digest();
+        let forgery = forge_sha1(&known_digest, 64, b"").unwrap();
+        let expected = Sha1Realization32::new().update_bytes(&[0x61; 64]).update_bytes(&forgery.extension).finalize().digest();
+        assert_eq!(forgery.digest, expected);
+
+        assert!(forge_sha1(&known_digest, u64::MAX - 10, SUFFIX).is_err());
+    }
+
+    #[test]
+    fn test_vulnerable_server_accepts_forgery() {
+        let server = VulnerableServer { secret: b"server secret key".to_vec() };
+        let tag = server.sign(MESSAGE);
+
+        // The attacker doesn't know the secret length and tries them until the server accepts the request
+        let accepted = (0..64u64).find_map(|secret_length| {
+            let forgery = forge_sha1(&tag, secret_length + MESSAGE.len() as u64, SUFFIX).unwrap();
+            let forged_message = [MESSAGE, forgery.extension.as_slice()].concat();
+
+            server.verify(&forged_message, &forgery.digest).then_some((secret_length, forged_message))
+        });
+
+        let (secret_length, forged_message) = accepted.expect("Forgery must be accepted");
+        assert_eq!(secret_length, 17);
+        assert!(forged_message.starts_with(MESSAGE));
+        assert!(forged_message.ends_with(SUFFIX));
+    }
+
+    #[test]
+    fn test_hmac_server_rejects_forgery() {
+        let server = HmacServer { secret: b"server secret key".to_vec() };
+        let tag = server.sign(MESSAGE);
+        assert!(server.verify(MESSAGE, &tag));
+
+        let mut known_tag = [0u8; DIGEST_SIZE];
+        known_tag.copy_from_slice(&tag);
+
+        for secret_length in 0..64u64 {
+            let forgery = forge_sha1(&known_tag, secret_length + MESSAGE.len() as u64, SUFFIX).unwrap();
+            let forged_message = [MESSAGE, forgery.extension.as_slice()].concat();
+
+            assert!(!server.verify(&forged_message, &forgery.digest));
+        }
+    }
+}
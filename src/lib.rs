@@ -6,18 +6,72 @@ pub mod kata_6_1;
 pub mod kata_6_2;
 pub mod kata_6_3;
 
+pub mod error;
+
 pub mod elgamal {
   pub mod elgamal;
+  pub mod groups;
+  pub mod keys;
+  pub mod hybrid;
 }
 
 pub mod elliptical_curve {
   pub mod ec_wrapper;
 }
 
+pub mod hmac {
+  pub mod hmac;
+}
+
+pub mod length_extension {
+  pub mod length_extension;
+}
+
+pub mod kdf {
+  pub mod kdf;
+}
+
+pub mod otp {
+  pub mod otp;
+}
+
+pub mod avalanche {
+  pub mod avalanche;
+}
+
+pub mod drbg {
+  pub mod hash_drbg;
+  pub mod cavp;
+}
+
+pub mod merkle {
+  pub mod merkle;
+}
+
+pub mod md {
+  pub mod block_buffer;
+}
+
 pub mod fips140 {
   pub mod fips140;
 }
 
 pub mod sha1 {
   pub mod sha1;
+  pub mod collision_detection;
+  pub mod shavs;
+  pub mod reduced;
+}
+
+pub mod identifiers {
+  pub mod git_object;
+  pub mod uuid_v5;
+}
+
+pub mod sha1sum {
+  pub mod sha1sum;
+}
+
+pub mod sha2 {
+  pub mod sha2;
 }
\ No newline at end of file
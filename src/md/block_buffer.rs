@@ -0,0 +1,148 @@
+use std::io::{self, Read};
+use crate::error::Error;
+
+// Size of the chunks read by 'read_chunks'
+pub const READ_CHUNK_SIZE: usize = 8192;
+
+// Merkle–Damgård block engine shared by the SHA-1 and SHA-2 realizations:
+// buffers the input, hands every full block to the compression function and does the final padding.
+#[derive(Clone)]
+pub struct BlockBuffer<const BLOCK_SIZE: usize> {
+    block: [u8; BLOCK_SIZE],
+    position: usize,
+    length: u128,
+}
+
+impl<const BLOCK_SIZE: usize> Default for BlockBuffer<BLOCK_SIZE> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const BLOCK_SIZE: usize> BlockBuffer<BLOCK_SIZE> {
+    pub fn new() -> Self {
+        BlockBuffer { block: [0; BLOCK_SIZE], position: 0, length: 0 }
+    }
+
+    // Rebuilds the buffer from a saved midstate: 'length' message bytes were passed to 'update',
+    // 'pending' is the incomplete tail that wasn't compressed yet
+    pub fn restore(pending: &[u8], length: u128) -> Result<Self, Error> {
+        if pending.len() >= BLOCK_SIZE {
+            return Err(Error::InvalidArgument(format!("Pending data must be shorter than a block ({} bytes), got: {}", BLOCK_SIZE, pending.len())));
+        }
+        if length % BLOCK_SIZE as u128 != pending.len() as u128 {
+            return Err(Error::InvalidArgument(format!("Processed length {} doesn't match {} pending bytes", length, pending.len())));
+        }
+
+        let mut buffer = BlockBuffer::new();
+        buffer.block[..pending.len()].copy_from_slice(pending);
+        buffer.position = pending.len();
+        buffer.length = length;
+
+        Ok(buffer)
+    }
+
+    // Bytes that 'finalize' appends to a message of 'length' bytes: '0x80', zeros and the length field.
+    // Never fails, unlike 'restore' the pending tail is implied by the length
+    pub fn padding(length: u128, length_size: usize) -> Vec<u8> {
+        let tail = (length % BLOCK_SIZE as u128) as usize;
+        let mut buffer = BlockBuffer { block: [0; BLOCK_SIZE], position: tail, length };
+
+        let mut padded = Vec::with_capacity(2 * BLOCK_SIZE);
+        buffer.finalize(length_size, |block| padded.extend_from_slice(block));
+
+        padded.split_off(tail)
+    }
+
+    // Count of the message bytes passed to 'update'
+    pub fn length(&self) -> u128 {
+        self.length
+    }
+
+    // Buffered bytes that don't make a full block yet
+    pub fn pending(&self) -> &[u8] {
+        &self.block[..self.position]
+    }
+
+    // Appends input to the message, every full block is compressed right away and
+    // only the incomplete tail stays in the buffer.
+    pub fn update(&mut self, mut input: &[u8], mut compress: impl FnMut(&[u8; BLOCK_SIZE])) {
+        self.length += input.len() as u128;
+
+        // Fill up the pending block first
+        if self.position > 0 {
+            let take = (BLOCK_SIZE - self.position).min(input.len());
+            self.block[self.position..self.position + take].copy_from_slice(&input[..take]);
+            self.position += take;
+            input = &input[take..];
+
+            if self.position < BLOCK_SIZE {
+                return;
+            }
+
+            compress(&self.block);
+            self.position = 0;
+        }
+
+        // Compress full blocks directly from the input
+        let mut chunks = input.chunks_exact(BLOCK_SIZE);
+        for chunk in &mut chunks {
+            let mut block = [0u8; BLOCK_SIZE];
+            block.copy_from_slice(chunk);
+            compress(&block);
+        }
+
+        // Keep the rest for the next call
+        let rest = chunks.remainder();
+        self.block[..rest.len()].copy_from_slice(rest);
+        self.position = rest.len();
+    }
+
+    // Pads the buffered tail and compresses the last block(s), after that the buffer is empty.
+    // 'length_size' - size of the big-endian message length field in bytes: 8 for SHA-1/SHA-256, 16 for SHA-512
+    pub fn finalize(&mut self, length_size: usize, compress: impl FnMut(&[u8; BLOCK_SIZE])) {
+        self.finalize_bits(0, 0, length_size, compress);
+    }
+
+    // The same for a message whose length in bits isn't a multiple of 8: the message ends with
+    // 'trailing_bits' (0..8) most significant bits of 'trailing_byte'
+    pub fn finalize_bits(&mut self, trailing_byte: u8, trailing_bits: u8, length_size: usize, mut compress: impl FnMut(&[u8; BLOCK_SIZE])) {
+        assert!(trailing_bits < 8, "Trailing bits must be less than a byte, got: {}", trailing_bits);
+        let original_length = self.length * 8 + trailing_bits as u128;
+
+        // Join bit '1' to the message, right after the trailing bits
+        let trailing_mask = !(0xffu8 >> trailing_bits);
+        self.block[self.position] = (trailing_byte & trailing_mask) | (0x80 >> trailing_bits);
+        self.position += 1;
+
+        // Join k '0' bits, where k is the smallest number ≥ 0 such that the length of the resulting message
+        // (in bits) is equal modulo block size to (block size - length size)
+        if self.position > BLOCK_SIZE - length_size {
+            self.block[self.position..].fill(0);
+            compress(&self.block);
+            self.position = 0;
+        }
+        self.block[self.position..BLOCK_SIZE - length_size].fill(0);
+
+        // Add the length of the original message (before preprocessing) as a big-endian number, in bits.
+        self.block[BLOCK_SIZE - length_size..].copy_from_slice(&original_length.to_be_bytes()[16 - length_size..]);
+        compress(&self.block);
+
+        *self = BlockBuffer::new();
+    }
+}
+
+// Reads the source to the end in fixed-size chunks, e.g. a file, stdin or a socket,
+// backs 'update_reader' of the SHA-1 and SHA-2 hashers
+pub fn read_chunks<R: Read>(mut reader: R, mut consume: impl FnMut(&[u8])) -> io::Result<()> {
+    let mut chunk = [0u8; READ_CHUNK_SIZE];
+
+    loop {
+        match reader.read(&mut chunk) {
+            Ok(0) => return Ok(()),
+            Ok(read) => consume(&chunk[..read]),
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error),
+        }
+    }
+}
\ No newline at end of file
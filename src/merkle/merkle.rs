@@ -0,0 +1,509 @@
+// Merkle tree of RFC 6962 (Certificate Transparency, section 2.1) over any 'Digest', e.g. Sha1Realization32.
+// Leaves are hashed as H(0x00 || data), nodes as H(0x01 || left || right), so a leaf can't pass for a node.
+// Only the complete subtrees are stored: appending a leaf adds O(log n) hashes, the roots of the smaller trees
+// and the proofs are assembled from them.
+use std::marker::PhantomData;
+use crate::error::Error;
+use digest::Digest;
+
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+pub struct MerkleTree<D: Digest> {
+    // levels[k][i] is the hash of the complete subtree over the leaves i * 2^k .. (i + 1) * 2^k
+    levels: Vec<Vec<Vec<u8>>>,
+    _digest: PhantomData<D>,
+}
+
+// Audit path of the leaf 'leaf_index' in the tree of the first 'tree_size' leaves, from the bottom
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InclusionProof {
+    pub leaf_index: usize,
+    pub tree_size: usize,
+    pub audit_path: Vec<Vec<u8>>,
+}
+
+// Proof that the tree of 'old_size' leaves is a prefix of the tree of 'new_size' leaves
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConsistencyProof {
+    pub old_size: usize,
+    pub new_size: usize,
+    pub path: Vec<Vec<u8>>,
+}
+
+impl<D: Digest> Default for MerkleTree<D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<D: Digest> MerkleTree<D> {
+    pub fn new() -> Self {
+        MerkleTree { levels: Vec::new(), _digest: PhantomData }
+    }
+
+    pub fn len(&self) -> usize {
+        self.levels.first().map_or(0, |leaves| leaves.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Appends a leaf and returns its index. Every complete pair on the way up gets its parent.
+    pub fn push(&mut self, data: &[u8]) -> usize {
+        let index = self.len();
+        let mut hash = leaf_hash::<D>(data);
+
+        for level in 0.. {
+            if self.levels.len() == level {
+                self.levels.push(Vec::new());
+            }
+            let nodes = &mut self.levels[level];
+            nodes.push(hash);
+            if nodes.len() % 2 == 1 {
+                break;
+            }
+            hash = node_hash::<D>(&nodes[nodes.len() - 2], &nodes[nodes.len() - 1]);
+        }
+
+        index
+    }
+
+    pub fn extend<I: IntoIterator<Item = T>, T: AsRef<[u8]>>(&mut self, leaves: I) {
+        for leaf in leaves {
+            self.push(leaf.as_ref());
+        }
+    }
+
+    pub fn leaf_hash(&self, index: usize) -> Option<&[u8]> {
+        self.levels.first().and_then(|leaves| leaves.get(index)).map(|hash| hash.as_slice())
+    }
+
+    // MTH of all leaves, the hash of an empty string for the empty tree
+    pub fn root(&self) -> Vec<u8> {
+        self.subtree_hash(0, self.len())
+    }
+
+    // Root the tree had when it had 'tree_size' leaves
+    pub fn root_at(&self, tree_size: usize) -> Result<Vec<u8>, Error> {
+        if tree_size > self.len() {
+            return Err(Error::InvalidArgument(format!("Tree has {} leaves, got size: {}", self.len(), tree_size)));
+        }
+        Ok(self.subtree_hash(0, tree_size))
+    }
+
+    // PATH(m, D[n]) of RFC 6962 2.1.1
+    pub fn inclusion_proof(&self, leaf_index: usize, tree_size: usize) -> Result<InclusionProof, Error> {
+        if tree_size > self.len() {
+            return Err(Error::InvalidArgument(format!("Tree has {} leaves, got size: {}", self.len(), tree_size)));
+        }
+        if leaf_index >= tree_size {
+            return Err(Error::InvalidArgument(format!("Leaf index must be less than the tree size {}, got: {}", tree_size, leaf_index)));
+        }
+
+        let mut audit_path = Vec::new();
+        self.audit_path(leaf_index, 0, tree_size, &mut audit_path);
+
+        Ok(InclusionProof { leaf_index, tree_size, audit_path })
+    }
+
+    // PROOF(m, D[n]) of RFC 6962 2.1.2, empty for the equal sizes and for the empty old tree
+    pub fn consistency_proof(&self, old_size: usize, new_size: usize) -> Result<ConsistencyProof, Error> {
+        if new_size > self.len() {
+            return Err(Error::InvalidArgument(format!("Tree has {} leaves, got size: {}", self.len(), new_size)));
+        }
+        if old_size > new_size {
+            return Err(Error::InvalidArgument(format!("Old size {} is bigger than the new size {}", old_size, new_size)));
+        }
+
+        let mut path = Vec::new();
+        if old_size > 0 {
+            self.subproof(old_size, 0, new_size, true, &mut path);
+        }
+
+        Ok(ConsistencyProof { old_size, new_size, path })
+    }
+
+    // MTH(D[start:end]), complete subtrees are taken from the levels, the rest is split at the biggest power of two
+    fn subtree_hash(&self, start: usize, end: usize) -> Vec<u8> {
+        let size = end - start;
+        if size == 0 {
+            return D::digest([]).to_vec();
+        }
+        if size.is_power_of_two() {
+            let level = size.trailing_zeros() as usize;
+            return self.levels[level][start >> level].clone();
+        }
+
+        let split = start + split_point(size);
+        node_hash::<D>(&self.subtree_hash(start, split), &self.subtree_hash(split, end))
+    }
+
+    fn audit_path(&self, index: usize, start: usize, end: usize, path: &mut Vec<Vec<u8>>) {
+        if end - start <= 1 {
+            return;
+        }
+
+        let split = start + split_point(end - start);
+        if index < split {
+            self.audit_path(index, start, split, path);
+            path.push(self.subtree_hash(split, end));
+        } else {
+            self.audit_path(index, split, end, path);
+            path.push(self.subtree_hash(start, split));
+        }
+    }
+
+    // SUBPROOF(m, D[start:end], b), 'm' counts from 'start'
+    fn subproof(&self, old_size: usize, start: usize, end: usize, complete: bool, path: &mut Vec<Vec<u8>>) {
+        if old_size == end - start {
+            if !complete {
+                path.push(self.subtree_hash(start, end));
+            }
+            return;
+        }
+
+        let split = split_point(end - start);
+        if old_size <= split {
+            self.subproof(old_size, start, start + split, complete, path);
+            path.push(self.subtree_hash(start + split, end));
+        } else {
+            self.subproof(old_size - split, start + split, end, false, path);
+            path.push(self.subtree_hash(start, start + split));
+        }
+    }
+}
+
+impl InclusionProof {
+    pub fn verify<D: Digest>(&self, leaf: &[u8], root: &[u8]) -> bool {
+        self.verify_leaf_hash::<D>(&leaf_hash::<D>(leaf), root)
+    }
+
+    // RFC 9162 2.1.3.2: the path is consumed from the bottom, 'last' tracks the rightmost node of the level
+    pub fn verify_leaf_hash<D: Digest>(&self, leaf_hash: &[u8], root: &[u8]) -> bool {
+        if self.leaf_index >= self.tree_size {
+            return false;
+        }
+
+        let mut index = self.leaf_index;
+        let mut last = self.tree_size - 1;
+        let mut hash = leaf_hash.to_vec();
+
+        for sibling in &self.audit_path {
+            if last == 0 {
+                return false;
+            }
+            if index & 1 == 1 || index == last {
+                hash = node_hash::<D>(sibling, &hash);
+                // A left node without a right sibling goes up unchanged
+                while index & 1 == 0 && index != 0 {
+                    index >>= 1;
+                    last >>= 1;
+                }
+            } else {
+                hash = node_hash::<D>(&hash, sibling);
+            }
+            index >>= 1;
+            last >>= 1;
+        }
+
+        last == 0 && hash == root
+    }
+}
+
+impl ConsistencyProof {
+    // RFC 9162 2.1.4.2: both roots are rebuilt from the same path
+    pub fn verify<D: Digest>(&self, old_root: &[u8], new_root: &[u8]) -> bool {
+        if self.old_size > self.new_size {
+            return false;
+        }
+        if self.old_size == self.new_size {
+            return self.path.is_empty() && old_root == new_root;
+        }
+        if self.old_size == 0 {
+            return self.path.is_empty();
+        }
+
+        // The old tree is a complete subtree, its root is the first node of the path
+        let mut path = self.path.iter().map(|hash| hash.as_slice());
+        let first = if self.old_size.is_power_of_two() { Some(old_root) } else { path.next() };
+        let Some(first) = first else {
+            return false;
+        };
+
+        let mut index = self.old_size - 1;
+        let mut last = self.new_size - 1;
+        while index & 1 == 1 {
+            index >>= 1;
+            last >>= 1;
+        }
+
+        let mut old_hash = first.to_vec();
+        let mut new_hash = first.to_vec();
+        for node in path {
+            if last == 0 {
+                return false;
+            }
+            if index & 1 == 1 || index == last {
+                old_hash = node_hash::<D>(node, &old_hash);
+                new_hash = node_hash::<D>(node, &new_hash);
+                while index & 1 == 0 && index != 0 {
+                    index >>= 1;
+                    last >>= 1;
+                }
+            } else {
+                new_hash = node_hash::<D>(&new_hash, node);
+            }
+            index >>= 1;
+            last >>= 1;
+        }
+
+        last == 0 && old_hash == old_root && new_hash == new_root
+    }
+}
+
+pub fn leaf_hash<D: Digest>(data: &[u8]) -> Vec<u8> {
+    D::new().chain_update([LEAF_PREFIX]).chain_update(data).finalize().to_vec()
+}
+
+pub fn node_hash<D: Digest>(left: &[u8], right: &[u8]) -> Vec<u8> {
+    D::new().chain_update([NODE_PREFIX]).chain_update(left).chain_update(right).finalize().to_vec()
+}
+
+// The biggest power of two smaller than 'size' (size > 1)
+fn split_point(size: usize) -> usize {
+    1 << (usize::BITS - 1 - (size - 1).leading_zeros())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use crate::sha1::sha1::Sha1Realization32;
+    use crate::sha2::sha2::Sha256Realization32;
+
+    // Leaves and roots of the Certificate Transparency test data
+    const LEAVES: [&str; 8] = ["", "00", "10", "2021", "3031", "40414243", "5051525354555657", "606162636465666768696a6b6c6d6e6f"];
+    const ROOTS: [&str; 8] = [
+        "6e340b9cffb37a989ca544e6bb780a2c78901d3fb33738768511a30617afa01d",
+        "fac54203e7cc696cf0dfcb42c92a1d9dbaf70ad9e621f4bd8d98662f00e3c125",
+        "aeb6bcfe274b70a14fb067a5e5578264db0fa9b51af5e0ba159158f329e06e77",
+        "d37ee418976dd95753c1c73862b9398fa2a2cf9b4ff0fdfe8b30cd95209614b7",
+        "4e3bbb1f7b478dcfe71fb631631519a3bca12c9aefca1612bfce4c13a86264d4",
+        "76e67dadbcdf1e10e1b74ddc608abd2f98dfb16fbce75277b5232a127f2087ef",
+        "ddb89be403809e325750d3d263cd78929c2942b7942a34b77e122c9594a74c8c",
+        "5dc9da79a70659a9ad559cb701ded9a2ab9d823aad2f4960cfe370eff4604328",
+    ];
+
+    fn leaves() -> Vec<Vec<u8>> {
+        LEAVES.iter().map(|leaf| hex::decode(leaf).unwrap()).collect()
+    }
+
+    fn hex_path(path: &[Vec<u8>]) -> Vec<String> {
+        path.iter().map(hex::encode).collect()
+    }
+
+    // MTH straight from the definition, for comparison
+    fn reference_root(leaves: &[Vec<u8>]) -> Vec<u8> {
+        match leaves.len() {
+            0 => <Sha256Realization32 as Digest>::digest([]).to_vec(),
+            1 => leaf_hash::<Sha256Realization32>(&leaves[0]),
+            size => {
+                let split = split_point(size);
+                node_hash::<Sha256Realization32>(&reference_root(&leaves[..split]), &reference_root(&leaves[split..]))
+            }
+        }
+    }
+
+    #[test]
+    fn test_merkle_roots() {
+        let mut tree = MerkleTree::<Sha256Realization32>::new();
+        assert_eq!(hex::encode(tree.root()), "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+
+        for (index, leaf) in leaves().iter().enumerate() {
+            assert_eq!(tree.push(leaf), index);
+            assert_eq!(hex::encode(tree.root()), ROOTS[index]);
+        }
+
+        // Earlier roots are still available
+        for (size, root) in ROOTS.iter().enumerate() {
+            assert_eq!(hex::encode(tree.root_at(size + 1).unwrap()), *root);
+        }
+        assert!(tree.root_at(9).is_err());
+        assert_eq!(tree.leaf_hash(0).map(hex::encode).unwrap(), ROOTS[0]);
+        assert_eq!(tree.leaf_hash(8), None);
+    }
+
+    #[test]
+    fn test_merkle_append() {
+        // Random leaves, the incremental tree matches the recursive definition at every size
+        let mut rng = StdRng::seed_from_u64(6962);
+        let mut tree = MerkleTree::<Sha256Realization32>::default();
+        let mut data = Vec::new();
+
+        for _ in 0..70 {
+            let leaf: Vec<u8> = (0..rng.gen_range(0..40)).map(|_| rng.gen()).collect();
+            tree.push(&leaf);
+            data.push(leaf);
+            assert_eq!(tree.root(), reference_root(&data));
+        }
+
+        // Stored hashes: one per leaf plus one per complete pair, 2n - popcount(n) in total
+        let stored: usize = tree.levels.iter().map(|level| level.len()).sum();
+        assert_eq!(stored, 2 * 70 - 70usize.count_ones() as usize);
+
+        let mut extended = MerkleTree::<Sha256Realization32>::new();
+        extended.extend(&data);
+        assert_eq!((extended.len(), extended.root()), (70, tree.root()));
+    }
+
+    #[test]
+    fn test_merkle_inclusion_proofs() {
+        let mut tree = MerkleTree::<Sha256Realization32>::new();
+        tree.extend(leaves());
+
+        let proof = tree.inclusion_proof(0, 8).unwrap();
+        assert_eq!(hex_path(&proof.audit_path), [
+            "96a296d224f285c67bee93c30f8a309157f0daa35dc5b87e410b78630a09cfc7",
+            "5f083f0a1a33ca076a95279832580db3e0ef4584bdff1f54c8a360f50de3031e",
+            "6b47aaf29ee3c2af9af889bc1fb9254dabd31177f16232dd6aab035ca39bf6e4",
+        ]);
+        let proof = tree.inclusion_proof(5, 8).unwrap();
+        assert_eq!(hex_path(&proof.audit_path), [
+            "bc1a0643b12e4d2d7c77918f44e0f4f79a838b6cf9ec5b5c283e1f4d88599e6b",
+            "ca854ea128ed050b41b35ffc1b87b8eb2bde461e9e3b5596ece6b9d5975a0ae0",
+            "d37ee418976dd95753c1c73862b9398fa2a2cf9b4ff0fdfe8b30cd95209614b7",
+        ]);
+        assert_eq!(hex_path(&tree.inclusion_proof(2, 3).unwrap().audit_path), [ROOTS[1]]);
+        assert!(tree.inclusion_proof(0, 1).unwrap().audit_path.is_empty());
+
+        // Every leaf in every tree size
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut tree = MerkleTree::<Sha256Realization32>::new();
+        let data: Vec<Vec<u8>> = (0..33).map(|_| (0..8).map(|_| rng.gen()).collect()).collect();
+        tree.extend(&data);
+
+        for size in 1..=33 {
+            let root = tree.root_at(size).unwrap();
+            for index in 0..size {
+                let proof = tree.inclusion_proof(index, size).unwrap();
+                assert!(proof.verify::<Sha256Realization32>(&data[index], &root), "{} of {}", index, size);
+
+                // Another leaf, index or a size without the leaf doesn't verify. The size itself isn't bound by the path
+                // (leaf 0 has the same path in the trees of 3 and 4 leaves), it comes with the signed root.
+                assert!(!proof.verify::<Sha256Realization32>(&data[(index + 1) % 33], &root));
+                assert!(!InclusionProof { leaf_index: index ^ 1, ..proof.clone() }.verify::<Sha256Realization32>(&data[index], &root) || size == 1);
+                assert!(!InclusionProof { tree_size: index, ..proof.clone() }.verify::<Sha256Realization32>(&data[index], &root));
+
+                // Tampered, extended or shortened paths don't verify
+                let mut tampered = proof.clone();
+                if let Some(node) = tampered.audit_path.first_mut() {
+                    node[0] ^= 1;
+                    assert!(!tampered.verify::<Sha256Realization32>(&data[index], &root));
+                    tampered.audit_path.pop();
+                    assert!(!tampered.verify::<Sha256Realization32>(&data[index], &root));
+                }
+                let mut extended = proof.clone();
+                extended.audit_path.push(root.clone());
+                assert!(!extended.verify::<Sha256Realization32>(&data[index], &root));
+            }
+        }
+
+        assert!(tree.inclusion_proof(33, 33).is_err());
+        assert!(tree.inclusion_proof(0, 34).is_err());
+        assert!(!InclusionProof { leaf_index: 0, tree_size: 0, audit_path: Vec::new() }.verify::<Sha256Realization32>(&data[0], &tree.root()));
+    }
+
+    #[test]
+    fn test_merkle_consistency_proofs() {
+        let mut tree = MerkleTree::<Sha256Realization32>::new();
+        tree.extend(leaves());
+
+        assert_eq!(hex_path(&tree.consistency_proof(1, 8).unwrap().path), [
+            "96a296d224f285c67bee93c30f8a309157f0daa35dc5b87e410b78630a09cfc7",
+            "5f083f0a1a33ca076a95279832580db3e0ef4584bdff1f54c8a360f50de3031e",
+            "6b47aaf29ee3c2af9af889bc1fb9254dabd31177f16232dd6aab035ca39bf6e4",
+        ]);
+        assert_eq!(hex_path(&tree.consistency_proof(6, 8).unwrap().path), [
+            "0ebc5d3437fbe2db158b9f126a1d118e308181031d0a949f8dededebc558ef6a",
+            "ca854ea128ed050b41b35ffc1b87b8eb2bde461e9e3b5596ece6b9d5975a0ae0",
+            "d37ee418976dd95753c1c73862b9398fa2a2cf9b4ff0fdfe8b30cd95209614b7",
+        ]);
+        assert_eq!(hex_path(&tree.consistency_proof(2, 5).unwrap().path), [
+            "5f083f0a1a33ca076a95279832580db3e0ef4584bdff1f54c8a360f50de3031e",
+            "bc1a0643b12e4d2d7c77918f44e0f4f79a838b6cf9ec5b5c283e1f4d88599e6b",
+        ]);
+
+        // All pairs of sizes
+        let mut tree = MerkleTree::<Sha256Realization32>::new();
+        tree.extend((0..30u32).map(|leaf| leaf.to_be_bytes()));
+
+        for new_size in 0..=30 {
+            let new_root = tree.root_at(new_size).unwrap();
+            for old_size in 0..=new_size {
+                let old_root = tree.root_at(old_size).unwrap();
+                let proof = tree.consistency_proof(old_size, new_size).unwrap();
+                assert!(proof.verify::<Sha256Realization32>(&old_root, &new_root), "{} -> {}", old_size, new_size);
+
+                if old_size == 0 || old_size == new_size {
+                    assert!(proof.path.is_empty());
+                    continue;
+                }
+
+                // Roots of other sizes don't verify
+                assert!(!proof.verify::<Sha256Realization32>(&new_root, &new_root));
+                assert!(!proof.verify::<Sha256Realization32>(&old_root, &old_root));
+
+                for position in 0..proof.path.len() {
+                    let mut tampered = proof.clone();
+                    tampered.path[position][0] ^= 1;
+                    assert!(!tampered.verify::<Sha256Realization32>(&old_root, &new_root));
+                }
+                let mut shortened = proof.clone();
+                shortened.path.pop();
+                assert!(!shortened.verify::<Sha256Realization32>(&old_root, &new_root));
+                let mut extended = proof.clone();
+                extended.path.push(new_root.clone());
+                assert!(!extended.verify::<Sha256Realization32>(&old_root, &new_root));
+            }
+        }
+
+        assert!(tree.consistency_proof(5, 4).is_err());
+        assert!(tree.consistency_proof(5, 31).is_err());
+        assert!(!ConsistencyProof { old_size: 5, new_size: 4, path: Vec::new() }.verify::<Sha256Realization32>(&tree.root(), &tree.root()));
+    }
+
+    #[test]
+    fn test_merkle_sha1() {
+        // Values printed by 'python3 test_data/merkle_sha1.py' (RFC 6962 over Python's hashlib), the empty tree has SHA-1("")
+        let mut tree = MerkleTree::<Sha1Realization32>::new();
+        assert_eq!(hex::encode(tree.root()), "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+        tree.extend(&leaves()[..7]);
+
+        assert_eq!(hex::encode(tree.root_at(1).unwrap()), "5ba93c9db0cff93f52b521d7420e43f6eda2784f");
+        assert_eq!(hex::encode(tree.root_at(3).unwrap()), "0a1d17488c27ab0f6fbdb3208d5d8196807c9028");
+        assert_eq!(hex::encode(tree.root()), "a4751286193b75f4e85c591dd7a735f813dedd55");
+
+        let proof = tree.inclusion_proof(3, 7).unwrap();
+        assert_eq!(hex_path(&proof.audit_path), [
+            "d0cf79023c72a3c57a6a110a067c402fd26104bf",
+            "4dc852b78c5e87e6848adf603e3c82cb819cc2c4",
+            "0678bd3f3e13b6f03a993518c87b3853d55a5251",
+        ]);
+        assert!(proof.verify::<Sha1Realization32>(&leaves()[3], &tree.root()));
+
+        let proof = tree.consistency_proof(3, 7).unwrap();
+        assert_eq!(hex_path(&proof.path), [
+            "d0cf79023c72a3c57a6a110a067c402fd26104bf",
+            "10d3225241748d011e9e26c528ed4323c8e64e2a",
+            "4dc852b78c5e87e6848adf603e3c82cb819cc2c4",
+            "0678bd3f3e13b6f03a993518c87b3853d55a5251",
+        ]);
+        assert!(proof.verify::<Sha1Realization32>(&tree.root_at(3).unwrap(), &tree.root()));
+
+        // Proofs of another hash function don't verify
+        assert!(!proof.verify::<Sha256Realization32>(&tree.root_at(3).unwrap(), &tree.root()));
+    }
+}
@@ -0,0 +1,62 @@
+#!/usr/bin/env python3
+# RFC 6962 Merkle tree hash, audit paths and consistency proofs (2.1) straight from the definitions,
+# over Python's hashlib. Prints the SHA-1 values of 'test_merkle_sha1': python3 merkle_sha1.py
+# With SHA-256 the same code gives the roots of the Certificate Transparency test data ('ROOTS').
+import hashlib
+
+# 'LEAVES' of the tests
+LEAVES = [bytes.fromhex(leaf) for leaf in ["", "00", "10", "2021", "3031", "40414243", "5051525354555657", "606162636465666768696a6b6c6d6e6f"]]
+CT_ROOT_8 = "5dc9da79a70659a9ad559cb701ded9a2ab9d823aad2f4960cfe370eff4604328"
+
+
+# The largest power of two smaller than n
+def split_point(n):
+    k = 1
+    while k * 2 < n:
+        k *= 2
+    return k
+
+
+def mth(hash, leaves):
+    if not leaves:
+        return hash(b"")
+    if len(leaves) == 1:
+        return hash(b"\x00" + leaves[0])
+    k = split_point(len(leaves))
+    return hash(b"\x01" + mth(hash, leaves[:k]) + mth(hash, leaves[k:]))
+
+
+def path(hash, m, leaves):
+    if len(leaves) == 1:
+        return []
+    k = split_point(len(leaves))
+    if m < k:
+        return path(hash, m, leaves[:k]) + [mth(hash, leaves[k:])]
+    return path(hash, m - k, leaves[k:]) + [mth(hash, leaves[:k])]
+
+
+def subproof(hash, m, leaves, complete):
+    n = len(leaves)
+    if m == n:
+        return [] if complete else [mth(hash, leaves)]
+    k = split_point(n)
+    if m <= k:
+        return subproof(hash, m, leaves[:k], complete) + [mth(hash, leaves[k:])]
+    return subproof(hash, m - k, leaves[k:], False) + [mth(hash, leaves[:k])]
+
+
+def main():
+    sha256 = lambda data: hashlib.sha256(data).digest()
+    assert mth(sha256, LEAVES).hex() == CT_ROOT_8
+
+    sha1 = lambda data: hashlib.sha1(data).digest()
+    leaves = LEAVES[:7]
+    print("empty tree", mth(sha1, []).hex())
+    for size in (1, 3, 7):
+        print("root_at({})".format(size), mth(sha1, leaves[:size]).hex())
+    print("inclusion_proof(3, 7)", [node.hex() for node in path(sha1, 3, leaves)])
+    print("consistency_proof(3, 7)", [node.hex() for node in subproof(sha1, 3, leaves, True)])
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,549 @@
+// One-time passwords: HOTP (RFC 4226) and TOTP (RFC 6238) over HMAC, plus 'otpauth://' provisioning URIs
+use crate::error::Error;
+use crate::hmac::hmac::{constant_time_eq, Hmac};
+use crate::sha1::sha1::Sha1Realization32;
+use crate::sha2::sha2::{Sha256Realization32, Sha512Realization64};
+
+pub const DEFAULT_DIGITS: u32 = 6;
+pub const DEFAULT_STEP: u64 = 30;
+
+// RFC 4226 requires at least 6 digits, 31-bit truncated value has 10 digits at most
+const MIN_DIGITS: u32 = 6;
+const MAX_DIGITS: u32 = 10;
+
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OtpAlgorithm {
+    Sha1,
+    Sha256,
+    Sha512,
+}
+
+impl OtpAlgorithm {
+    fn mac(&self, secret: &[u8], message: &[u8]) -> Vec<u8> {
+        match self {
+            OtpAlgorithm::Sha1 => Hmac::<Sha1Realization32>::mac(secret, message),
+            OtpAlgorithm::Sha256 => Hmac::<Sha256Realization32>::mac(secret, message),
+            OtpAlgorithm::Sha512 => Hmac::<Sha512Realization64>::mac(secret, message),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            OtpAlgorithm::Sha1 => "SHA1",
+            OtpAlgorithm::Sha256 => "SHA256",
+            OtpAlgorithm::Sha512 => "SHA512",
+        }
+    }
+
+    fn parse(name: &str) -> Result<Self, Error> {
+        match name.to_ascii_uppercase().as_str() {
+            "SHA1" => Ok(OtpAlgorithm::Sha1),
+            "SHA256" => Ok(OtpAlgorithm::Sha256),
+            "SHA512" => Ok(OtpAlgorithm::Sha512),
+            _ => Err(Error::InvalidFormat(format!("Unknown OTP algorithm: {}", name))),
+        }
+    }
+}
+
+// HOTP value for the counter: HMAC of the 8-byte big-endian counter, dynamic truncation
+// to 31 bits and the last 'digits' decimal digits, padded with zeros
+pub fn generate_code(algorithm: OtpAlgorithm, secret: &[u8], counter: u64, digits: u32) -> Result<String, Error> {
+    check_digits(digits)?;
+    Ok(truncated_code(algorithm, secret, counter, digits))
+}
+
+// 'generate_code' for the checked digits of 'Hotp' and 'Totp'
+fn truncated_code(algorithm: OtpAlgorithm, secret: &[u8], counter: u64, digits: u32) -> String {
+    let mac = algorithm.mac(secret, &counter.to_be_bytes());
+
+    // The low 4 bits of the last byte give the offset of the 4 bytes to take
+    let offset = (mac[mac.len() - 1] & 0x0f) as usize;
+    let truncated = u32::from_be_bytes([mac[offset] & 0x7f, mac[offset + 1], mac[offset + 2], mac[offset + 3]]);
+
+    let code = truncated as u64 % 10u64.pow(digits);
+    format!("{:0width$}", code, width = digits as usize)
+}
+
+fn check_digits(digits: u32) -> Result<(), Error> {
+    if !(MIN_DIGITS..=MAX_DIGITS).contains(&digits) {
+        return Err(Error::InvalidArgument(format!("Digits must be from {} to {}, got: {}", MIN_DIGITS, MAX_DIGITS, digits)));
+    }
+    Ok(())
+}
+
+// Counter-based passwords, the server keeps the counter of the next expected code
+#[derive(Clone, Debug)]
+pub struct Hotp {
+    algorithm: OtpAlgorithm,
+    secret: Vec<u8>,
+    digits: u32,
+    pub counter: u64,
+    // How many counter values after the expected one are accepted (the client pressed the button without logging in)
+    pub look_ahead: u64,
+}
+
+impl Hotp {
+    pub fn new(secret: &[u8], algorithm: OtpAlgorithm, digits: u32, counter: u64) -> Result<Self, Error> {
+        check_digits(digits)?;
+        Ok(Hotp { algorithm, secret: secret.to_vec(), digits, counter, look_ahead: 10 })
+    }
+
+    pub fn generate(&self, counter: u64) -> String {
+        truncated_code(self.algorithm, &self.secret, counter, self.digits)
+    }
+
+    // Accepts a code from 'counter' to 'counter + look_ahead', after that the counter goes after the matched value,
+    // so the same code can't be used again. A code for u64::MAX is refused, the counter can't go after it.
+    pub fn verify(&mut self, code: &str) -> bool {
+        match self.find_code(code, self.counter, self.look_ahead).and_then(|counter| counter.checked_add(1)) {
+            Some(next_counter) => {
+                self.counter = next_counter;
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Resynchronization (RFC 4226, 7.4): the client sends two consecutive codes, they are searched in the bigger
+    // window 'resync_window'. On success the counter goes after the second code.
+    pub fn resync(&mut self, first_code: &str, second_code: &str, resync_window: u64) -> bool {
+        let end = self.counter.saturating_add(resync_window);
+
+        let matched = (self.counter..end).find(|counter| {
+            constant_time_eq(self.generate(*counter).as_bytes(), first_code.as_bytes())
+                && constant_time_eq(self.generate(counter + 1).as_bytes(), second_code.as_bytes())
+        });
+
+        match matched.and_then(|counter| counter.checked_add(2)) {
+            Some(next_counter) => {
+                self.counter = next_counter;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn find_code(&self, code: &str, start: u64, window: u64) -> Option<u64> {
+        (start..=start.saturating_add(window)).find(|counter| constant_time_eq(self.generate(*counter).as_bytes(), code.as_bytes()))
+    }
+}
+
+// Time-based passwords: HOTP with the counter (time - T0) / step
+#[derive(Clone, Debug)]
+pub struct Totp {
+    algorithm: OtpAlgorithm,
+    secret: Vec<u8>,
+    digits: u32,
+    step: u64,
+    // Unix time to start counting steps from (T0)
+    pub start_time: u64,
+    // Accepted steps before and after the current one (network delay and clock skew)
+    pub look_behind: u64,
+    pub look_ahead: u64,
+    // Clock drift of the client in steps, found by the last accepted code (RFC 6238, 6)
+    drift: i64,
+    // The last accepted step, a code can be used only once (RFC 6238, 5.2)
+    last_step: Option<u64>,
+}
+
+impl Totp {
+    pub fn new(secret: &[u8], algorithm: OtpAlgorithm, digits: u32, step: u64) -> Result<Self, Error> {
+        check_digits(digits)?;
+        if step == 0 {
+            return Err(Error::InvalidArgument("Time step must be positive".to_string()));
+        }
+
+        Ok(Totp { algorithm, secret: secret.to_vec(), digits, step, start_time: 0, look_behind: 1, look_ahead: 1, drift: 0, last_step: None })
+    }
+
+    pub fn time_step(&self, timestamp: u64) -> u64 {
+        timestamp.saturating_sub(self.start_time) / self.step
+    }
+
+    pub fn generate(&self, timestamp: u64) -> String {
+        truncated_code(self.algorithm, &self.secret, self.time_step(timestamp), self.digits)
+    }
+
+    pub fn drift(&self) -> i64 {
+        self.drift
+    }
+
+    // Checks the steps around the current one (moved by the known drift). The matched step becomes the new drift
+    // and can't be accepted again, as well as the steps before it.
+    pub fn verify(&mut self, code: &str, timestamp: u64) -> bool {
+        let expected_step = self.time_step(timestamp).saturating_add_signed(self.drift);
+        let first_step = expected_step.saturating_sub(self.look_behind);
+        let last_step = expected_step.saturating_add(self.look_ahead);
+
+        let matched = (first_step..=last_step)
+            .filter(|step| self.last_step.is_none_or(|used| *step > used))
+            .find(|step| constant_time_eq(truncated_code(self.algorithm, &self.secret, *step, self.digits).as_bytes(), code.as_bytes()));
+
+        match matched {
+            Some(step) => {
+                self.drift = step as i64 - self.time_step(timestamp) as i64;
+                self.last_step = Some(step);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OtpKind {
+    Hotp { counter: u64 },
+    Totp { step: u64 },
+}
+
+// Provisioning URI for authenticator apps:
+// otpauth://totp/Issuer:account?secret=BASE32&issuer=Issuer&algorithm=SHA1&digits=6&period=30
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OtpAuthUri {
+    pub kind: OtpKind,
+    pub issuer: Option<String>,
+    pub account: String,
+    pub secret: Vec<u8>,
+    pub algorithm: OtpAlgorithm,
+    pub digits: u32,
+}
+
+impl OtpAuthUri {
+    pub fn to_uri(&self) -> String {
+        let (kind, parameter) = match self.kind {
+            OtpKind::Hotp { counter } => ("hotp", format!("counter={}", counter)),
+            OtpKind::Totp { step } => ("totp", format!("period={}", step)),
+        };
+
+        let label = match &self.issuer {
+            Some(issuer) => format!("{}:{}", percent_encode(issuer), percent_encode(&self.account)),
+            None => percent_encode(&self.account),
+        };
+
+        let mut uri = format!("otpauth://{}/{}?secret={}", kind, label, base32_encode(&self.secret));
+        if let Some(issuer) = &self.issuer {
+            uri += &format!("&issuer={}", percent_encode(issuer));
+        }
+        uri + &format!("&algorithm={}&digits={}&{}", self.algorithm.name(), self.digits, parameter)
+    }
+
+    pub fn parse(uri: &str) -> Result<Self, Error> {
+        let rest = uri.strip_prefix("otpauth://").ok_or_else(|| Error::InvalidFormat("URI must start with 'otpauth://'".to_string()))?;
+        let (kind, rest) = rest.split_once('/').ok_or_else(|| Error::InvalidFormat("URI has no label".to_string()))?;
+        let (label, query) = rest.split_once('?').unwrap_or((rest, ""));
+
+        let label = percent_decode(label)?;
+        let (label_issuer, account) = match label.split_once(':') {
+            Some((issuer, account)) => (Some(issuer.to_string()), account.trim_start().to_string()),
+            None => (None, label),
+        };
+        if account.is_empty() {
+            return Err(Error::InvalidFormat("URI has no account name".to_string()));
+        }
+
+        let mut secret = None;
+        let mut issuer = None;
+        let mut algorithm = OtpAlgorithm::Sha1;
+        let mut digits = DEFAULT_DIGITS;
+        let mut counter = None;
+        let mut step = DEFAULT_STEP;
+
+        for parameter in query.split('&').filter(|parameter| !parameter.is_empty()) {
+            let (key, value) = parameter.split_once('=').ok_or_else(|| Error::InvalidFormat(format!("Parameter without value: {}", parameter)))?;
+            let value = percent_decode(value)?;
+
+            match key {
+                "secret" => secret = Some(base32_decode(&value)?),
+                "issuer" => issuer = Some(value),
+                "algorithm" => algorithm = OtpAlgorithm::parse(&value)?,
+                "digits" => digits = value.parse().map_err(|_| Error::InvalidFormat(format!("Invalid digits: {}", value)))?,
+                "counter" => counter = Some(value.parse().map_err(|_| Error::InvalidFormat(format!("Invalid counter: {}", value)))?),
+                "period" => step = value.parse().map_err(|_| Error::InvalidFormat(format!("Invalid period: {}", value)))?,
+                // Other parameters (e.g. 'image') are for the apps only
+                _ => {}
+            }
+        }
+
+        if let (Some(label_issuer), Some(issuer)) = (&label_issuer, &issuer) {
+            if label_issuer != issuer {
+                return Err(Error::InvalidFormat(format!("Issuer in the label '{}' doesn't match the parameter '{}'", label_issuer, issuer)));
+            }
+        }
+
+        let kind = match kind {
+            "hotp" => OtpKind::Hotp { counter: counter.ok_or_else(|| Error::InvalidFormat("HOTP URI must have a counter".to_string()))? },
+            "totp" if step > 0 => OtpKind::Totp { step },
+            "totp" => return Err(Error::InvalidFormat("Time step must be positive".to_string())),
+            other => return Err(Error::InvalidFormat(format!("Unknown OTP type: {}", other))),
+        };
+        check_digits(digits)?;
+
+        Ok(OtpAuthUri {
+            kind,
+            issuer: issuer.or(label_issuer),
+            account,
+            secret: secret.ok_or_else(|| Error::InvalidFormat("URI must have a secret".to_string()))?,
+            algorithm,
+            digits,
+        })
+    }
+
+    pub fn to_hotp(&self) -> Result<Hotp, Error> {
+        match self.kind {
+            OtpKind::Hotp { counter } => Hotp::new(&self.secret, self.algorithm, self.digits, counter),
+            OtpKind::Totp { .. } => Err(Error::InvalidArgument("URI is for TOTP".to_string())),
+        }
+    }
+
+    pub fn to_totp(&self) -> Result<Totp, Error> {
+        match self.kind {
+            OtpKind::Totp { step } => Totp::new(&self.secret, self.algorithm, self.digits, step),
+            OtpKind::Hotp { .. } => Err(Error::InvalidArgument("URI is for HOTP".to_string())),
+        }
+    }
+}
+
+// RFC 4648 base32 without padding, as authenticator apps expect it
+pub fn base32_encode(data: &[u8]) -> String {
+    let mut encoded = String::with_capacity(data.len().div_ceil(5) * 8);
+    let mut buffer: u64 = 0;
+    let mut bits = 0;
+
+    for byte in data {
+        buffer = (buffer << 8) | *byte as u64;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            encoded.push(BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        encoded.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+
+    encoded
+}
+
+// Any case, padding and spaces are allowed
+pub fn base32_decode(text: &str) -> Result<Vec<u8>, Error> {
+    let mut decoded = Vec::with_capacity(text.len() * 5 / 8);
+    let mut buffer: u64 = 0;
+    let mut bits = 0;
+
+    for symbol in text.chars().filter(|symbol| *symbol != '=' && *symbol != ' ') {
+        let value = BASE32_ALPHABET
+            .iter()
+            .position(|letter| *letter as char == symbol.to_ascii_uppercase())
+            .ok_or_else(|| Error::InvalidFormat(format!("Invalid base32 symbol: '{}'", symbol)))?;
+
+        buffer = (buffer << 5) | value as u64;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            decoded.push((buffer >> bits) as u8);
+        }
+    }
+
+    Ok(decoded)
+}
+
+fn percent_encode(text: &str) -> String {
+    text.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+fn percent_decode(text: &str) -> Result<String, Error> {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = text.get(i + 1..i + 3).ok_or_else(|| Error::InvalidFormat(format!("Invalid percent encoding: {}", text)))?;
+            decoded.push(u8::from_str_radix(hex, 16).map_err(|_| Error::InvalidFormat(format!("Invalid percent encoding: {}", text)))?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+
+    String::from_utf8(decoded).map_err(|_| Error::InvalidFormat(format!("Percent encoding isn't UTF-8: {}", text)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET_SHA1: &[u8] = b"12345678901234567890";
+    const SECRET_SHA256: &[u8] = b"12345678901234567890123456789012";
+    const SECRET_SHA512: &[u8] = b"1234567890123456789012345678901234567890123456789012345678901234";
+
+    #[test]
+    fn test_hotp_rfc_4226() {
+        // Appendix D
+        let expected = ["755224", "287082", "359152", "969429", "338314", "254676", "287922", "162583", "399871", "520489"];
+        let hotp = Hotp::new(SECRET_SHA1, OtpAlgorithm::Sha1, 6, 0).unwrap();
+
+        for (counter, code) in expected.iter().enumerate() {
+            assert_eq!(hotp.generate(counter as u64), *code);
+        }
+
+        assert!(matches!(Hotp::new(SECRET_SHA1, OtpAlgorithm::Sha1, 5, 0), Err(Error::InvalidArgument(_))));
+        assert!(Hotp::new(SECRET_SHA1, OtpAlgorithm::Sha1, 11, 0).is_err());
+
+        // 10^20 doesn't fit u64, such lengths are refused instead of an overflow
+        assert_eq!(generate_code(OtpAlgorithm::Sha1, SECRET_SHA1, 0, 6).unwrap(), expected[0]);
+        assert!(matches!(generate_code(OtpAlgorithm::Sha1, SECRET_SHA1, 0, 20), Err(Error::InvalidArgument(_))));
+        assert!(generate_code(OtpAlgorithm::Sha1, SECRET_SHA1, 0, 0).is_err());
+    }
+
+    #[test]
+    fn test_totp_rfc_6238() {
+        // Appendix B: time, SHA1, SHA256, SHA512
+        let vectors = [
+            (59, "94287082", "46119246", "90693936"),
+            (1111111109, "07081804", "68084774", "25091201"),
+            (1111111111, "14050471", "67062674", "99943326"),
+            (1234567890, "89005924", "91819424", "93441116"),
+            (2000000000, "69279037", "90698825", "38618901"),
+            (20000000000, "65353130", "77737706", "47863826"),
+        ];
+
+        let totp_sha1 = Totp::new(SECRET_SHA1, OtpAlgorithm::Sha1, 8, 30).unwrap();
+        let totp_sha256 = Totp::new(SECRET_SHA256, OtpAlgorithm::Sha256, 8, 30).unwrap();
+        let totp_sha512 = Totp::new(SECRET_SHA512, OtpAlgorithm::Sha512, 8, 30).unwrap();
+
+        for (time, sha1, sha256, sha512) in vectors {
+            assert_eq!(totp_sha1.generate(time), sha1, "SHA1 at {}", time);
+            assert_eq!(totp_sha256.generate(time), sha256, "SHA256 at {}", time);
+            assert_eq!(totp_sha512.generate(time), sha512, "SHA512 at {}", time);
+        }
+
+        // Other step and T0 change only the counter
+        let mut totp = Totp::new(SECRET_SHA1, OtpAlgorithm::Sha1, 6, 60).unwrap();
+        totp.start_time = 600;
+        assert_eq!(totp.time_step(659), 0);
+        assert_eq!(totp.generate(1200), generate_code(OtpAlgorithm::Sha1, SECRET_SHA1, 10, 6).unwrap());
+        assert!(Totp::new(SECRET_SHA1, OtpAlgorithm::Sha1, 6, 0).is_err());
+    }
+
+    #[test]
+    fn test_hotp_verify_and_resync() {
+        let client = Hotp::new(SECRET_SHA1, OtpAlgorithm::Sha1, 6, 0).unwrap();
+        let mut server = client.clone();
+        server.look_ahead = 3;
+
+        // Expected code, the same code again, a code inside the look-ahead window
+        assert!(server.verify(&client.generate(0)));
+        assert_eq!(server.counter, 1);
+        assert!(!server.verify(&client.generate(0)));
+        assert!(server.verify(&client.generate(4)));
+        assert_eq!(server.counter, 5);
+
+        // The client is too far ahead for the window
+        assert!(!server.verify(&client.generate(20)));
+        assert_eq!(server.counter, 5);
+
+        // Two consecutive codes bring it back, a pair of non consecutive codes doesn't
+        assert!(!server.resync(&client.generate(20), &client.generate(22), 50));
+        assert!(!server.resync(&client.generate(20), &client.generate(21), 10));
+        assert!(server.resync(&client.generate(20), &client.generate(21), 50));
+        assert_eq!(server.counter, 22);
+        assert!(server.verify(&client.generate(22)));
+
+        // The counter can't go after u64::MAX: the last codes are refused, not wrapped to 0
+        server.counter = u64::MAX - 1;
+        assert!(server.verify(&client.generate(u64::MAX - 1)));
+        assert_eq!(server.counter, u64::MAX);
+        assert!(!server.verify(&client.generate(u64::MAX)));
+        assert_eq!(server.counter, u64::MAX);
+
+        server.counter = u64::MAX - 3;
+        assert!(!server.resync(&client.generate(u64::MAX - 1), &client.generate(u64::MAX), 50));
+        assert_eq!(server.counter, u64::MAX - 3);
+        assert!(server.resync(&client.generate(u64::MAX - 3), &client.generate(u64::MAX - 2), 50));
+        assert_eq!(server.counter, u64::MAX - 1);
+    }
+
+    #[test]
+    fn test_totp_verify_window_and_drift() {
+        let client = Totp::new(SECRET_SHA1, OtpAlgorithm::Sha1, 6, 30).unwrap();
+        let mut server = client.clone();
+        let now = 1_700_000_000;
+
+        // Code of the previous step is accepted, but only once
+        assert!(server.verify(&client.generate(now - 30), now));
+        assert_eq!(server.drift(), -1);
+        assert!(!server.verify(&client.generate(now - 30), now));
+
+        // Two steps away is outside the default window
+        assert!(!server.verify(&client.generate(now + 60), now));
+
+        // The client clock is 90 seconds ahead: accepted with a bigger window, then followed by the drift
+        let mut server = client.clone();
+        server.look_ahead = 3;
+        assert!(server.verify(&client.generate(now + 90), now));
+        assert_eq!(server.drift(), 3);
+        server.look_ahead = 1;
+        assert!(server.verify(&client.generate(now + 120), now + 30));
+        assert_eq!(server.drift(), 3);
+
+        assert!(!server.verify("000000x", now));
+    }
+
+    #[test]
+    fn test_otpauth_uri() {
+        let uri = OtpAuthUri {
+            kind: OtpKind::Totp { step: 30 },
+            issuer: Some("Example Co".to_string()),
+            account: "alice@example.com".to_string(),
+            secret: SECRET_SHA1.to_vec(),
+            algorithm: OtpAlgorithm::Sha1,
+            digits: 6,
+        };
+        let text = uri.to_uri();
+        assert_eq!(
+            text,
+            "otpauth://totp/Example%20Co:alice%40example.com?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&issuer=Example%20Co&algorithm=SHA1&digits=6&period=30"
+        );
+        assert_eq!(OtpAuthUri::parse(&text).unwrap(), uri);
+        assert_eq!(OtpAuthUri::parse(&text).unwrap().to_totp().unwrap().generate(59), "287082");
+
+        // Minimal URI with defaults, lower-case secret and the issuer only in the label
+        let parsed = OtpAuthUri::parse("otpauth://hotp/ACME:bob?secret=gezdgnbvgy3tqojqgezdgnbvgy3tqojq&counter=3").unwrap();
+        assert_eq!(parsed.kind, OtpKind::Hotp { counter: 3 });
+        assert_eq!((parsed.issuer.as_deref(), parsed.account.as_str(), parsed.digits), (Some("ACME"), "bob", 6));
+        assert_eq!(parsed.to_hotp().unwrap().generate(3), "969429");
+        assert!(parsed.to_totp().is_err());
+
+        let sha512 = OtpAuthUri { kind: OtpKind::Totp { step: 60 }, issuer: None, algorithm: OtpAlgorithm::Sha512, digits: 8, ..uri.clone() };
+        assert_eq!(OtpAuthUri::parse(&sha512.to_uri()).unwrap(), sha512);
+
+        assert!(matches!(OtpAuthUri::parse("https://example.com"), Err(Error::InvalidFormat(_))));
+        assert!(OtpAuthUri::parse("otpauth://hotp/bob?secret=GEZDGNBV").is_err());
+        assert!(OtpAuthUri::parse("otpauth://totp/bob?issuer=A").is_err());
+        assert!(OtpAuthUri::parse("otpauth://totp/A:bob?secret=GEZDGNBV&issuer=B").is_err());
+        assert!(OtpAuthUri::parse("otpauth://totp/bob?secret=GEZDGNB1").is_err());
+        assert!(OtpAuthUri::parse("otpauth://totp/bob?secret=GEZDGNBV&digits=4").is_err());
+        assert!(OtpAuthUri::parse("otpauth://totp/bob?secret=GEZDGNBV&algorithm=MD5").is_err());
+    }
+
+    #[test]
+    fn test_base32() {
+        assert_eq!(base32_encode(b""), "");
+        assert_eq!(base32_encode(b"f"), "MY");
+        assert_eq!(base32_encode(b"foobar"), "MZXW6YTBOI");
+        assert_eq!(base32_encode(b"Hello!\xde\xad\xbe\xef"), "JBSWY3DPEHPK3PXP");
+
+        assert_eq!(base32_decode("MZXW6YTBOI======").unwrap(), b"foobar");
+        assert_eq!(base32_decode("jbsw y3dp ehpk 3pxp").unwrap(), b"Hello!\xde\xad\xbe\xef");
+        assert!(base32_decode("MZXW0").is_err());
+    }
+}
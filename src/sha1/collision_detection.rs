@@ -0,0 +1,201 @@
+// Counter-cryptanalysis for SHA-1 (Marc Stevens, "Counter-cryptanalysis", CRYPTO 2013), the idea behind sha1dc.
+// Every known practical collision attack (SHAttered, Shambles) uses a near-collision block pair built on one of
+// a few disturbance vectors (DV). For each DV we take the message block with the DV message difference applied,
+// recompute its chaining input backwards from the intermediate state and its output forward. If that output is
+// equal to the real one, the block is one half of a crafted collision.
+// Unlike sha1dc there is no unavoidable bit conditions pre-filter, so every DV is checked for every block.
+use super::sha1::{expand_words, step, step_back, BLOCK_SIZE};
+
+// Disturbance vectors checked by sha1dc: (type, K, b) for I(K, b) and II(K, b)
+const DISTURBANCE_VECTORS: [(u8, usize, u32); 32] = [
+    (1, 43, 0), (1, 44, 0), (1, 45, 0), (1, 46, 0), (1, 46, 2), (1, 47, 0), (1, 47, 2), (1, 48, 0),
+    (1, 48, 2), (1, 49, 0), (1, 49, 2), (1, 50, 0), (1, 50, 2), (1, 51, 0), (1, 51, 2), (1, 52, 0),
+    (2, 45, 0), (2, 46, 0), (2, 46, 2), (2, 47, 0), (2, 48, 0), (2, 49, 0), (2, 49, 2), (2, 50, 0),
+    (2, 50, 2), (2, 51, 0), (2, 51, 2), (2, 52, 0), (2, 53, 0), (2, 54, 0), (2, 55, 0), (2, 56, 0),
+];
+
+// First 16 words of the message XOR-difference for I(43, 0) and II(45, 0). The difference satisfies the
+// message expansion, so these words define all 80 of them. I(K, b) is I(43, 0) moved by K - 43 steps
+// and rotated left by b bits, the same for type II.
+const MESSAGE_DIFFERENCE_I_43: [u32; 16] = [
+    0x08000000, 0x9800000c, 0xd8000010, 0x08000010, 0xb8000010, 0x98000000, 0x60000000, 0x00000008,
+    0xc0000000, 0x90000014, 0x10000010, 0xb8000014, 0x28000000, 0x20000010, 0x48000000, 0x08000018,
+];
+
+const MESSAGE_DIFFERENCE_II_45: [u32; 16] = [
+    0xec000014, 0x0c000002, 0xc0000010, 0xb400001c, 0x2c000004, 0xbc000018, 0xb0000010, 0x0000000c,
+    0xb8000010, 0x08000018, 0x78000010, 0x08000014, 0x70000010, 0xb800001c, 0xe8000000, 0xb0000004,
+];
+
+#[derive(Clone)]
+pub struct CollisionDetection {
+    message_differences: Vec<([u32; 80], usize)>,
+    safe_hash: bool,
+    found: bool,
+}
+
+impl CollisionDetection {
+    // 'safe_hash' - when a collision block is found, it's processed two more times, so both colliding
+    // messages get different hashes (the mitigation used by git)
+    pub fn new(safe_hash: bool) -> Self {
+        let message_differences = DISTURBANCE_VECTORS
+            .iter()
+            .map(|(dv_type, k, b)| {
+                // Recompression starts from the step where the DV has no state differences
+                let test_step = if *k < 50 { 58 } else { 65 };
+                (message_difference(*dv_type, *k, *b), test_step)
+            })
+            .collect();
+
+        CollisionDetection { message_differences, safe_hash, found: false }
+    }
+
+    pub fn found(&self) -> bool {
+        self.found
+    }
+
+    pub fn clear(&mut self) {
+        self.found = false;
+    }
+
+    // Compresses the block like the plain SHA-1 and checks it against every DV
+    pub fn compress(&mut self, state: &mut [u32; 5], block: &[u8; BLOCK_SIZE]) {
+        let words = expand_words(block);
+
+        // States before the steps 58 and 65
+        let mut working = *state;
+        let mut state_58 = working;
+        let mut state_65 = working;
+        for (i, word) in words.iter().enumerate() {
+            if i == 58 {
+                state_58 = working;
+            }
+            if i == 65 {
+                state_65 = working;
+            }
+            working = step(i, working, *word);
+        }
+
+        for (h, value) in state.iter_mut().zip(working) {
+            *h = h.wrapping_add(value);
+        }
+
+        for (difference, test_step) in self.message_differences.iter() {
+            let mut other_words = words;
+            for (word, delta) in other_words.iter_mut().zip(difference.iter()) {
+                *word ^= delta;
+            }
+
+            let test_state = if *test_step == 58 { state_58 } else { state_65 };
+            let other_output = recompress(&other_words, *test_step, test_state);
+
+            if other_output == *state {
+                self.found = true;
+
+                if self.safe_hash {
+                    compress_words(state, &words);
+                    compress_words(state, &words);
+                }
+                break;
+            }
+        }
+    }
+}
+
+// Expanded message XOR-difference of the disturbance vector I(K, b) or II(K, b)
+fn message_difference(dv_type: u8, k: usize, b: u32) -> [u32; 80] {
+    let (base, base_k) = if dv_type == 1 { (MESSAGE_DIFFERENCE_I_43, 43) } else { (MESSAGE_DIFFERENCE_II_45, 45) };
+    let shift = k - base_k;
+
+    // Expand forward: W[i] = (W[i-3] ^ W[i-8] ^ W[i-14] ^ W[i-16]) <<< 1,
+    // and backward: W[i-16] = (W[i] >>> 1) ^ W[i-3] ^ W[i-8] ^ W[i-14]
+    let mut words = vec![0u32; 80 + shift];
+    words[shift..shift + 16].copy_from_slice(&base);
+    for i in shift + 16..80 + shift {
+        words[i] = (words[i - 3] ^ words[i - 8] ^ words[i - 14] ^ words[i - 16]).rotate_left(1);
+    }
+    for i in (16..shift + 16).rev() {
+        words[i - 16] = words[i].rotate_right(1) ^ words[i - 3] ^ words[i - 8] ^ words[i - 14];
+    }
+
+    let mut difference = [0u32; 80];
+    for (delta, word) in difference.iter_mut().zip(words.iter()) {
+        *delta = word.rotate_left(b);
+    }
+
+    difference
+}
+
+// Goes back from the state before 'test_step' to the chaining input, then forward to the chaining output
+fn recompress(words: &[u32; 80], test_step: usize, test_state: [u32; 5]) -> [u32; 5] {
+    let mut chaining_input = test_state;
+    for i in (0..test_step).rev() {
+        chaining_input = step_back(i, chaining_input, words[i]);
+    }
+
+    let mut working = test_state;
+    for (i, word) in words.iter().enumerate().skip(test_step) {
+        working = step(i, working, *word);
+    }
+
+    let mut output = chaining_input;
+    for (h, value) in output.iter_mut().zip(working) {
+        *h = h.wrapping_add(value);
+    }
+
+    output
+}
+
+fn compress_words(state: &mut [u32; 5], words: &[u32; 80]) {
+    let mut working = *state;
+    for (i, word) in words.iter().enumerate() {
+        working = step(i, working, *word);
+    }
+
+    for (h, value) in state.iter_mut().zip(working) {
+        *h = h.wrapping_add(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_differences_follow_expansion() {
+        for (dv_type, k, b) in DISTURBANCE_VECTORS.iter() {
+            let difference = message_difference(*dv_type, *k, *b);
+            for i in 16..80 {
+                let expanded = (difference[i - 3] ^ difference[i - 8] ^ difference[i - 14] ^ difference[i - 16]).rotate_left(1);
+                assert_eq!(difference[i], expanded);
+            }
+        }
+
+        // Values from the sha1dc table for I(44, 0) and II(56, 0)
+        assert_eq!(message_difference(1, 44, 0)[0], 0xb4000008);
+        assert_eq!(message_difference(1, 44, 0)[1..16], MESSAGE_DIFFERENCE_I_43[..15]);
+        assert_eq!(message_difference(2, 56, 0)[0], 0x2600001a);
+        assert_eq!(message_difference(2, 56, 0)[79], 0xc0000046);
+        assert_eq!(message_difference(1, 46, 2)[3], MESSAGE_DIFFERENCE_I_43[0].rotate_left(2));
+    }
+
+    #[test]
+    fn test_recompress_plain_block() {
+        // Without a message difference the recompression must give the normal chaining output
+        let block = [0x61u8; BLOCK_SIZE];
+        let words = expand_words(&block);
+
+        let mut expected = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+        let mut working = expected;
+        let mut state_58 = working;
+        for (i, word) in words.iter().enumerate() {
+            if i == 58 {
+                state_58 = working;
+            }
+            working = step(i, working, *word);
+        }
+        compress_words(&mut expected, &words);
+
+        assert_eq!(recompress(&words, 58, state_58), expected);
+    }
+}
@@ -0,0 +1,208 @@
+// Round-reduced and instrumented SHA-1 for cryptanalysis: the compression function runs only the first
+// 'steps' steps (the feed-forward is kept), and every step can be reported to an observer.
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use crate::error::Error;
+use crate::md::block_buffer::BlockBuffer;
+use super::sha1::{expand_words, round_function, step, BLOCK_SIZE, DIGEST_SIZE, INITIAL_STATE};
+
+pub const FULL_STEPS: usize = 80;
+
+const CSV_HEADER: &str = "block,step,a,b,c,d,e,w,f,k";
+
+// One step of the main loop: (a, b, c, d, e) after the step, the message word W[step],
+// the boolean function value 'f' (from the state before the step) and the constant 'k'
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StepTrace {
+    pub block: usize,
+    pub step: usize,
+    pub state: [u32; 5],
+    pub word: u32,
+    pub f: u32,
+    pub k: u32,
+}
+
+type StepObserver<'a> = Box<dyn FnMut(&StepTrace) + 'a>;
+
+pub struct ReducedSha1<'a> {
+    steps: usize,
+    state: [u32; 5],
+    buffer: BlockBuffer<BLOCK_SIZE>,
+    // Index of the next block, for the trace
+    blocks: usize,
+    observer: Option<StepObserver<'a>>,
+}
+
+impl<'a> ReducedSha1<'a> {
+    // 'steps' - from 1 to 80, 80 is the normal SHA-1
+    pub fn new(steps: usize) -> Result<Self, Error> {
+        if !(1..=FULL_STEPS).contains(&steps) {
+            return Err(Error::InvalidArgument(format!("Steps must be from 1 to {}, got: {}", FULL_STEPS, steps)));
+        }
+
+        Ok(ReducedSha1 { steps, state: INITIAL_STATE, buffer: BlockBuffer::new(), blocks: 0, observer: None })
+    }
+
+    // The observer is called after every step of every block, including the padding blocks
+    pub fn with_observer(steps: usize, observer: impl FnMut(&StepTrace) + 'a) -> Result<Self, Error> {
+        let mut hasher = ReducedSha1::new(steps)?;
+        hasher.observer = Some(Box::new(observer));
+        Ok(hasher)
+    }
+
+    pub fn update_bytes(&mut self, input: &[u8]) -> &mut Self {
+        let (steps, state, blocks, observer) = (self.steps, &mut self.state, &mut self.blocks, &mut self.observer);
+        self.buffer.update(input, |block| compress_reduced(steps, state, blocks, observer, block));
+        self
+    }
+
+    // Returns the digest and starts a new message, the empty message is allowed
+    pub fn finalize(&mut self) -> [u8; DIGEST_SIZE] {
+        let (steps, state, blocks, observer) = (self.steps, &mut self.state, &mut self.blocks, &mut self.observer);
+        self.buffer.finalize(8, |block| compress_reduced(steps, state, blocks, observer, block));
+
+        let mut hash = [0u8; DIGEST_SIZE];
+        for (bytes, h) in hash.chunks_exact_mut(4).zip(self.state.iter()) {
+            bytes.copy_from_slice(&h.to_be_bytes());
+        }
+
+        self.state = INITIAL_STATE;
+        self.blocks = 0;
+        hash
+    }
+}
+
+fn compress_reduced(
+    steps: usize,
+    state: &mut [u32; 5],
+    blocks: &mut usize,
+    observer: &mut Option<StepObserver<'_>>,
+    block: &[u8; BLOCK_SIZE],
+) {
+    let words = expand_words(block);
+    let mut working = *state;
+
+    for (i, word) in words.iter().enumerate().take(steps) {
+        let [_, b, c, d, _] = working;
+        let (f, k) = round_function(i, b, c, d);
+        working = step(i, working, *word);
+
+        if let Some(observer) = observer.as_mut() {
+            observer(&StepTrace { block: *blocks, step: i, state: working, word: *word, f, k });
+        }
+    }
+
+    for (h, value) in state.iter_mut().zip(working) {
+        *h = h.wrapping_add(value);
+    }
+    *blocks += 1;
+}
+
+// Digest and the trace of every step for the message
+pub fn trace(steps: usize, message: &[u8]) -> Result<([u8; DIGEST_SIZE], Vec<StepTrace>), Error> {
+    let mut steps_trace = Vec::new();
+    let digest = ReducedSha1::with_observer(steps, |step_trace| steps_trace.push(*step_trace))?.update_bytes(message).finalize();
+
+    Ok((digest, steps_trace))
+}
+
+// One row per step: block,step,a,b,c,d,e,w,f,k, words as 8 hex digits
+pub fn trace_to_csv(steps_trace: &[StepTrace]) -> String {
+    let mut csv = String::from(CSV_HEADER);
+    csv.push('\n');
+
+    for step_trace in steps_trace {
+        let [a, b, c, d, e] = step_trace.state;
+        let _ = writeln!(
+            csv,
+            "{},{},{:08x},{:08x},{:08x},{:08x},{:08x},{:08x},{:08x},{:08x}",
+            step_trace.block, step_trace.step, a, b, c, d, e, step_trace.word, step_trace.f, step_trace.k
+        );
+    }
+
+    csv
+}
+
+pub fn write_trace_csv<W: Write>(mut writer: W, steps_trace: &[StepTrace]) -> io::Result<()> {
+    writer.write_all(trace_to_csv(steps_trace).as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sha1::sha1::{sha1_const, Sha1Realization32};
+
+    #[test]
+    fn test_full_steps_match_sha1() {
+        for message in [&b"abc"[..], b"", &[0x61; 64], &[0x5a; 200]] {
+            let digest = ReducedSha1::new(FULL_STEPS).unwrap().update_bytes(message).finalize();
+            assert_eq!(digest, sha1_const(message));
+        }
+
+        let digest = ReducedSha1::new(FULL_STEPS).unwrap().update_bytes(b"Hello").finalize();
+        assert_eq!(digest, Sha1Realization32::new().update("Hello").finalize().digest());
+        assert_eq!(hex::encode(ReducedSha1::new(FULL_STEPS).unwrap().finalize()), "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+        assert!(ReducedSha1::new(0).is_err());
+        assert!(ReducedSha1::new(81).is_err());
+    }
+
+    #[test]
+    fn test_trace_abc() {
+        // Intermediate values from the "abc" example of FIPS 180
+        let (digest, steps_trace) = trace(FULL_STEPS, b"abc").unwrap();
+        assert_eq!(hex::encode(digest), "a9993e364706816aba3e25717850c26c9cd0d89d");
+        assert_eq!(steps_trace.len(), 80);
+
+        assert_eq!(steps_trace[0], StepTrace { block: 0, step: 0, state: [0x0116fc33, 0x67452301, 0x7bf36ae2, 0x98badcfe, 0x10325476], word: 0x61626380, f: 0x98badcfe, k: 0x5a827999 });
+        assert_eq!(steps_trace[79].state, [0x42541b35, 0x5738d5e1, 0x21834873, 0x681e6df6, 0xd8fdf6ad]);
+        assert_eq!(steps_trace[79].k, 0xca62c1d6);
+
+        // Reduced hash is the feed-forward of the state after the last step
+        let (digest, steps_trace) = trace(20, b"abc").unwrap();
+        assert_eq!(steps_trace.len(), 20);
+        let mut expected = [0u8; DIGEST_SIZE];
+        for (i, (h, value)) in INITIAL_STATE.iter().zip(steps_trace[19].state).enumerate() {
+            expected[4 * i..4 * i + 4].copy_from_slice(&h.wrapping_add(value).to_be_bytes());
+        }
+        assert_eq!(digest, expected);
+    }
+
+    #[test]
+    fn test_trace_csv() {
+        let (_, steps_trace) = trace(40, &[0x61; 60]).unwrap();
+        // 60 bytes need a second block for the length
+        assert_eq!(steps_trace.len(), 80);
+        assert_eq!((steps_trace[40].block, steps_trace[40].step), (1, 0));
+
+        let csv = trace_to_csv(&steps_trace);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 81);
+        assert_eq!(lines[0], "block,step,a,b,c,d,e,w,f,k");
+        assert!(lines[1].starts_with("0,0,"));
+        assert!(lines[1].ends_with(",61616161,98badcfe,5a827999"));
+        assert!(lines[80].starts_with("1,39,"));
+
+        let mut written = Vec::new();
+        write_trace_csv(&mut written, &steps_trace).unwrap();
+        assert_eq!(written, csv.as_bytes());
+    }
+
+    #[test]
+    fn test_reduced_collision() {
+        // With 15 steps the message word W[15] is never used, so blocks that differ only in it collide
+        let first = [0x61u8; 64];
+        let mut second = first;
+        second[60..].copy_from_slice(&[0x12, 0x34, 0x56, 0x78]);
+
+        let hash = |steps: usize, message: &[u8]| ReducedSha1::new(steps).unwrap().update_bytes(message).finalize();
+        assert_eq!(hash(15, &first), hash(15, &second));
+        assert_ne!(hash(16, &first), hash(16, &second));
+        assert_ne!(hash(FULL_STEPS, &first), hash(FULL_STEPS, &second));
+
+        // The trace shows where the states start to differ
+        let (_, first_trace) = trace(20, &first).unwrap();
+        let (_, second_trace) = trace(20, &second).unwrap();
+        let first_difference = first_trace.iter().zip(second_trace.iter()).position(|(left, right)| left.state != right.state);
+        assert_eq!(first_difference, Some(15));
+    }
+}
@@ -1,123 +1,240 @@
+use std::io::{self, Read};
+use digest::{FixedOutput, FixedOutputReset, HashMarker, Output, OutputSizeUser, Reset, Update};
+use digest::core_api::BlockSizeUser;
+use digest::typenum::{U20, U64};
+use crate::md::block_buffer::{read_chunks, BlockBuffer};
+use super::collision_detection::CollisionDetection;
+use crate::error::Error;
+
+// Size of one SHA-1 block in bytes (512 bits)
+pub const BLOCK_SIZE: usize = 64;
+
+// Size of the SHA-1 digest in bytes (160 bits)
+pub const DIGEST_SIZE: usize = 20;
+
+// Initial chaining values h0..h4
+pub(super) const INITIAL_STATE: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+
+// Snapshot of the running hasher (midstate): chaining values h0..h4, the pending partial block
+// and the count of processed bytes (including the pending ones)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Sha1State {
+    pub chaining_values: [u32; 5],
+    pub pending: Vec<u8>,
+    pub length: u64,
+}
+
+// Size of the serialized state without the pending bytes: h0..h4 and the length
+const STATE_HEADER_SIZE: usize = 28;
+
+impl Sha1State {
+    // h0..h4 (big-endian) || length in bytes (64-bit big-endian) || pending bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes: Vec<u8> = self.chaining_values.iter().flat_map(|h| h.to_be_bytes()).collect();
+        bytes.extend_from_slice(&self.length.to_be_bytes());
+        bytes.extend_from_slice(&self.pending);
+
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() < STATE_HEADER_SIZE || bytes.len() >= STATE_HEADER_SIZE + BLOCK_SIZE {
+            return Err(Error::InvalidFormat(format!("Serialized SHA-1 state must be from {} to {} bytes, got: {}", STATE_HEADER_SIZE, STATE_HEADER_SIZE + BLOCK_SIZE - 1, bytes.len())));
+        }
+
+        let mut chaining_values = [0u32; 5];
+        for (h, chunk_bytes) in chaining_values.iter_mut().zip(bytes[..20].chunks_exact(4)) {
+            *h = u32::from_be_bytes([chunk_bytes[0], chunk_bytes[1], chunk_bytes[2], chunk_bytes[3]]);
+        }
+
+        let mut length_bytes = [0u8; 8];
+        length_bytes.copy_from_slice(&bytes[20..STATE_HEADER_SIZE]);
+
+        let state = Sha1State {
+            chaining_values,
+            pending: bytes[STATE_HEADER_SIZE..].to_vec(),
+            length: u64::from_be_bytes(length_bytes),
+        };
+        state.validate()?;
+
+        Ok(state)
+    }
+
+    // The pending data must be exactly the part of the message after the last full block
+    pub fn validate(&self) -> Result<(), Error> {
+        BlockBuffer::<BLOCK_SIZE>::restore(&self.pending, self.length as u128)?;
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
 pub struct Sha1Realization32 {
-    buffer: Vec<u8>,
-    hash: Vec<u8>,
+    state: [u32; 5],
+    buffer: BlockBuffer<BLOCK_SIZE>,
+    hash: [u8; DIGEST_SIZE],
+    detection: Option<CollisionDetection>,
+    // Bits of a message that isn't byte-aligned: the 'trailing_bits' most significant bits of 'trailing_byte'
+    trailing_byte: u8,
+    trailing_bits: u8,
+}
+
+impl Default for Sha1Realization32 {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Sha1Realization32 {
     pub fn new() -> Self {
-        Sha1Realization32 { buffer: vec![], hash: vec![] }
+        Sha1Realization32 {
+            state: INITIAL_STATE,
+            buffer: BlockBuffer::new(),
+            hash: [0; DIGEST_SIZE],
+            detection: None,
+            trailing_byte: 0,
+            trailing_bits: 0,
+        }
     }
 
-    pub fn update(&self, input: &str) -> Self {
-        let buffer = input.bytes().collect();
+    // Checks every block for the known SHA-1 collision attacks (like sha1dc), see 'collision_detected'.
+    // The digest stays the normal SHA-1 value.
+    pub fn new_collision_detecting() -> Self {
+        Sha1Realization32 { detection: Some(CollisionDetection::new(false)), ..Sha1Realization32::new() }
+    }
 
-        Sha1Realization32 { buffer, hash: vec![] }
+    // Collision detection plus the "safe hash" used by git: a detected collision block is processed
+    // two more times, so the colliding messages get different digests.
+    pub fn new_safe_hash() -> Self {
+        Sha1Realization32 { detection: Some(CollisionDetection::new(true)), ..Sha1Realization32::new() }
     }
 
-    pub fn hash(&self) -> Self {
-        if self.buffer.len() == 0 {
-            panic!("Emty buffer, call 'update' before hash")
+    // Midstate of the current message, e.g. to checkpoint a long-running hash or precompute a common prefix.
+    // Only byte-aligned messages can be exported.
+    pub fn export_state(&self) -> Result<Sha1State, Error> {
+        if self.trailing_bits != 0 {
+            return Err(Error::UnalignedMessage { trailing_bits: self.trailing_bits });
         }
 
-        let mut buffer: Vec<u8> = self.buffer.clone();
-        let original_length = buffer.len() as u64 * 8;
+        Ok(Sha1State {
+            chaining_values: self.state,
+            pending: self.buffer.pending().to_vec(),
+            length: self.buffer.length() as u64,
+        })
+    }
+
+    // Continues hashing from a midstate made by 'export_state', the restored hasher has no collision detection
+    pub fn from_state(state: &Sha1State) -> Result<Self, Error> {
+        let buffer = BlockBuffer::restore(&state.pending, state.length as u128)?;
 
-        // Join bit '1' to the message
-        buffer.push(0x80);
+        Ok(Sha1Realization32 { state: state.chaining_values, buffer, ..Sha1Realization32::new() })
+    }
+
+    // Whether the current message (or the last finalized one, until the next 'update') contains
+    // a block that looks like one half of a crafted collision. Always false without detection.
+    pub fn collision_detected(&self) -> bool {
+        self.detection.as_ref().is_some_and(|detection| detection.found())
+    }
+
+    // Appends input to the message. Every full 64-byte block is compressed right away,
+    // only the incomplete tail stays in the buffer, so memory use doesn't depend on the input size.
+    pub fn update(&mut self, input: &str) -> &mut Self {
+        self.update_bytes(input.as_bytes())
+    }
 
-        // Join k '0' bits, where k is the smallest number ≥ 0 such that the length of the resulting message
-        // (in bits) is equal modulo 512 to 448 (length mod 512 == 448)
-        while (buffer.len() * 8) % 512 != 448 {
-            buffer.push(0);
+    pub fn update_bytes(&mut self, input: &[u8]) -> &mut Self {
+        // The result of the previous message is cleared when a new one starts
+        if let (0, 0, Some(detection)) = (self.buffer.length(), self.trailing_bits, self.detection.as_mut()) {
+            detection.clear();
         }
 
-        // Add the length of the original message (before preprocessing) as a whole 64-bit
-        // Big-endian number, in bits.
-        let length_bytes: [u8; 8] = (original_length as u64).to_be_bytes();
-        buffer.extend_from_slice(&length_bytes);
+        // After a non byte-aligned part every input byte is split between two message bytes
+        if self.trailing_bits != 0 {
+            let realigned: Vec<u8> = input.iter().filter_map(|byte| self.push_bits(*byte, 8)).collect();
+            return self.update_aligned(&realigned);
+        }
 
-        // Variable initialization:
-        let mut h0: u32 = 0x67452301;
-        let mut h1: u32 = 0xEFCDAB89;
-        let mut h2: u32 = 0x98BADCFE;
-        let mut h3: u32 = 0x10325476;
-        let mut h4: u32 = 0xC3D2E1F0;
+        self.update_aligned(input)
+    }
 
-        // In the process, the message is broken down sequentially by 512 bits:
-        for chunk in buffer.chunks(64) {
-            let mut words: [u32; 80] = [0; 80];
+    // Appends the first 'bit_length' bits of the input (the most significant bits first, as in FIPS 180-4),
+    // so the message length doesn't have to be a multiple of 8
+    pub fn update_bits(&mut self, input: &[u8], bit_length: usize) -> Result<&mut Self, Error> {
+        if bit_length > input.len() * 8 {
+            return Err(Error::InvalidArgument(format!("Bit length {} is bigger than the input ({} bytes)", bit_length, input.len())));
+        }
 
-            // Initializing a word array
-            for (i, chunk_bytes) in chunk.chunks(4).enumerate() {
-                let mut word = [0u8; 4];
-                word.copy_from_slice(chunk_bytes);
-                words[i] = u32::from_be_bytes(word);
-            }
+        let full_bytes = bit_length / 8;
+        self.update_bytes(&input[..full_bytes]);
 
-            // 16 32-bit words are augmented to 80 32-bit words:
-            for i in 16..80 {
-                let word = words[i - 3] ^ words[i - 8] ^ words[i - 14] ^ words[i - 16];
-                words[i] = word.rotate_left(1);
+        let rest_bits = (bit_length % 8) as u8;
+        if rest_bits != 0 {
+            if let Some(byte) = self.push_bits(input[full_bytes], rest_bits) {
+                self.update_aligned(&[byte]);
             }
+        }
 
-            // Initializing the hash values of this part:
-            let mut a = h0;
-            let mut b = h1;
-            let mut c = h2;
-            let mut d = h3;
-            let mut e = h4;
-
-            // Main loop:
-            for i in 0..80 {
-                let f;
-                let k;
-
-                if i < 20 {
-                    f = (b & c) | (!b & d);
-                    k = 0x5A827999;
-                } else if i < 40 {
-                    f = b ^ c ^ d;
-                    k = 0x6ED9EBA1;
-                } else if i < 60 {
-                    f = (b & c) | (b & d) | (c & d);
-                    k = 0x8F1BBCDC;
-                } else {
-                    f = b ^ c ^ d;
-                    k = 0xCA62C1D6;
-                }
+        Ok(self)
+    }
 
-                let temp = a.rotate_left(5)
-                    .wrapping_add(f)
-                    .wrapping_add(e)
-                    .wrapping_add(words[i])
-                    .wrapping_add(k);
-
-                e = d;
-                d = c;
-                c = b.rotate_left(30);
-                b = a;
-                a = temp;
-            }
+    fn update_aligned(&mut self, input: &[u8]) -> &mut Self {
+        let state = &mut self.state;
+        let detection = &mut self.detection;
+        self.buffer.update(input, |block| compress_block(state, detection, block));
+        self
+    }
 
-            // We add the hash value of this part to the result:
-            h0 = h0.wrapping_add(a);
-            h1 = h1.wrapping_add(b);
-            h2 = h2.wrapping_add(c);
-            h3 = h3.wrapping_add(d);
-            h4 = h4.wrapping_add(e);
+    // Adds 'count' most significant bits of 'byte' to the trailing bits, returns a byte once it's complete
+    fn push_bits(&mut self, byte: u8, count: u8) -> Option<u8> {
+        let byte = byte & !(0xffu16 >> count) as u8;
+        let joined = ((self.trailing_byte as u16) << 8) | ((byte as u16) << (8 - self.trailing_bits));
+        let total_bits = self.trailing_bits + count;
+
+        if total_bits < 8 {
+            self.trailing_byte = (joined >> 8) as u8;
+            self.trailing_bits = total_bits;
+            return None;
         }
 
+        self.trailing_byte = joined as u8;
+        self.trailing_bits = total_bits - 8;
+        Some((joined >> 8) as u8)
+    }
+
+    // Reads the source to the end in fixed-size chunks, e.g. a file, stdin or a socket
+    pub fn update_reader<R: Read>(&mut self, reader: R) -> io::Result<&mut Self> {
+        read_chunks(reader, |chunk| {
+            self.update_bytes(chunk);
+        })?;
+        Ok(self)
+    }
+
+    // Pads the buffered tail, compresses the last block(s) and stores the digest.
+    // After that the hasher is reset and ready for a new message. An empty message is a padding block only.
+    pub fn finalize(&mut self) -> &mut Self {
+        // Message padding with the 64-bit length in bits
+        let state = &mut self.state;
+        let detection = &mut self.detection;
+        self.buffer.finalize_bits(self.trailing_byte, self.trailing_bits, 8, |block| compress_block(state, detection, block));
+
         // Total hash value(h0, h1, h2, h3, h4 must be converted to big-endian):
-        let hash = [
-            h0.to_be_bytes(),
-            h1.to_be_bytes(),
-            h2.to_be_bytes(),
-            h3.to_be_bytes(),
-            h4.to_be_bytes(),
-        ]
-        .concat();
+        let mut hash = [0u8; DIGEST_SIZE];
+        for (bytes, h) in hash.chunks_exact_mut(4).zip(self.state.iter()) {
+            bytes.copy_from_slice(&h.to_be_bytes());
+        }
 
-        Sha1Realization32 { buffer, hash }
+        *self = Sha1Realization32 { hash, detection: self.detection.take(), ..Sha1Realization32::new() };
+        self
+    }
+
+    // One-shot digest of everything passed to 'update' so far, the hasher itself isn't changed
+    pub fn hash(&self) -> Self {
+        let mut hasher = self.clone();
+        hasher.finalize();
+        hasher
+    }
+
+    pub fn digest(&self) -> [u8; DIGEST_SIZE] {
+        self.hash
     }
 
     pub fn to_hex(&self) -> String {
@@ -125,12 +242,203 @@ impl Sha1Realization32 {
     }
 }
 
+// RustCrypto 'digest' traits, so the hasher can be used anywhere a 'Digest' is expected
+impl HashMarker for Sha1Realization32 {}
+
+impl BlockSizeUser for Sha1Realization32 {
+    type BlockSize = U64;
+}
+
+impl OutputSizeUser for Sha1Realization32 {
+    type OutputSize = U20;
+}
+
+impl Update for Sha1Realization32 {
+    fn update(&mut self, data: &[u8]) {
+        self.update_bytes(data);
+    }
+}
+
+impl FixedOutput for Sha1Realization32 {
+    fn finalize_into(mut self, out: &mut Output<Self>) {
+        out.copy_from_slice(&self.finalize().digest());
+    }
+}
+
+impl FixedOutputReset for Sha1Realization32 {
+    fn finalize_into_reset(&mut self, out: &mut Output<Self>) {
+        // 'finalize' already leaves the hasher in the initial state
+        out.copy_from_slice(&self.finalize().digest());
+    }
+}
+
+impl Reset for Sha1Realization32 {
+    fn reset(&mut self) {
+        if let Some(detection) = self.detection.as_mut() {
+            detection.clear();
+        }
+
+        *self = Sha1Realization32 { detection: self.detection.take(), ..Sha1Realization32::new() };
+    }
+}
+
+fn compress_block(state: &mut [u32; 5], detection: &mut Option<CollisionDetection>, block: &[u8; BLOCK_SIZE]) {
+    match detection {
+        Some(detection) => detection.compress(state, block),
+        None => compress(state, block),
+    }
+}
+
+// One-shot SHA-1 that can run in const evaluation, e.g. for fingerprints of embedded resources:
+// const FINGERPRINT: [u8; 20] = sha1_const(include_bytes!("resource.bin"));
+// It uses the same compression core as 'Sha1Realization32', nothing is allocated.
+// Unlike 'finalize', the empty message is allowed.
+pub const fn sha1_const(input: &[u8]) -> [u8; DIGEST_SIZE] {
+    let mut state = INITIAL_STATE;
+
+    // Full blocks
+    let full_blocks = input.len() / BLOCK_SIZE;
+    let mut block_index = 0;
+    while block_index < full_blocks {
+        let mut block = [0u8; BLOCK_SIZE];
+        let mut i = 0;
+        while i < BLOCK_SIZE {
+            block[i] = input[block_index * BLOCK_SIZE + i];
+            i += 1;
+        }
+        compress(&mut state, &block);
+        block_index += 1;
+    }
+
+    // The tail, bit '1', zeros and the 64-bit length in bits, the same padding as 'BlockBuffer::finalize'
+    let tail = input.len() % BLOCK_SIZE;
+    let mut block = [0u8; BLOCK_SIZE];
+    let mut i = 0;
+    while i < tail {
+        block[i] = input[full_blocks * BLOCK_SIZE + i];
+        i += 1;
+    }
+    block[tail] = 0x80;
+
+    if tail + 1 > BLOCK_SIZE - 8 {
+        compress(&mut state, &block);
+        block = [0u8; BLOCK_SIZE];
+    }
+
+    let length_bytes = ((input.len() as u64).wrapping_mul(8)).to_be_bytes();
+    let mut i = 0;
+    while i < 8 {
+        block[BLOCK_SIZE - 8 + i] = length_bytes[i];
+        i += 1;
+    }
+    compress(&mut state, &block);
+
+    // h0..h4 in big-endian
+    let mut hash = [0u8; DIGEST_SIZE];
+    let mut i = 0;
+    while i < 5 {
+        let bytes = state[i].to_be_bytes();
+        hash[4 * i] = bytes[0];
+        hash[4 * i + 1] = bytes[1];
+        hash[4 * i + 2] = bytes[2];
+        hash[4 * i + 3] = bytes[3];
+        i += 1;
+    }
+
+    hash
+}
+
+// Processes a single 512-bit block and adds the result to the chaining values.
+// Written with 'while' loops only, so it's usable in const evaluation ('sha1_const').
+const fn compress(state: &mut [u32; 5], block: &[u8; BLOCK_SIZE]) {
+    let words = expand_words(block);
+
+    // Initializing the hash values of this part:
+    let mut working = *state;
+
+    // Main loop:
+    let mut i = 0;
+    while i < 80 {
+        working = step(i, working, words[i]);
+        i += 1;
+    }
+
+    // We add the hash value of this part to the result:
+    let mut i = 0;
+    while i < 5 {
+        state[i] = state[i].wrapping_add(working[i]);
+        i += 1;
+    }
+}
+
+pub(super) const fn expand_words(block: &[u8; BLOCK_SIZE]) -> [u32; 80] {
+    let mut words: [u32; 80] = [0; 80];
+
+    // Initializing a word array
+    let mut i = 0;
+    while i < 16 {
+        words[i] = u32::from_be_bytes([block[4 * i], block[4 * i + 1], block[4 * i + 2], block[4 * i + 3]]);
+        i += 1;
+    }
+
+    // 16 32-bit words are augmented to 80 32-bit words:
+    while i < 80 {
+        let word = words[i - 3] ^ words[i - 8] ^ words[i - 14] ^ words[i - 16];
+        words[i] = word.rotate_left(1);
+        i += 1;
+    }
+
+    words
+}
+
+// Boolean function 'f' and constant 'k' of the step 'i'
+pub(super) const fn round_function(i: usize, b: u32, c: u32, d: u32) -> (u32, u32) {
+    if i < 20 {
+        ((b & c) | (!b & d), 0x5A827999)
+    } else if i < 40 {
+        (b ^ c ^ d, 0x6ED9EBA1)
+    } else if i < 60 {
+        ((b & c) | (b & d) | (c & d), 0x8F1BBCDC)
+    } else {
+        (b ^ c ^ d, 0xCA62C1D6)
+    }
+}
+
+// One step of the main loop: (a, b, c, d, e) before the step 'i' -> (a, b, c, d, e) after it
+pub(super) const fn step(i: usize, [a, b, c, d, e]: [u32; 5], word: u32) -> [u32; 5] {
+    let (f, k) = round_function(i, b, c, d);
+
+    let temp = a.rotate_left(5)
+        .wrapping_add(f)
+        .wrapping_add(e)
+        .wrapping_add(word)
+        .wrapping_add(k);
+
+    [temp, a, b.rotate_left(30), c, d]
+}
+
+// Inverse of 'step': (a, b, c, d, e) after the step 'i' -> (a, b, c, d, e) before it
+pub(super) fn step_back(i: usize, [temp, a, c_rotated, c, d]: [u32; 5], word: u32) -> [u32; 5] {
+    let b = c_rotated.rotate_right(30);
+    let (f, k) = round_function(i, b, c, d);
+
+    let e = temp.wrapping_sub(
+        a.rotate_left(5)
+            .wrapping_add(f)
+            .wrapping_add(word)
+            .wrapping_add(k)
+    );
+
+    [a, b, c, d, e]
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::md::block_buffer::READ_CHUNK_SIZE;
     use rand::Rng;
     // use std::time::Instant;
-    use sha1::{Sha1, Digest};
+    use sha1::Sha1;
 
     fn generate_random_string(length: usize) -> String {
         let mut rng = rand::thread_rng();
@@ -150,19 +458,236 @@ mod tests {
     fn test_sha1_with_valid_hashes() {
         // Used https://emn178.github.io/online-tools/sha1.html for result check
 
-        let sha1_dl = Sha1Realization32::new();
-
-        assert_eq!(sha1_dl.update("Hello").hash().to_hex(), "f7ff9e8b7bb2e09b70935a5d785e0cc5d9d0abf0");
-        assert_eq!(sha1_dl.update("hello").hash().to_hex(), "aaf4c61ddcc5e8a2dabede0f3b482cd9aea9434d");
-        assert_eq!(sha1_dl.update("world").hash().to_hex(), "7c211433f02071597741e6ff5a8ea34789abbf43");
-        assert_eq!(sha1_dl.update("Hello world").hash().to_hex(), "7b502c3a1f48c8609ae212cdfb639dee39673f5e");
-        assert_eq!(sha1_dl.update("Програмна реалізація алгоритму гешування").hash().to_hex(), "60849d3ae9b32743c3d06693d14a2b08f561b87b");
+        assert_eq!(Sha1Realization32::new().update("Hello").finalize().to_hex(), "f7ff9e8b7bb2e09b70935a5d785e0cc5d9d0abf0");
+        assert_eq!(Sha1Realization32::new().update("hello").finalize().to_hex(), "aaf4c61ddcc5e8a2dabede0f3b482cd9aea9434d");
+        assert_eq!(Sha1Realization32::new().update("world").finalize().to_hex(), "7c211433f02071597741e6ff5a8ea34789abbf43");
+        assert_eq!(Sha1Realization32::new().update("Hello world").finalize().to_hex(), "7b502c3a1f48c8609ae212cdfb639dee39673f5e");
+        assert_eq!(Sha1Realization32::new().update("Програмна реалізація алгоритму гешування").finalize().to_hex(), "60849d3ae9b32743c3d06693d14a2b08f561b87b");
         assert_eq!(
-            sha1_dl.update("1 Теоретичні відомості. Сучасні блочні алгоритми гешування є важливою складовою криптографічних систем і використовуються для створення геш-значень, які є незворотніми ідентифікаторами вхідних даних. Одним з ключових аспектів сучасних блочних алгоритмів гешування є порядок обробки вхідних даних.").hash().to_hex(),
+            Sha1Realization32::new().update("1 Теоретичні відомості. Сучасні блочні алгоритми гешування є важливою складовою криптографічних систем і використовуються для створення геш-значень, які є незворотніми ідентифікаторами вхідних даних. Одним з ключових аспектів сучасних блочних алгоритмів гешування є порядок обробки вхідних даних.").finalize().to_hex(),
             "efdec6ff592d2cd154dbea402e56e0aee273fada"
         );
     }
 
+    #[test]
+    fn test_sha1_streaming_update() {
+        // "Hello world" split in parts gives the same hash as one call
+        let mut sha1_dl = Sha1Realization32::new();
+        sha1_dl.update("Hello").update(" ").update("world");
+        assert_eq!(sha1_dl.finalize().to_hex(), "7b502c3a1f48c8609ae212cdfb639dee39673f5e");
+
+        // After finalize the hasher starts a new message
+        assert_eq!(sha1_dl.update("hello").finalize().to_hex(), "aaf4c61ddcc5e8a2dabede0f3b482cd9aea9434d");
+
+        // 'hash' doesn't touch the running state
+        let mut sha1_dl = Sha1Realization32::new();
+        sha1_dl.update("Hello");
+        assert_eq!(sha1_dl.hash().to_hex(), "f7ff9e8b7bb2e09b70935a5d785e0cc5d9d0abf0");
+        assert_eq!(sha1_dl.update(" world").finalize().to_hex(), "7b502c3a1f48c8609ae212cdfb639dee39673f5e");
+
+        // Lengths around the block and padding boundaries, fed with different chunk sizes
+        for length in [1, 55, 56, 63, 64, 65, 119, 120, 128, 1000] {
+            let message = generate_random_string(length);
+            let expected = Sha1Realization32::new().update(&message).finalize().to_hex();
+
+            for chunk_size in [1, 3, 63, 64, 65] {
+                let mut sha1_dl = Sha1Realization32::new();
+                for chunk in message.as_bytes().chunks(chunk_size) {
+                    sha1_dl.update(std::str::from_utf8(chunk).unwrap());
+                }
+                assert_eq!(sha1_dl.finalize().to_hex(), expected, "length: {}, chunk size: {}", length, chunk_size);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sha1_bytes_and_reader() {
+        // Binary data, including bytes that aren't valid UTF-8
+        let bytes: Vec<u8> = (0..=255u8).cycle().take(1000).collect();
+        let mut lib_hasher = <Sha1 as digest::Digest>::new();
+        digest::Digest::update(&mut lib_hasher, &bytes);
+        let lib_digest: [u8; DIGEST_SIZE] = digest::Digest::finalize(lib_hasher).into();
+
+        let mut sha1_dl = Sha1Realization32::new();
+        assert_eq!(sha1_dl.update_bytes(&bytes).finalize().digest(), lib_digest);
+
+        // Reader source gives the same digest, also when it's longer than one read chunk
+        let mut sha1_dl = Sha1Realization32::new();
+        sha1_dl.update_reader(std::io::Cursor::new(&bytes)).unwrap();
+        assert_eq!(sha1_dl.finalize().digest(), lib_digest);
+
+        let bytes: Vec<u8> = (0..=255u8).cycle().take(3 * READ_CHUNK_SIZE + 7).collect();
+        let mut sha1_dl = Sha1Realization32::new();
+        sha1_dl.update_reader(bytes.as_slice()).unwrap();
+        assert_eq!(sha1_dl.finalize().digest(), Sha1Realization32::new().update_bytes(&bytes).finalize().digest());
+
+        // Digest and hex representation are the same value
+        let mut sha1_dl = Sha1Realization32::new();
+        sha1_dl.update_bytes(b"Hello").finalize();
+        assert_eq!(sha1_dl.digest(), hex_literal::hex!("f7ff9e8b7bb2e09b70935a5d785e0cc5d9d0abf0"));
+        assert_eq!(hex::encode(sha1_dl.digest()), sha1_dl.to_hex());
+    }
+
+    #[test]
+    fn test_sha1_collision_detection() {
+        // SHAttered PDF prefixes: different 320-byte messages with the same SHA-1
+        let shattered_1 = include_bytes!("test_data/shattered-1-prefix.bin");
+        let shattered_2 = include_bytes!("test_data/shattered-2-prefix.bin");
+        assert_ne!(shattered_1, shattered_2);
+
+        for message in [&shattered_1[..], &shattered_2[..]] {
+            assert_eq!(Sha1Realization32::new().update_bytes(message).finalize().to_hex(), "f92d74e3874587aaf443d1db961d4e26dde13e9c");
+
+            // Detection keeps the SHA-1 value, but reports the collision
+            let mut sha1_dl = Sha1Realization32::new_collision_detecting();
+            sha1_dl.update_bytes(message);
+            assert!(sha1_dl.collision_detected());
+            assert_eq!(sha1_dl.finalize().to_hex(), "f92d74e3874587aaf443d1db961d4e26dde13e9c");
+            assert!(sha1_dl.collision_detected());
+
+            // Next message starts clean
+            sha1_dl.update("Hello");
+            assert!(!sha1_dl.collision_detected());
+            assert_eq!(sha1_dl.finalize().to_hex(), "f7ff9e8b7bb2e09b70935a5d785e0cc5d9d0abf0");
+        }
+
+        // Safe hash gives different digests for the colliding pair, values match sha1dc
+        let mut sha1_dl = Sha1Realization32::new_safe_hash();
+        sha1_dl.update_bytes(shattered_1).finalize();
+        assert!(sha1_dl.collision_detected());
+        assert_eq!(sha1_dl.to_hex(), "7117b3cb9225aaf0d8ef1a40e493957b0bf8693d");
+        assert_eq!(Sha1Realization32::new_safe_hash().update_bytes(shattered_2).finalize().to_hex(), "29f38ae9fd98e2931120fa0bf213e024250d3f6a");
+
+        // Regular data isn't reported and has the normal SHA-1 value
+        let message = generate_random_string(1000);
+        let mut sha1_dl = Sha1Realization32::new_safe_hash();
+        sha1_dl.update(&message).finalize();
+        assert!(!sha1_dl.collision_detected());
+        assert_eq!(sha1_dl.to_hex(), Sha1Realization32::new().update(&message).finalize().to_hex());
+    }
+
+    #[test]
+    fn test_sha1_export_import_state() {
+        let prefix = generate_random_string(150);
+        let suffix = generate_random_string(90);
+        let expected = Sha1Realization32::new().update(&prefix).update(&suffix).finalize().to_hex();
+
+        // Midstate of the common prefix, serialized and resumed
+        let mut sha1_dl = Sha1Realization32::new();
+        sha1_dl.update(&prefix);
+        let state = sha1_dl.export_state().unwrap();
+        assert_eq!(state.length, 150);
+        assert_eq!(state.pending, prefix.as_bytes()[128..].to_vec());
+
+        let bytes = state.to_bytes();
+        assert_eq!(bytes.len(), 28 + 22);
+        let restored_state = Sha1State::from_bytes(&bytes).unwrap();
+        assert_eq!(restored_state, state);
+
+        let mut resumed = Sha1Realization32::from_state(&restored_state).unwrap();
+        assert_eq!(resumed.update(&suffix).finalize().to_hex(), expected);
+
+        // Initial state and a state on the block boundary
+        let empty_state = Sha1Realization32::new().export_state().unwrap();
+        assert_eq!(empty_state.chaining_values, INITIAL_STATE);
+        assert_eq!(Sha1Realization32::from_state(&empty_state).unwrap().update("Hello").finalize().to_hex(), "f7ff9e8b7bb2e09b70935a5d785e0cc5d9d0abf0");
+
+        let mut sha1_dl = Sha1Realization32::new();
+        sha1_dl.update(&prefix[..128]);
+        let state = sha1_dl.export_state().unwrap();
+        assert!(state.pending.is_empty());
+        let mut resumed = Sha1Realization32::from_state(&Sha1State::from_bytes(&state.to_bytes()).unwrap()).unwrap();
+        assert_eq!(resumed.update(&prefix[128..]).update(&suffix).finalize().to_hex(), expected);
+    }
+
+    #[test]
+    fn test_sha1_import_invalid_state() {
+        let mut sha1_dl = Sha1Realization32::new();
+        sha1_dl.update("Hello world");
+        let state = sha1_dl.export_state().unwrap();
+
+        // Length doesn't match the buffered data
+        let wrong_length = Sha1State { length: 12, ..state.clone() };
+        assert!(wrong_length.validate().is_err());
+        assert!(Sha1Realization32::from_state(&wrong_length).is_err());
+        assert!(Sha1State::from_bytes(&wrong_length.to_bytes()).is_err());
+
+        let wrong_pending = Sha1State { pending: b"Hello worl".to_vec(), ..state.clone() };
+        assert!(Sha1Realization32::from_state(&wrong_pending).is_err());
+
+        // Whole block can't be pending
+        let full_block = Sha1State { pending: vec![0; 64], length: 64, ..state.clone() };
+        assert!(Sha1Realization32::from_state(&full_block).is_err());
+
+        // Truncated or too long serialized data
+        let bytes = state.to_bytes();
+        assert!(matches!(Sha1State::from_bytes(&bytes[..27]), Err(Error::InvalidFormat(_))));
+        assert!(Sha1State::from_bytes(&[bytes.clone(), vec![0; 64]].concat()).is_err());
+    }
+
+    #[test]
+    fn test_sha1_const() {
+        // Computed during compilation
+        const EMPTY: [u8; DIGEST_SIZE] = sha1_const(b"");
+        const HELLO: [u8; DIGEST_SIZE] = sha1_const(b"Hello");
+        const SHATTERED_PREFIX: [u8; DIGEST_SIZE] = sha1_const(include_bytes!("test_data/shattered-1-prefix.bin"));
+
+        assert_eq!(hex::encode(EMPTY), "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+        assert_eq!(hex::encode(HELLO), "f7ff9e8b7bb2e09b70935a5d785e0cc5d9d0abf0");
+        assert_eq!(SHATTERED_PREFIX, Sha1Realization32::new().update_bytes(include_bytes!("test_data/shattered-1-prefix.bin")).finalize().digest());
+
+        // Lengths around the padding boundaries match the runtime hasher
+        let data = generate_random_string(200);
+        for length in [1, 55, 56, 63, 64, 65, 119, 120, 128, 200] {
+            let expected = Sha1Realization32::new().update(&data[..length]).finalize().digest();
+            assert_eq!(sha1_const(&data.as_bytes()[..length]), expected, "length {}", length);
+        }
+    }
+
+    #[test]
+    fn test_sha1_empty_message() {
+        assert_eq!(Sha1Realization32::new().finalize().to_hex(), "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+        assert_eq!(Sha1Realization32::new().update("").hash().to_hex(), "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+        assert_eq!(Sha1Realization32::new().update_bits(&[], 0).unwrap().finalize().digest(), sha1_const(b""));
+
+        // The hasher after 'finalize' starts an empty message again
+        let mut sha1_dl = Sha1Realization32::new();
+        sha1_dl.update("abc").finalize();
+        assert_eq!(sha1_dl.finalize().to_hex(), "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+    }
+
+    #[test]
+    fn test_sha1_digest_traits() {
+        use digest::Digest;
+
+        fn hash_in_parts<D: Digest>(parts: &[&str]) -> Vec<u8> {
+            let mut hasher = D::new();
+            for part in parts {
+                Digest::update(&mut hasher, part);
+            }
+            hasher.finalize().to_vec()
+        }
+
+        let parts = ["Hello", " ", "world"];
+        assert_eq!(hash_in_parts::<Sha1Realization32>(&parts), hash_in_parts::<Sha1>(&parts));
+        assert_eq!(hex::encode(<Sha1Realization32 as Digest>::digest(b"Hello world")), "7b502c3a1f48c8609ae212cdfb639dee39673f5e");
+
+        // Reset drops everything fed before
+        let mut hasher = <Sha1Realization32 as Digest>::new();
+        Digest::update(&mut hasher, b"world");
+        Digest::reset(&mut hasher);
+        Digest::update(&mut hasher, b"Hello");
+        assert_eq!(hex::encode(hasher.finalize_reset()), "f7ff9e8b7bb2e09b70935a5d785e0cc5d9d0abf0");
+        Digest::update(&mut hasher, b"hello");
+        assert_eq!(hex::encode(Digest::finalize(hasher)), "aaf4c61ddcc5e8a2dabede0f3b482cd9aea9434d");
+
+        assert_eq!(<Sha1Realization32 as Digest>::output_size(), DIGEST_SIZE);
+    }
+
+    fn hash_strings<D: digest::Digest>(strings: &[String]) -> Vec<String> {
+        strings.iter().map(|word| hex::encode(D::digest(word))).collect()
+    }
+
     #[test]
     fn test_comapre_sha1_realization_32_with_sha1_from_lib() {
         let strings_count = 500;
@@ -174,27 +699,21 @@ mod tests {
         }
 
         // let start = Instant::now();
-        for word in strings.iter() {
-            let mut hasher = Sha1::new();
-            hasher.update(word);
-            hasher.finalize();
-        }
+        let lib_hashes = hash_strings::<Sha1>(&strings);
         // let end = Instant::now();
         // let duration = end - start;
 
         // let duration_ms = duration.as_millis();
         // println!("Rust existed lib execution time for {} words with length {} symbols: {} ms", strings_count, string_length, duration_ms);
 
-        let sha1_dl = Sha1Realization32::new();
-
         // let start = Instant::now();
-        for word in strings.iter() {
-            sha1_dl.update(word).hash();
-        }
+        let realization_hashes = hash_strings::<Sha1Realization32>(&strings);
         // let end = Instant::now();
         // let duration = end - start;
 
         // let duration_ms = duration.as_millis();
         // println!("Realization execution time for {} words with length {} symbols: {} ms", strings_count, string_length, duration_ms);
+
+        assert_eq!(realization_hashes, lib_hashes);
     }
 }
\ No newline at end of file
@@ -0,0 +1,300 @@
+// Runner for the NIST CAVP SHAVS response files (SHA1ShortMsg.rsp, SHA1LongMsg.rsp, SHA1Monte.rsp and
+// the bit-oriented variants). The files aren't a part of the repository: download them from the CAVP page
+// and set CAVP_SHA_DIRECTORY (environment or .env) to the directory with them.
+use std::fs;
+use std::path::{Path, PathBuf};
+use super::sha1::{Sha1Realization32, DIGEST_SIZE};
+use crate::error::Error;
+
+pub const DIRECTORY_VARIABLE: &str = "CAVP_SHA_DIRECTORY";
+
+// Iterations of the inner Monte Carlo loop for every checkpoint
+const MONTE_CARLO_ITERATIONS: usize = 1000;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ShavsVector {
+    // ShortMsg/LongMsg: message of 'bit_length' bits and its digest
+    Message { bit_length: usize, message: Vec<u8>, digest: Vec<u8> },
+    // Monte Carlo: the seed and the digests expected after every outer iteration (COUNT = 0, 1, ...)
+    MonteCarlo { seed: Vec<u8>, checkpoints: Vec<Vec<u8>> },
+}
+
+#[derive(Debug, Default)]
+pub struct ShavsReport {
+    pub passed: usize,
+    // Descriptions of the failed vectors
+    pub failed: Vec<String>,
+}
+
+impl ShavsReport {
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+// Parses the response file: "[L = 20]" header, "Len"/"Msg"/"MD" triples or "Seed" with "COUNT"/"MD" pairs.
+// Comments (#) and empty lines are skipped.
+pub fn parse_rsp(content: &str) -> Result<Vec<ShavsVector>, Error> {
+    let mut vectors = Vec::new();
+    let mut bit_length: Option<usize> = None;
+    let mut message: Option<Vec<u8>> = None;
+    let mut seed: Option<Vec<u8>> = None;
+    let mut checkpoints = Vec::new();
+
+    for (line_index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let line_error = || format!("Line {}: '{}'", line_index + 1, line);
+
+        if let Some(header) = line.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+            if let Some(("L", value)) = header.split_once('=').map(|(key, value)| (key.trim(), value.trim())) {
+                if value != DIGEST_SIZE.to_string() {
+                    return Err(Error::InvalidFormat(format!("Only SHA-1 vectors (L = {}) are supported, got: L = {}", DIGEST_SIZE, value)));
+                }
+            }
+            continue;
+        }
+
+        let (key, value) = line.split_once('=').map(|(key, value)| (key.trim(), value.trim())).ok_or_else(|| Error::InvalidFormat(line_error()))?;
+
+        match key {
+            "Len" => bit_length = Some(value.parse().map_err(|_| Error::InvalidFormat(line_error()))?),
+            "Msg" => message = Some(hex::decode(value).map_err(|_| Error::InvalidFormat(line_error()))?),
+            "Seed" => seed = Some(hex::decode(value).map_err(|_| Error::InvalidFormat(line_error()))?),
+            "COUNT" => {
+                let count: usize = value.parse().map_err(|_| Error::InvalidFormat(line_error()))?;
+                if seed.is_some() && count != checkpoints.len() {
+                    return Err(Error::InvalidFormat(format!("{}: expected COUNT = {}", line_error(), checkpoints.len())));
+                }
+            }
+            "MD" => {
+                let digest = hex::decode(value).map_err(|_| Error::InvalidFormat(line_error()))?;
+                if digest.len() != DIGEST_SIZE {
+                    return Err(Error::InvalidFormat(format!("{}: digest must be {} bytes", line_error(), DIGEST_SIZE)));
+                }
+
+                if seed.is_some() {
+                    checkpoints.push(digest);
+                    continue;
+                }
+
+                let (length, message) = bit_length.take().zip(message.take()).ok_or_else(|| Error::InvalidFormat(format!("{}: MD without Len and Msg", line_error())))?;
+                // Msg of an empty message is "00"
+                if message.len() < length.div_ceil(8) {
+                    return Err(Error::InvalidFormat(format!("{}: Msg is shorter than Len = {} bits", line_error(), length)));
+                }
+                vectors.push(ShavsVector::Message { bit_length: length, message, digest });
+            }
+            _ => return Err(Error::InvalidFormat(format!("{}: unknown field", line_error()))),
+        }
+    }
+
+    if let Some(seed) = seed {
+        if seed.len() != DIGEST_SIZE {
+            return Err(Error::InvalidFormat(format!("Seed must be {} bytes", DIGEST_SIZE)));
+        }
+        vectors.push(ShavsVector::MonteCarlo { seed, checkpoints });
+    }
+
+    Ok(vectors)
+}
+
+// SHAVS Monte Carlo procedure: MD0 = MD1 = MD2 = Seed, MDi = SHA1(MDi-3 || MDi-2 || MDi-1) for i = 3..1002,
+// the checkpoint and the next seed is MD1002
+pub fn monte_carlo(seed: &[u8; DIGEST_SIZE], checkpoints: usize) -> Vec<[u8; DIGEST_SIZE]> {
+    let mut seed = *seed;
+    let mut results = Vec::with_capacity(checkpoints);
+    let mut hasher = Sha1Realization32::new();
+
+    for _ in 0..checkpoints {
+        let mut window = [seed, seed, seed];
+        for _ in 0..MONTE_CARLO_ITERATIONS {
+            let digest = hasher.update_bytes(&window.concat()).finalize().digest();
+            window = [window[1], window[2], digest];
+        }
+
+        seed = window[2];
+        results.push(seed);
+    }
+
+    results
+}
+
+pub fn check_vectors(vectors: &[ShavsVector]) -> ShavsReport {
+    let mut report = ShavsReport::default();
+
+    for (index, vector) in vectors.iter().enumerate() {
+        match vector {
+            ShavsVector::Message { bit_length, message, digest } => {
+                let mut hasher = Sha1Realization32::new();
+                let actual = match hasher.update_bits(message, *bit_length) {
+                    Ok(hasher) => hasher.finalize().digest(),
+                    Err(error) => {
+                        report.failed.push(format!("Vector {} (Len = {}): {}", index, bit_length, error));
+                        continue;
+                    }
+                };
+
+                if actual[..] == digest[..] {
+                    report.passed += 1;
+                } else {
+                    report.failed.push(format!("Vector {} (Len = {}): expected {}, got {}", index, bit_length, hex::encode(digest), hex::encode(actual)));
+                }
+            }
+            ShavsVector::MonteCarlo { seed, checkpoints } => {
+                let mut seed_bytes = [0u8; DIGEST_SIZE];
+                seed_bytes.copy_from_slice(seed);
+
+                for (count, (expected, actual)) in checkpoints.iter().zip(monte_carlo(&seed_bytes, checkpoints.len())).enumerate() {
+                    if expected[..] == actual[..] {
+                        report.passed += 1;
+                    } else {
+                        report.failed.push(format!("Monte Carlo COUNT = {}: expected {}, got {}", count, hex::encode(expected), hex::encode(actual)));
+                    }
+                }
+            }
+        }
+    }
+
+    report
+}
+
+pub fn run_rsp_file(path: &Path) -> Result<ShavsReport, Error> {
+    let content = fs::read_to_string(path).map_err(|error| Error::io(path, error))?;
+    let vectors = parse_rsp(&content).map_err(|error| Error::InvalidFormat(format!("Can't parse {}: {}", path.display(), error)))?;
+
+    Ok(check_vectors(&vectors))
+}
+
+// All SHA1*.rsp files of the directory, sorted by name
+pub fn rsp_files(directory: &Path) -> Result<Vec<PathBuf>, Error> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(directory).map_err(|error| Error::io(directory, error))? {
+        let path = entry.map_err(|error| Error::io(directory, error))?.path();
+        let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+        if name.starts_with("SHA1") && name.ends_with(".rsp") {
+            files.push(path);
+        }
+    }
+    files.sort();
+
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Small file in the CAVP format: byte and bit-oriented vectors, some of them from SHA1ShortMsg.rsp
+    const SAMPLE_MESSAGES: &str = include_str!("test_data/SHA1Sample.rsp");
+
+    #[test]
+    fn test_sha1_bit_oriented_messages() {
+        // 5 bits '10011' from the bit-oriented SHAVS suite
+        let mut hasher = Sha1Realization32::new();
+        assert_eq!(hasher.update_bits(&[0x98], 5).unwrap().finalize().to_hex(), "29826b003b906e660eff4027ce98af3531ac75ba");
+
+        // Bits after the length are ignored, whole bytes give the usual hash
+        assert_eq!(hasher.update_bits(&[0x9f], 5).unwrap().finalize().to_hex(), "29826b003b906e660eff4027ce98af3531ac75ba");
+        assert_eq!(hasher.update_bits(b"abc", 24).unwrap().finalize().to_hex(), "a9993e364706816aba3e25717850c26c9cd0d89d");
+
+        // The same message split into non byte-aligned parts
+        let message = hex::decode("49b2aec2594bbe3a3b117542d94ac8a0").unwrap();
+        let expected = "c0ab02482446a74cd9a4582c0d120d4489e59da7";
+        assert_eq!(hasher.update_bits(&message, 123).unwrap().finalize().to_hex(), expected);
+
+        let bits: Vec<bool> = message.iter().flat_map(|byte| (0..8).rev().map(move |shift| (byte >> shift) & 1 == 1)).take(123).collect();
+        for split in [1, 3, 11, 64, 100, 122] {
+            // First part bit by bit, the rest through 'update_bits' and 'update_bytes'
+            for bit in bits[..split].iter() {
+                hasher.update_bits(&[if *bit { 0x80 } else { 0 }], 1).unwrap();
+            }
+            let rest_bits = &bits[split..];
+            let rest_bytes: Vec<u8> = rest_bits.chunks(8).map(|chunk| chunk.iter().enumerate().fold(0u8, |byte, (i, bit)| byte | ((*bit as u8) << (7 - i)))).collect();
+            let whole_bytes = rest_bits.len() / 8;
+            hasher.update_bytes(&rest_bytes[..whole_bytes]);
+            hasher.update_bits(&rest_bytes[whole_bytes..], rest_bits.len() % 8).unwrap();
+
+            assert_eq!(hasher.finalize().to_hex(), expected, "split at {}", split);
+        }
+
+        assert!(hasher.update_bits(&[0xff], 9).is_err());
+    }
+
+    #[test]
+    fn test_sha1_export_bit_oriented_state() {
+        let result = Sha1Realization32::new().update_bits(&[0xff], 3).unwrap().export_state();
+        assert_eq!(result, Err(crate::error::Error::UnalignedMessage { trailing_bits: 3 }));
+    }
+
+    #[test]
+    fn test_shavs_sample_file() {
+        let vectors = parse_rsp(SAMPLE_MESSAGES).unwrap();
+        assert_eq!(vectors.len(), 17);
+        assert_eq!(vectors[0], ShavsVector::Message { bit_length: 0, message: vec![0], digest: hex::decode("da39a3ee5e6b4b0d3255bfef95601890afd80709").unwrap() });
+
+        let report = check_vectors(&vectors);
+        assert!(report.is_success(), "{:?}", report.failed);
+        assert_eq!(report.passed, 17);
+
+        // A broken digest is reported
+        let broken = SAMPLE_MESSAGES.replace("MD = 29826b003b906e660eff4027ce98af3531ac75ba", "MD = 29826b003b906e660eff4027ce98af3531ac75bb");
+        let report = check_vectors(&parse_rsp(&broken).unwrap());
+        assert_eq!(report.failed.len(), 1);
+        assert!(report.failed[0].contains("Len = 5"));
+    }
+
+    #[test]
+    fn test_shavs_monte_carlo() {
+        // Seed and the first checkpoints of SHA1Monte.rsp
+        let rsp = "\
+            # CAVS 11.1\n[L = 20]\n\nSeed = dd4df644eaf3d85bace2b21accaa22b28821f5cd\n\n\
+            COUNT = 0\nMD = 11f5c38b4479d4ad55cb69fadf62de0b036d5163\n\n\
+            COUNT = 1\nMD = 5c26de848c21586bec36995809cb02d3677423d9\n\n\
+            COUNT = 2\nMD = 453b5fcf263d01c891d7897d4013990f7c1fb0ab\n";
+
+        let vectors = parse_rsp(rsp).unwrap();
+        assert!(matches!(&vectors[..], [ShavsVector::MonteCarlo { checkpoints, .. }] if checkpoints.len() == 3));
+
+        let report = check_vectors(&vectors);
+        assert!(report.is_success(), "{:?}", report.failed);
+        assert_eq!(report.passed, 3);
+    }
+
+    #[test]
+    fn test_shavs_parse_errors() {
+        assert!(parse_rsp("[L = 32]\nLen = 0\nMsg = 00\nMD = 00").is_err());
+        assert!(parse_rsp("Len = 8\nMD = da39a3ee5e6b4b0d3255bfef95601890afd80709").is_err());
+        assert!(parse_rsp("Len = 16\nMsg = 61\nMD = da39a3ee5e6b4b0d3255bfef95601890afd80709").is_err());
+        assert!(parse_rsp("Len = 8\nMsg = zz\nMD = da39a3ee5e6b4b0d3255bfef95601890afd80709").is_err());
+        assert!(parse_rsp("Len = 8\nMsg = 61\nMD = da39").is_err());
+        assert!(parse_rsp("Seed = dd4df644eaf3d85bace2b21accaa22b28821f5cd\nCOUNT = 1\nMD = da39a3ee5e6b4b0d3255bfef95601890afd80709").is_err());
+        assert!(parse_rsp("something else").is_err());
+
+        // A missing file or directory is reported with its path
+        assert!(matches!(run_rsp_file(Path::new("missing.rsp")), Err(Error::Io { .. })));
+        assert!(matches!(rsp_files(Path::new("missing")), Err(Error::Io { .. })));
+    }
+
+    // Checks every SHA1*.rsp file from CAVP_SHA_DIRECTORY, nothing to do when it isn't set
+    #[test]
+    fn test_shavs_local_files() {
+        dotenv::dotenv().ok();
+        let Ok(directory) = std::env::var(DIRECTORY_VARIABLE) else {
+            println!("{} isn't set, CAVP files are skipped", DIRECTORY_VARIABLE);
+            return;
+        };
+
+        let files = rsp_files(Path::new(&directory)).unwrap();
+        assert!(!files.is_empty(), "No SHA1*.rsp files in {}", directory);
+
+        for file in files {
+            let report = run_rsp_file(&file).unwrap();
+            println!("{}: {} passed", file.display(), report.passed);
+            assert!(report.is_success(), "{}: {:?}", file.display(), report.failed);
+        }
+    }
+}
@@ -0,0 +1,665 @@
+// sha1sum from GNU coreutils on top of Sha1Realization32: the same output format, check mode,
+// messages and exit codes, so the results can be compared with the system tool byte by byte.
+use std::borrow::Cow;
+use std::ffi::{OsStr, OsString};
+use std::fs::File;
+use std::io::{self, Read, Write};
+#[cfg(unix)]
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use crate::sha1::sha1::Sha1Realization32;
+
+const PROGRAM: &str = "sha1sum";
+const DIGEST_HEX_LENGTH: usize = 40;
+const EXIT_SUCCESS: i32 = 0;
+const EXIT_FAILURE: i32 = 1;
+
+const USAGE: &str = "\
+Usage: sha1sum [OPTION]... [FILE]...
+Print or check SHA1 (160-bit) checksums.
+
+With no FILE, or when FILE is -, read standard input.
+  -b, --binary          read in binary mode
+  -c, --check           read checksums from the FILEs and check them
+      --tag             create a BSD-style checksum
+  -t, --text            read in text mode (default)
+
+The following five options are useful only when verifying checksums:
+      --ignore-missing  don't fail or report status for missing files
+      --quiet           don't print OK for each successfully verified file
+      --status          don't output anything, status code shows success
+      --strict          exit non-zero for improperly formatted checksum lines
+  -w, --warn            warn about improperly formatted checksum lines
+
+      --help        display this help and exit
+      --version     output version information and exit
+";
+
+#[derive(Default)]
+struct Options {
+    check: bool,
+    tag: bool,
+    binary: bool,
+    ignore_missing: bool,
+    quiet: bool,
+    status: bool,
+    strict: bool,
+    warn: bool,
+    help: bool,
+    version: bool,
+    files: Vec<PathBuf>,
+}
+
+// Counters of one checksum file in check mode
+#[derive(Default)]
+struct CheckSummary {
+    improperly_formatted: usize,
+    unreadable: usize,
+    mismatched: usize,
+    verified: usize,
+    properly_formatted: bool,
+}
+
+// 'args' - command line arguments without the program name, file names don't have to be UTF-8.
+// Returns the exit code: 0 - all files are hashed or verified, 1 - any error, mismatch or bad usage.
+pub fn run(args: &[OsString], stdin: &mut dyn Read, stdout: &mut dyn Write, stderr: &mut dyn Write) -> i32 {
+    let options = match parse_args(args) {
+        Ok(options) => options,
+        Err(message) => {
+            let _ = writeln!(stderr, "{}: {}\nTry '{} --help' for more information.", PROGRAM, message, PROGRAM);
+            return EXIT_FAILURE;
+        }
+    };
+
+    let result = if options.help {
+        stdout.write_all(USAGE.as_bytes()).map(|_| EXIT_SUCCESS)
+    } else if options.version {
+        writeln!(stdout, "{} ({}) {}", PROGRAM, env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")).map(|_| EXIT_SUCCESS)
+    } else if options.check {
+        check_files(&options, stdin, stdout, stderr)
+    } else {
+        hash_files(&options, stdin, stdout, stderr)
+    };
+
+    match result.and_then(|code| stdout.flush().map(|_| code)) {
+        Ok(code) => code,
+        Err(error) => {
+            let _ = writeln!(stderr, "{}: write error: {}", PROGRAM, error_message(&error));
+            EXIT_FAILURE
+        }
+    }
+}
+
+fn parse_args(args: &[OsString]) -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut only_files = false;
+
+    for arg in args {
+        // Options are ASCII, the lossy text is only for matching them
+        let text = arg.to_string_lossy();
+        if only_files || text == "-" || !text.starts_with('-') {
+            options.files.push(PathBuf::from(arg));
+            continue;
+        }
+
+        match text.as_ref() {
+            "--" => only_files = true,
+            "--binary" => options.binary = true,
+            "--check" => options.check = true,
+            "--tag" => options.tag = true,
+            "--text" => options.binary = false,
+            "--ignore-missing" => options.ignore_missing = true,
+            "--quiet" => options.quiet = true,
+            "--status" => options.status = true,
+            "--strict" => options.strict = true,
+            "--warn" => options.warn = true,
+            "--help" => options.help = true,
+            "--version" => options.version = true,
+            long if long.starts_with("--") => return Err(format!("unrecognized option '{}'", long)),
+            short => {
+                // Short options can be grouped: -cw
+                for flag in short.chars().skip(1) {
+                    match flag {
+                        'b' => options.binary = true,
+                        'c' => options.check = true,
+                        't' => options.binary = false,
+                        'w' => options.warn = true,
+                        other => return Err(format!("invalid option -- '{}'", other)),
+                    }
+                }
+            }
+        }
+    }
+
+    if options.help || options.version {
+        return Ok(options);
+    }
+    if options.tag && options.check {
+        return Err("the --tag option is meaningless when verifying checksums".to_string());
+    }
+    if !options.check {
+        let check_only = [
+            (options.ignore_missing, "--ignore-missing"),
+            (options.quiet, "--quiet"),
+            (options.status, "--status"),
+            (options.strict, "--strict"),
+            (options.warn, "--warn"),
+        ];
+        if let Some((_, name)) = check_only.iter().find(|(is_set, _)| *is_set) {
+            return Err(format!("the {} option is meaningful only when verifying checksums", name));
+        }
+    }
+    if options.files.is_empty() {
+        options.files.push(PathBuf::from("-"));
+    }
+
+    Ok(options)
+}
+
+fn hash_files(options: &Options, stdin: &mut dyn Read, stdout: &mut dyn Write, stderr: &mut dyn Write) -> io::Result<i32> {
+    let mut code = EXIT_SUCCESS;
+
+    for name in options.files.iter() {
+        let hex = match hash_input(name, stdin) {
+            Ok(hex) => hex,
+            Err(error) => {
+                write_error(stderr, &name_bytes(name), &error_message(&error))?;
+                code = EXIT_FAILURE;
+                continue;
+            }
+        };
+
+        // Names with a backslash or a line break are escaped, the line starts with '\' then
+        let (escaped, escaped_name) = escape_file_name(&name_bytes(name));
+        let prefix = if escaped { "\\" } else { "" };
+
+        if options.tag {
+            write!(stdout, "{}SHA1 (", prefix)?;
+            stdout.write_all(&escaped_name)?;
+            writeln!(stdout, ") = {}", hex)?;
+        } else {
+            let mode = if options.binary { '*' } else { ' ' };
+            write!(stdout, "{}{} {}", prefix, hex, mode)?;
+            stdout.write_all(&escaped_name)?;
+            writeln!(stdout)?;
+        }
+    }
+
+    Ok(code)
+}
+
+fn check_files(options: &Options, stdin: &mut dyn Read, stdout: &mut dyn Write, stderr: &mut dyn Write) -> io::Result<i32> {
+    let mut code = EXIT_SUCCESS;
+
+    for check_file in options.files.iter() {
+        let is_stdin = check_file.as_os_str() == "-";
+        let display_name = if is_stdin { Cow::Borrowed(&b"standard input"[..]) } else { name_bytes(check_file) };
+
+        let mut content = Vec::new();
+        let read_result = if is_stdin {
+            stdin.read_to_end(&mut content)
+        } else {
+            File::open(check_file).and_then(|mut file| file.read_to_end(&mut content))
+        };
+        if let Err(error) = read_result {
+            write_error(stderr, &display_name, &error_message(&error))?;
+            code = EXIT_FAILURE;
+            continue;
+        }
+
+        let summary = check_lines(options, &display_name, &content, stdin, stdout, stderr)?;
+
+        if !summary.properly_formatted {
+            write_error(stderr, &display_name, "no properly formatted checksum lines found")?;
+            code = EXIT_FAILURE;
+            continue;
+        }
+
+        if !options.status {
+            if summary.improperly_formatted > 0 {
+                let (verb, noun) = plural(summary.improperly_formatted, "line is", "lines are");
+                writeln!(stderr, "{}: WARNING: {} {} improperly formatted", PROGRAM, verb, noun)?;
+            }
+            if summary.unreadable > 0 {
+                let (count, noun) = plural(summary.unreadable, "listed file", "listed files");
+                writeln!(stderr, "{}: WARNING: {} {} could not be read", PROGRAM, count, noun)?;
+            }
+            if summary.mismatched > 0 {
+                let (count, noun) = plural(summary.mismatched, "computed checksum", "computed checksums");
+                writeln!(stderr, "{}: WARNING: {} {} did NOT match", PROGRAM, count, noun)?;
+            }
+        }
+
+        if options.ignore_missing && summary.verified == 0 {
+            write_error(stderr, &display_name, "no file was verified")?;
+            code = EXIT_FAILURE;
+        }
+
+        if summary.mismatched > 0 || summary.unreadable > 0 || (options.strict && summary.improperly_formatted > 0) {
+            code = EXIT_FAILURE;
+        }
+    }
+
+    Ok(code)
+}
+
+fn check_lines(
+    options: &Options,
+    display_name: &[u8],
+    content: &[u8],
+    stdin: &mut dyn Read,
+    stdout: &mut dyn Write,
+    stderr: &mut dyn Write,
+) -> io::Result<CheckSummary> {
+    let mut summary = CheckSummary::default();
+
+    let content = content.strip_suffix(b"\n").unwrap_or(content);
+    for (line_index, line) in content.split(|byte| *byte == b'\n').enumerate() {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+
+        // Empty lines and comments are skipped
+        if line.is_empty() || line.starts_with(b"#") {
+            continue;
+        }
+
+        let (expected, name) = match parse_check_line(line) {
+            Some(parsed) => parsed,
+            None => {
+                summary.improperly_formatted += 1;
+                if options.warn {
+                    write_error(stderr, display_name, &format!("{}: improperly formatted SHA1 checksum line", line_index + 1))?;
+                }
+                continue;
+            }
+        };
+        summary.properly_formatted = true;
+
+        let (escaped, escaped_name) = escape_status_name(&name);
+
+        let actual = match hash_input(&path_from_bytes(&name), stdin) {
+            Ok(actual) => actual,
+            Err(error) if options.ignore_missing && error.kind() == io::ErrorKind::NotFound => continue,
+            Err(error) => {
+                write_error(stderr, &name, &error_message(&error))?;
+                if !options.status {
+                    write_status(stdout, escaped, &escaped_name, "FAILED open or read")?;
+                }
+                summary.unreadable += 1;
+                continue;
+            }
+        };
+
+        // Like in coreutils, only matching files count as verified for --ignore-missing
+        if actual.eq_ignore_ascii_case(&expected) {
+            summary.verified += 1;
+            if !options.quiet && !options.status {
+                write_status(stdout, escaped, &escaped_name, "OK")?;
+            }
+        } else {
+            summary.mismatched += 1;
+            if !options.status {
+                write_status(stdout, escaped, &escaped_name, "FAILED")?;
+            }
+        }
+    }
+
+    Ok(summary)
+}
+
+// "<hex>  <name>", "<hex> *<name>" or the BSD-style "SHA1 (<name>) = <hex>",
+// a leading '\' means that the name is escaped. The name is kept as bytes, it doesn't have to be UTF-8.
+fn parse_check_line(line: &[u8]) -> Option<(String, Vec<u8>)> {
+    let (escaped, line) = match line.strip_prefix(b"\\") {
+        Some(rest) => (true, rest),
+        None => (false, line),
+    };
+
+    let (hex, name) = if let Some(rest) = line.strip_prefix(b"SHA1 (") {
+        let split = rest.windows(4).rposition(|window| window == b") = ")?;
+        (&rest[split + 4..], &rest[..split])
+    } else {
+        let hex = line.get(..DIGEST_HEX_LENGTH)?;
+        let rest = &line[DIGEST_HEX_LENGTH..];
+        let name = rest.strip_prefix(b"  ").or_else(|| rest.strip_prefix(b" *"))?;
+        (hex, name)
+    };
+
+    if hex.len() != DIGEST_HEX_LENGTH || !hex.iter().all(u8::is_ascii_hexdigit) || name.is_empty() {
+        return None;
+    }
+
+    let name = if escaped { unescape_file_name(name)? } else { name.to_vec() };
+
+    Some((String::from_utf8_lossy(hex).into_owned(), name))
+}
+
+fn hash_input(name: &Path, stdin: &mut dyn Read) -> io::Result<String> {
+    let mut hasher = Sha1Realization32::new();
+    if name.as_os_str() == "-" {
+        hasher.update_reader(stdin)?;
+    } else {
+        hasher.update_reader(File::open(name)?)?;
+    }
+
+    Ok(hasher.finalize().to_hex())
+}
+
+// File name as it is written: the raw bytes on Unix, so names that aren't UTF-8 come out unchanged
+#[cfg(unix)]
+fn name_bytes(name: &Path) -> Cow<'_, [u8]> {
+    Cow::Borrowed(name.as_os_str().as_bytes())
+}
+
+#[cfg(not(unix))]
+fn name_bytes(name: &Path) -> Cow<'_, [u8]> {
+    Cow::Owned(name.to_string_lossy().into_owned().into_bytes())
+}
+
+// File name from a checksum line, the reverse of 'name_bytes'
+#[cfg(unix)]
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    PathBuf::from(OsStr::from_bytes(bytes))
+}
+
+#[cfg(not(unix))]
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    PathBuf::from(OsStr::new(String::from_utf8_lossy(bytes).as_ref()))
+}
+
+// "sha1sum: <name>: <message>"
+fn write_error(stderr: &mut dyn Write, name: &[u8], message: &str) -> io::Result<()> {
+    write!(stderr, "{}: ", PROGRAM)?;
+    stderr.write_all(name)?;
+    writeln!(stderr, ": {}", message)
+}
+
+// "<name>: <status>" line of the check mode, the line starts with '\' for an escaped name
+fn write_status(stdout: &mut dyn Write, escaped: bool, name: &[u8], status: &str) -> io::Result<()> {
+    if escaped {
+        stdout.write_all(b"\\")?;
+    }
+    stdout.write_all(name)?;
+    writeln!(stdout, ": {}", status)
+}
+
+// Status lines of the check mode escape a name only when it has a line break, a lone backslash
+// is printed as it is (coreutils 9.1 does the same)
+fn escape_status_name(name: &[u8]) -> (bool, Vec<u8>) {
+    if name.iter().any(|byte| matches!(byte, b'\n' | b'\r')) {
+        escape_file_name(name)
+    } else {
+        (false, name.to_vec())
+    }
+}
+
+// Returns whether the name has to be escaped and the escaped name
+fn escape_file_name(name: &[u8]) -> (bool, Vec<u8>) {
+    if !name.iter().any(|byte| matches!(byte, b'\\' | b'\n' | b'\r')) {
+        return (false, name.to_vec());
+    }
+
+    let mut escaped = Vec::with_capacity(name.len() + 2);
+    for byte in name {
+        match byte {
+            b'\\' => escaped.extend_from_slice(b"\\\\"),
+            b'\n' => escaped.extend_from_slice(b"\\n"),
+            b'\r' => escaped.extend_from_slice(b"\\r"),
+            _ => escaped.push(*byte),
+        }
+    }
+    (true, escaped)
+}
+
+fn unescape_file_name(name: &[u8]) -> Option<Vec<u8>> {
+    let mut unescaped = Vec::with_capacity(name.len());
+    let mut bytes = name.iter();
+
+    while let Some(byte) = bytes.next() {
+        if *byte != b'\\' {
+            unescaped.push(*byte);
+            continue;
+        }
+
+        match bytes.next()? {
+            b'\\' => unescaped.push(b'\\'),
+            b'n' => unescaped.push(b'\n'),
+            b'r' => unescaped.push(b'\r'),
+            _ => return None,
+        }
+    }
+
+    Some(unescaped)
+}
+
+// Error text without the " (os error N)" tail added by Rust, as printed by coreutils
+fn error_message(error: &io::Error) -> String {
+    let message = error.to_string();
+    match message.find(" (os error") {
+        Some(position) => message[..position].to_string(),
+        None => message,
+    }
+}
+
+fn plural<'a>(count: usize, singular: &'a str, plural: &'a str) -> (usize, &'a str) {
+    if count == 1 {
+        (count, singular)
+    } else {
+        (count, plural)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    // Runs the tool with the given stdin, returns the exit code, stdout and stderr
+    fn run_sha1sum(args: &[&str], input: &[u8]) -> (i32, String, String) {
+        let args: Vec<OsString> = args.iter().map(OsString::from).collect();
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+
+        let code = run(&args, &mut &input[..], &mut stdout, &mut stderr);
+        (code, String::from_utf8(stdout).unwrap(), String::from_utf8(stderr).unwrap())
+    }
+
+    // Fresh directory with the test files, removed by the test
+    fn test_directory(test_name: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("sha1sum_{}_{}", test_name, std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+
+        fs::write(directory.join("hello.txt"), "Hello").unwrap();
+        fs::write(directory.join("empty.txt"), "").unwrap();
+        fs::write(directory.join("big.bin"), vec![0x61u8; 100_000]).unwrap();
+
+        directory
+    }
+
+    fn path(directory: &Path, name: &str) -> String {
+        directory.join(name).to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_sha1sum_hash_files_and_stdin() {
+        let directory = test_directory("hash");
+        let hello = path(&directory, "hello.txt");
+        let empty = path(&directory, "empty.txt");
+        let big = path(&directory, "big.bin");
+
+        let (code, stdout, stderr) = run_sha1sum(&[&hello, &empty, &big], b"");
+        assert_eq!(code, 0);
+        assert_eq!(stderr, "");
+        assert_eq!(
+            stdout,
+            format!(
+                "f7ff9e8b7bb2e09b70935a5d785e0cc5d9d0abf0  {}\nda39a3ee5e6b4b0d3255bfef95601890afd80709  {}\n\
+                 c4d4b30851182fc4eb8675494d42fd7f17e29c93  {}\n",
+                hello, empty, big
+            )
+        );
+
+        // No files and '-' read stdin
+        let (code, stdout, _) = run_sha1sum(&[], b"Hello");
+        assert_eq!((code, stdout.as_str()), (0, "f7ff9e8b7bb2e09b70935a5d785e0cc5d9d0abf0  -\n"));
+        let (_, stdout, _) = run_sha1sum(&["-b", "-"], b"");
+        assert_eq!(stdout, "da39a3ee5e6b4b0d3255bfef95601890afd80709 *-\n");
+
+        // BSD-style output
+        let (code, stdout, _) = run_sha1sum(&["--tag", &hello], b"");
+        assert_eq!((code, stdout), (0, format!("SHA1 ({}) = f7ff9e8b7bb2e09b70935a5d785e0cc5d9d0abf0\n", hello)));
+
+        // Missing file is reported, other files are still hashed
+        let missing = path(&directory, "missing.txt");
+        let (code, stdout, stderr) = run_sha1sum(&[&missing, &hello], b"");
+        assert_eq!(code, 1);
+        assert_eq!(stdout, format!("f7ff9e8b7bb2e09b70935a5d785e0cc5d9d0abf0  {}\n", hello));
+        assert_eq!(stderr, format!("sha1sum: {}: No such file or directory\n", missing));
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_sha1sum_escaped_names() {
+        let directory = test_directory("escaped");
+        let name = path(&directory, "new\nline\\x");
+        fs::write(&name, "Hello").unwrap();
+
+        let (code, stdout, _) = run_sha1sum(&[&name], b"");
+        let escaped_name = path(&directory, "new\\nline\\\\x");
+        assert_eq!(code, 0);
+        assert_eq!(stdout, format!("\\f7ff9e8b7bb2e09b70935a5d785e0cc5d9d0abf0  {}\n", escaped_name));
+
+        // Escaped line is read back by the check mode
+        let (code, check_stdout, _) = run_sha1sum(&["-c"], stdout.as_bytes());
+        assert_eq!((code, check_stdout), (0, format!("\\{}: OK\n", escaped_name)));
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    // A name that isn't UTF-8 is written as raw bytes and found again by the check mode
+    #[cfg(unix)]
+    #[test]
+    fn test_sha1sum_non_utf8_names() {
+        use std::os::unix::ffi::OsStringExt;
+
+        let directory = test_directory("non_utf8");
+        let mut name = directory.join("caf").into_os_string().into_vec();
+        name.push(0xe9);
+        let name = OsString::from_vec(name);
+        fs::write(&name, "Hello").unwrap();
+
+        let (mut stdout, mut stderr) = (Vec::new(), Vec::new());
+        assert_eq!(run(std::slice::from_ref(&name), &mut &b""[..], &mut stdout, &mut stderr), 0);
+        assert_eq!(stdout, [b"f7ff9e8b7bb2e09b70935a5d785e0cc5d9d0abf0  ", name.as_bytes(), b"\n"].concat());
+
+        let mut check_stdout = Vec::new();
+        assert_eq!(run(&[OsString::from("-c")], &mut stdout.as_slice(), &mut check_stdout, &mut stderr), 0);
+        assert_eq!(check_stdout, [name.as_bytes(), b": OK\n"].concat());
+        assert!(stderr.is_empty());
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_sha1sum_check() {
+        let directory = test_directory("check");
+        let hello = path(&directory, "hello.txt");
+        let empty = path(&directory, "empty.txt");
+        let missing = path(&directory, "missing.txt");
+
+        // Output of the hash mode in both formats is accepted
+        let (_, gnu_list, _) = run_sha1sum(&[&hello, &empty], b"");
+        let (_, bsd_list, _) = run_sha1sum(&["--tag", &hello, &empty], b"");
+        for list in [gnu_list, bsd_list.to_uppercase().replace(&hello.to_uppercase(), &hello).replace(&empty.to_uppercase(), &empty)] {
+            let (code, stdout, stderr) = run_sha1sum(&["--check"], list.as_bytes());
+            assert_eq!(code, 0);
+            assert_eq!(stdout, format!("{}: OK\n{}: OK\n", hello, empty));
+            assert_eq!(stderr, "");
+        }
+
+        // Mismatch, missing file and a bad line
+        let list = format!(
+            "# comment\n0000000000000000000000000000000000000000  {}\nda39a3ee5e6b4b0d3255bfef95601890afd80709 *{}\n\
+             da39a3ee5e6b4b0d3255bfef95601890afd80709  {}\nnot a checksum line\r\n",
+            hello, empty, missing
+        );
+        let (code, stdout, stderr) = run_sha1sum(&["-c", "-w"], list.as_bytes());
+        assert_eq!(code, 1);
+        assert_eq!(stdout, format!("{}: FAILED\n{}: OK\n{}: FAILED open or read\n", hello, empty, missing));
+        assert_eq!(
+            stderr,
+            format!(
+                "sha1sum: {}: No such file or directory\nsha1sum: standard input: 5: improperly formatted SHA1 checksum line\n\
+                 sha1sum: WARNING: 1 line is improperly formatted\nsha1sum: WARNING: 1 listed file could not be read\n\
+                 sha1sum: WARNING: 1 computed checksum did NOT match\n",
+                missing
+            )
+        );
+
+        // --quiet prints only failures, --status prints nothing
+        let (code, stdout, _) = run_sha1sum(&["-c", "--quiet"], list.as_bytes());
+        assert_eq!((code, stdout), (1, format!("{}: FAILED\n{}: FAILED open or read\n", hello, missing)));
+        let (code, stdout, stderr) = run_sha1sum(&["-c", "--status"], list.as_bytes());
+        assert_eq!((code, stdout.as_str(), stderr), (1, "", format!("sha1sum: {}: No such file or directory\n", missing)));
+
+        // Missing files can be ignored, bad lines fail only with --strict
+        let list = format!("da39a3ee5e6b4b0d3255bfef95601890afd80709  {}\nda39a3ee5e6b4b0d3255bfef95601890afd80709  {}\nbad\n", empty, missing);
+        let (code, stdout, _) = run_sha1sum(&["-c", "--ignore-missing"], list.as_bytes());
+        assert_eq!((code, stdout), (0, format!("{}: OK\n", empty)));
+        let (code, _, _) = run_sha1sum(&["-c", "--ignore-missing", "--strict"], list.as_bytes());
+        assert_eq!(code, 1);
+
+        let list = format!("da39a3ee5e6b4b0d3255bfef95601890afd80709  {}\n", missing);
+        let (code, _, stderr) = run_sha1sum(&["-c", "--ignore-missing"], list.as_bytes());
+        assert_eq!((code, stderr.as_str()), (1, "sha1sum: standard input: no file was verified\n"));
+
+        // A mismatching file isn't verified either
+        let list = format!("0000000000000000000000000000000000000000  {}\nda39a3ee5e6b4b0d3255bfef95601890afd80709  {}\n", hello, missing);
+        let (code, stdout, stderr) = run_sha1sum(&["-c", "--ignore-missing"], list.as_bytes());
+        assert_eq!((code, stdout), (1, format!("{}: FAILED\n", hello)));
+        assert_eq!(stderr, "sha1sum: WARNING: 1 computed checksum did NOT match\nsha1sum: standard input: no file was verified\n");
+
+        // The list escapes a backslash, the status line doesn't
+        let backslash = path(&directory, "back\\slash");
+        fs::write(&backslash, "Hello").unwrap();
+        let (_, list, _) = run_sha1sum(&[&backslash], b"");
+        assert_eq!(list, format!("\\f7ff9e8b7bb2e09b70935a5d785e0cc5d9d0abf0  {}\n", path(&directory, "back\\\\slash")));
+        let (code, stdout, _) = run_sha1sum(&["-c"], list.as_bytes());
+        assert_eq!((code, stdout), (0, format!("{}: OK\n", backslash)));
+
+        // Checksum list from a file without any valid line
+        let list_file = path(&directory, "list.sha1");
+        fs::write(&list_file, "nothing here\n").unwrap();
+        let (code, _, stderr) = run_sha1sum(&["-c", &list_file], b"");
+        assert_eq!(code, 1);
+        assert_eq!(stderr, format!("sha1sum: {}: no properly formatted checksum lines found\n", list_file));
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_sha1sum_usage_errors() {
+        let (code, _, stderr) = run_sha1sum(&["--bogus"], b"");
+        assert_eq!((code, stderr.as_str()), (1, "sha1sum: unrecognized option '--bogus'\nTry 'sha1sum --help' for more information.\n"));
+
+        let (code, _, stderr) = run_sha1sum(&["-x"], b"");
+        assert_eq!((code, stderr.as_str()), (1, "sha1sum: invalid option -- 'x'\nTry 'sha1sum --help' for more information.\n"));
+
+        let (code, _, stderr) = run_sha1sum(&["-c", "--tag"], b"");
+        assert_eq!(code, 1);
+        assert!(stderr.starts_with("sha1sum: the --tag option is meaningless when verifying checksums\n"));
+
+        let (code, _, stderr) = run_sha1sum(&["--quiet"], b"");
+        assert_eq!(code, 1);
+        assert!(stderr.starts_with("sha1sum: the --quiet option is meaningful only when verifying checksums\n"));
+
+        let (code, stdout, _) = run_sha1sum(&["--help"], b"");
+        assert_eq!(code, 0);
+        assert!(stdout.starts_with("Usage: sha1sum [OPTION]... [FILE]..."));
+
+        // Everything after '--' is a file name
+        let (code, _, stderr) = run_sha1sum(&["--", "--help"], b"");
+        assert_eq!((code, stderr.as_str()), (1, "sha1sum: --help: No such file or directory\n"));
+    }
+}
@@ -0,0 +1,522 @@
+use std::io::{self, Read};
+use std::sync::OnceLock;
+use digest::{FixedOutput, FixedOutputReset, HashMarker, Output, OutputSizeUser, Reset, Update};
+use digest::core_api::BlockSizeUser;
+use digest::typenum::{U28, U32, U48, U64, U128};
+use crate::error::Error;
+use crate::md::block_buffer::{read_chunks, BlockBuffer};
+
+// Size of one SHA-224/SHA-256 block in bytes (512 bits)
+const BLOCK_SIZE_256: usize = 64;
+
+// Size of one SHA-384/SHA-512 block in bytes (1024 bits)
+const BLOCK_SIZE_512: usize = 128;
+
+// SHA-512/t initial values by digest size in bytes, computed once on the first use
+static SHA512_T_INITIAL_STATES: [OnceLock<[u64; 8]>; 64] = [const { OnceLock::new() }; 64];
+
+// First 32 bits of the fractional parts of the cube roots of the first 64 primes
+const K256: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+// First 64 bits of the fractional parts of the cube roots of the first 80 primes
+const K512: [u64; 80] = [
+    0x428a2f98d728ae22, 0x7137449123ef65cd, 0xb5c0fbcfec4d3b2f, 0xe9b5dba58189dbbc,
+    0x3956c25bf348b538, 0x59f111f1b605d019, 0x923f82a4af194f9b, 0xab1c5ed5da6d8118,
+    0xd807aa98a3030242, 0x12835b0145706fbe, 0x243185be4ee4b28c, 0x550c7dc3d5ffb4e2,
+    0x72be5d74f27b896f, 0x80deb1fe3b1696b1, 0x9bdc06a725c71235, 0xc19bf174cf692694,
+    0xe49b69c19ef14ad2, 0xefbe4786384f25e3, 0x0fc19dc68b8cd5b5, 0x240ca1cc77ac9c65,
+    0x2de92c6f592b0275, 0x4a7484aa6ea6e483, 0x5cb0a9dcbd41fbd4, 0x76f988da831153b5,
+    0x983e5152ee66dfab, 0xa831c66d2db43210, 0xb00327c898fb213f, 0xbf597fc7beef0ee4,
+    0xc6e00bf33da88fc2, 0xd5a79147930aa725, 0x06ca6351e003826f, 0x142929670a0e6e70,
+    0x27b70a8546d22ffc, 0x2e1b21385c26c926, 0x4d2c6dfc5ac42aed, 0x53380d139d95b3df,
+    0x650a73548baf63de, 0x766a0abb3c77b2a8, 0x81c2c92e47edaee6, 0x92722c851482353b,
+    0xa2bfe8a14cf10364, 0xa81a664bbc423001, 0xc24b8b70d0f89791, 0xc76c51a30654be30,
+    0xd192e819d6ef5218, 0xd69906245565a910, 0xf40e35855771202a, 0x106aa07032bbd1b8,
+    0x19a4c116b8d2d0c8, 0x1e376c085141ab53, 0x2748774cdf8eeb99, 0x34b0bcb5e19b48a8,
+    0x391c0cb3c5c95a63, 0x4ed8aa4ae3418acb, 0x5b9cca4f7763e373, 0x682e6ff3d6b2b8a3,
+    0x748f82ee5defb2fc, 0x78a5636f43172f60, 0x84c87814a1f0ab72, 0x8cc702081a6439ec,
+    0x90befffa23631e28, 0xa4506cebde82bde9, 0xbef9a3f7b2c67915, 0xc67178f2e372532b,
+    0xca273eceea26619c, 0xd186b8c721c0c207, 0xeada7dd6cde0eb1e, 0xf57d4f7fee6ed178,
+    0x06f067aa72176fba, 0x0a637dc5a2c898a6, 0x113f9804bef90dae, 0x1b710b35131c471b,
+    0x28db77f523047d84, 0x32caab7b40c72493, 0x3c9ebe0a15c9bebc, 0x431d67c49c100d4c,
+    0x4cc5d4becb3e42b6, 0x597f299cfc657e2a, 0x5fcb6fab3ad6faec, 0x6c44198c4a475817,
+];
+
+const INITIAL_STATE_224: [u32; 8] = [
+    0xc1059ed8, 0x367cd507, 0x3070dd17, 0xf70e5939,
+    0xffc00b31, 0x68581511, 0x64f98fa7, 0xbefa4fa4,
+];
+
+const INITIAL_STATE_256: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a,
+    0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+const INITIAL_STATE_384: [u64; 8] = [
+    0xcbbb9d5dc1059ed8, 0x629a292a367cd507, 0x9159015a3070dd17, 0x152fecd8f70e5939,
+    0x67332667ffc00b31, 0x8eb44a8768581511, 0xdb0c2e0d64f98fa7, 0x47b5481dbefa4fa4,
+];
+
+const INITIAL_STATE_512: [u64; 8] = [
+    0x6a09e667f3bcc908, 0xbb67ae8584caa73b, 0x3c6ef372fe94f82b, 0xa54ff53a5f1d36f1,
+    0x510e527fade682d1, 0x9b05688c2b3e6c1f, 0x1f83d9abfb41bd6b, 0x5be0cd19137e2179,
+];
+
+// FIPS 180-4 5.3.6.1, can be reproduced with 'sha512_t_initial_state(224)'
+const INITIAL_STATE_512_224: [u64; 8] = [
+    0x8c3d37c819544da2, 0x73e1996689dcd4d6, 0x1dfab7ae32ff9c82, 0x679dd514582f9fcf,
+    0x0f6d2b697bd44da8, 0x77e36f7304c48942, 0x3f9d85a86a1d36c8, 0x1112e6ad91d692a1,
+];
+
+// FIPS 180-4 5.3.6.2, can be reproduced with 'sha512_t_initial_state(256)'
+const INITIAL_STATE_512_256: [u64; 8] = [
+    0x22312194fc2bf72c, 0x9f555fa3c84c64c2, 0x2393b86b6f53b151, 0x963877195940eabd,
+    0x96283ee2a88effe3, 0xbe5e1e2553863992, 0x2b0199fc2c85b8aa, 0x0eb72ddc81c52ca2,
+];
+
+// SHA-224 and SHA-256, 'OUTPUT_SIZE' is the digest size in bytes
+#[derive(Clone)]
+pub struct Sha256Family<const OUTPUT_SIZE: usize> {
+    state: [u32; 8],
+    buffer: BlockBuffer<BLOCK_SIZE_256>,
+    hash: [u8; OUTPUT_SIZE],
+}
+
+pub type Sha224Realization32 = Sha256Family<28>;
+pub type Sha256Realization32 = Sha256Family<32>;
+
+// SHA-384, SHA-512 and SHA-512/t (t = 8 * 'OUTPUT_SIZE'), 'OUTPUT_SIZE' is the digest size in bytes
+#[derive(Clone)]
+pub struct Sha512Family<const OUTPUT_SIZE: usize> {
+    state: [u64; 8],
+    buffer: BlockBuffer<BLOCK_SIZE_512>,
+    hash: [u8; OUTPUT_SIZE],
+}
+
+pub type Sha384Realization64 = Sha512Family<48>;
+pub type Sha512Realization64 = Sha512Family<64>;
+pub type Sha512_224Realization64 = Sha512Family<28>;
+pub type Sha512_256Realization64 = Sha512Family<32>;
+
+impl<const OUTPUT_SIZE: usize> Default for Sha256Family<OUTPUT_SIZE> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const OUTPUT_SIZE: usize> Sha256Family<OUTPUT_SIZE> {
+    pub fn new() -> Self {
+        const { assert!(OUTPUT_SIZE == 28 || OUTPUT_SIZE == 32, "SHA-256 family has only 28 (SHA-224) and 32 (SHA-256) byte digests") };
+        let state = if OUTPUT_SIZE == 28 { INITIAL_STATE_224 } else { INITIAL_STATE_256 };
+
+        Sha256Family { state, buffer: BlockBuffer::new(), hash: [0; OUTPUT_SIZE] }
+    }
+
+    pub fn update(&mut self, input: &str) -> &mut Self {
+        self.update_bytes(input.as_bytes())
+    }
+
+    pub fn update_bytes(&mut self, input: &[u8]) -> &mut Self {
+        let state = &mut self.state;
+        self.buffer.update(input, |block| compress_256(state, block));
+        self
+    }
+
+    pub fn update_reader<R: Read>(&mut self, reader: R) -> io::Result<&mut Self> {
+        read_chunks(reader, |chunk| {
+            self.update_bytes(chunk);
+        })?;
+        Ok(self)
+    }
+
+    // Pads the buffered tail with the 64-bit length and stores the digest (truncated for SHA-224).
+    // After that the hasher is reset and ready for a new message.
+    pub fn finalize(&mut self) -> &mut Self {
+        let state = &mut self.state;
+        self.buffer.finalize(8, |block| compress_256(state, block));
+
+        let bytes: Vec<u8> = self.state.iter().flat_map(|h| h.to_be_bytes()).collect();
+        let mut hash = [0u8; OUTPUT_SIZE];
+        hash.copy_from_slice(&bytes[..OUTPUT_SIZE]);
+
+        *self = Sha256Family { hash, ..Sha256Family::new() };
+        self
+    }
+
+    // One-shot digest of everything passed to 'update' so far, the hasher itself isn't changed
+    pub fn hash(&self) -> Self {
+        let mut hasher = self.clone();
+        hasher.finalize();
+        hasher
+    }
+
+    pub fn digest(&self) -> [u8; OUTPUT_SIZE] {
+        self.hash
+    }
+
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.hash)
+    }
+}
+
+impl<const OUTPUT_SIZE: usize> Default for Sha512Family<OUTPUT_SIZE> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const OUTPUT_SIZE: usize> Sha512Family<OUTPUT_SIZE> {
+    pub fn new() -> Self {
+        const { assert!(OUTPUT_SIZE >= 1 && OUTPUT_SIZE <= 64, "SHA-512 family digest must be from 1 to 64 bytes") };
+        let state = match OUTPUT_SIZE {
+            28 => INITIAL_STATE_512_224,
+            32 => INITIAL_STATE_512_256,
+            48 => INITIAL_STATE_384,
+            64 => INITIAL_STATE_512,
+            _ => *SHA512_T_INITIAL_STATES[OUTPUT_SIZE].get_or_init(|| generate_sha512_t_state(OUTPUT_SIZE * 8)),
+        };
+
+        Sha512Family { state, buffer: BlockBuffer::new(), hash: [0; OUTPUT_SIZE] }
+    }
+
+    pub fn update(&mut self, input: &str) -> &mut Self {
+        self.update_bytes(input.as_bytes())
+    }
+
+    pub fn update_bytes(&mut self, input: &[u8]) -> &mut Self {
+        let state = &mut self.state;
+        self.buffer.update(input, |block| compress_512(state, block));
+        self
+    }
+
+    pub fn update_reader<R: Read>(&mut self, reader: R) -> io::Result<&mut Self> {
+        read_chunks(reader, |chunk| {
+            self.update_bytes(chunk);
+        })?;
+        Ok(self)
+    }
+
+    // Pads the buffered tail with the 128-bit length and stores the digest (truncated for SHA-384 and SHA-512/t).
+    // After that the hasher is reset and ready for a new message.
+    pub fn finalize(&mut self) -> &mut Self {
+        let state = &mut self.state;
+        self.buffer.finalize(16, |block| compress_512(state, block));
+
+        let bytes: Vec<u8> = self.state.iter().flat_map(|h| h.to_be_bytes()).collect();
+        let mut hash = [0u8; OUTPUT_SIZE];
+        hash.copy_from_slice(&bytes[..OUTPUT_SIZE]);
+
+        *self = Sha512Family { hash, ..Sha512Family::new() };
+        self
+    }
+
+    // One-shot digest of everything passed to 'update' so far, the hasher itself isn't changed
+    pub fn hash(&self) -> Self {
+        let mut hasher = self.clone();
+        hasher.finalize();
+        hasher
+    }
+
+    pub fn digest(&self) -> [u8; OUTPUT_SIZE] {
+        self.hash
+    }
+
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.hash)
+    }
+}
+
+// SHA-512/t IV generation function (FIPS 180-4 5.3.6): SHA-512 with the initial values XOR 0xa5a5a5a5a5a5a5a5
+// applied to the string "SHA-512/t", where t is the digest length in bits
+pub fn sha512_t_initial_state(t: usize) -> Result<[u64; 8], Error> {
+    if t == 0 || t >= 512 || t == 384 {
+        return Err(Error::InvalidArgument(format!("SHA-512/t isn't defined for t = {}", t)));
+    }
+
+    Ok(generate_sha512_t_state(t))
+}
+
+// 't' is already checked, 'Sha512Family::new' passes only the sizes allowed at compile time
+fn generate_sha512_t_state(t: usize) -> [u64; 8] {
+    let mut state = INITIAL_STATE_512.map(|h| h ^ 0xa5a5a5a5a5a5a5a5);
+    let mut buffer: BlockBuffer<BLOCK_SIZE_512> = BlockBuffer::new();
+    buffer.update(format!("SHA-512/{}", t).as_bytes(), |block| compress_512(&mut state, block));
+    buffer.finalize(16, |block| compress_512(&mut state, block));
+
+    state
+}
+
+// Processes a single 512-bit block of SHA-224/SHA-256
+fn compress_256(state: &mut [u32; 8], block: &[u8; BLOCK_SIZE_256]) {
+    let mut words: [u32; 64] = [0; 64];
+
+    for (word, chunk_bytes) in words.iter_mut().zip(block.chunks_exact(4)) {
+        *word = u32::from_be_bytes([chunk_bytes[0], chunk_bytes[1], chunk_bytes[2], chunk_bytes[3]]);
+    }
+
+    // Message schedule
+    for i in 16..64 {
+        let s0 = words[i - 15].rotate_right(7) ^ words[i - 15].rotate_right(18) ^ (words[i - 15] >> 3);
+        let s1 = words[i - 2].rotate_right(17) ^ words[i - 2].rotate_right(19) ^ (words[i - 2] >> 10);
+        words[i] = words[i - 16].wrapping_add(s0).wrapping_add(words[i - 7]).wrapping_add(s1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+
+    // Main loop:
+    for (word, k) in words.iter().zip(K256.iter()) {
+        let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+        let ch = (e & f) ^ (!e & g);
+        let temp1 = h.wrapping_add(s1).wrapping_add(ch).wrapping_add(*k).wrapping_add(*word);
+        let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let temp2 = s0.wrapping_add(maj);
+
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(temp1);
+        d = c;
+        c = b;
+        b = a;
+        a = temp1.wrapping_add(temp2);
+    }
+
+    for (h, value) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+        *h = h.wrapping_add(value);
+    }
+}
+
+// Processes a single 1024-bit block of SHA-384/SHA-512
+fn compress_512(state: &mut [u64; 8], block: &[u8; BLOCK_SIZE_512]) {
+    let mut words: [u64; 80] = [0; 80];
+
+    for (word, chunk_bytes) in words.iter_mut().zip(block.chunks_exact(8)) {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(chunk_bytes);
+        *word = u64::from_be_bytes(bytes);
+    }
+
+    // Message schedule
+    for i in 16..80 {
+        let s0 = words[i - 15].rotate_right(1) ^ words[i - 15].rotate_right(8) ^ (words[i - 15] >> 7);
+        let s1 = words[i - 2].rotate_right(19) ^ words[i - 2].rotate_right(61) ^ (words[i - 2] >> 6);
+        words[i] = words[i - 16].wrapping_add(s0).wrapping_add(words[i - 7]).wrapping_add(s1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+
+    // Main loop:
+    for (word, k) in words.iter().zip(K512.iter()) {
+        let s1 = e.rotate_right(14) ^ e.rotate_right(18) ^ e.rotate_right(41);
+        let ch = (e & f) ^ (!e & g);
+        let temp1 = h.wrapping_add(s1).wrapping_add(ch).wrapping_add(*k).wrapping_add(*word);
+        let s0 = a.rotate_right(28) ^ a.rotate_right(34) ^ a.rotate_right(39);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let temp2 = s0.wrapping_add(maj);
+
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(temp1);
+        d = c;
+        c = b;
+        b = a;
+        a = temp1.wrapping_add(temp2);
+    }
+
+    for (h, value) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+        *h = h.wrapping_add(value);
+    }
+}
+
+// RustCrypto 'digest' traits, implemented for the named variants
+impl<const OUTPUT_SIZE: usize> HashMarker for Sha256Family<OUTPUT_SIZE> {}
+impl<const OUTPUT_SIZE: usize> HashMarker for Sha512Family<OUTPUT_SIZE> {}
+
+impl<const OUTPUT_SIZE: usize> BlockSizeUser for Sha256Family<OUTPUT_SIZE> {
+    type BlockSize = U64;
+}
+
+impl<const OUTPUT_SIZE: usize> BlockSizeUser for Sha512Family<OUTPUT_SIZE> {
+    type BlockSize = U128;
+}
+
+impl OutputSizeUser for Sha224Realization32 {
+    type OutputSize = U28;
+}
+
+impl OutputSizeUser for Sha256Realization32 {
+    type OutputSize = U32;
+}
+
+impl OutputSizeUser for Sha512_224Realization64 {
+    type OutputSize = U28;
+}
+
+impl OutputSizeUser for Sha512_256Realization64 {
+    type OutputSize = U32;
+}
+
+impl OutputSizeUser for Sha384Realization64 {
+    type OutputSize = U48;
+}
+
+impl OutputSizeUser for Sha512Realization64 {
+    type OutputSize = U64;
+}
+
+impl<const OUTPUT_SIZE: usize> Update for Sha256Family<OUTPUT_SIZE> {
+    fn update(&mut self, data: &[u8]) {
+        self.update_bytes(data);
+    }
+}
+
+impl<const OUTPUT_SIZE: usize> Update for Sha512Family<OUTPUT_SIZE> {
+    fn update(&mut self, data: &[u8]) {
+        self.update_bytes(data);
+    }
+}
+
+impl<const OUTPUT_SIZE: usize> FixedOutput for Sha256Family<OUTPUT_SIZE> where Self: OutputSizeUser {
+    fn finalize_into(mut self, out: &mut Output<Self>) {
+        out.copy_from_slice(&self.finalize().digest());
+    }
+}
+
+impl<const OUTPUT_SIZE: usize> FixedOutput for Sha512Family<OUTPUT_SIZE> where Self: OutputSizeUser {
+    fn finalize_into(mut self, out: &mut Output<Self>) {
+        out.copy_from_slice(&self.finalize().digest());
+    }
+}
+
+impl<const OUTPUT_SIZE: usize> FixedOutputReset for Sha256Family<OUTPUT_SIZE> where Self: OutputSizeUser {
+    fn finalize_into_reset(&mut self, out: &mut Output<Self>) {
+        out.copy_from_slice(&self.finalize().digest());
+    }
+}
+
+impl<const OUTPUT_SIZE: usize> FixedOutputReset for Sha512Family<OUTPUT_SIZE> where Self: OutputSizeUser {
+    fn finalize_into_reset(&mut self, out: &mut Output<Self>) {
+        out.copy_from_slice(&self.finalize().digest());
+    }
+}
+
+impl<const OUTPUT_SIZE: usize> Reset for Sha256Family<OUTPUT_SIZE> {
+    fn reset(&mut self) {
+        *self = Sha256Family::new();
+    }
+}
+
+impl<const OUTPUT_SIZE: usize> Reset for Sha512Family<OUTPUT_SIZE> {
+    fn reset(&mut self) {
+        *self = Sha512Family::new();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    // FIPS 180-4 example messages
+    const ONE_BLOCK_MESSAGE: &str = "abc";
+    const TWO_BLOCK_MESSAGE_256: &str = "abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq";
+    const TWO_BLOCK_MESSAGE_512: &str = "abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu";
+
+    fn reference_hash<D: digest::Digest>(message: &[u8]) -> Vec<u8> {
+        D::digest(message).to_vec()
+    }
+
+    #[test]
+    fn test_sha2_fips_180_4_vectors() {
+        assert_eq!(Sha224Realization32::new().update(ONE_BLOCK_MESSAGE).finalize().to_hex(), "23097d223405d8228642a477bda255b32aadbce4bda0b3f7e36c9da7");
+        assert_eq!(Sha224Realization32::new().update(TWO_BLOCK_MESSAGE_256).finalize().to_hex(), "75388b16512776cc5dba5da1fd890150b0c6455cb4f58b1952522525");
+
+        assert_eq!(Sha256Realization32::new().update(ONE_BLOCK_MESSAGE).finalize().to_hex(), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+        assert_eq!(Sha256Realization32::new().update(TWO_BLOCK_MESSAGE_256).finalize().to_hex(), "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1");
+
+        assert_eq!(
+            Sha384Realization64::new().update(ONE_BLOCK_MESSAGE).finalize().to_hex(),
+            "cb00753f45a35e8bb5a03d699ac65007272c32ab0eded1631a8b605a43ff5bed8086072ba1e7cc2358baeca134c825a7"
+        );
+        assert_eq!(
+            Sha384Realization64::new().update(TWO_BLOCK_MESSAGE_512).finalize().to_hex(),
+            "09330c33f71147e83d192fc782cd1b4753111b173b3b05d22fa08086e3b0f712fcc7c71a557e2db966c3e9fa91746039"
+        );
+
+        assert_eq!(
+            Sha512Realization64::new().update(ONE_BLOCK_MESSAGE).finalize().to_hex(),
+            "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"
+        );
+        assert_eq!(
+            Sha512Realization64::new().update(TWO_BLOCK_MESSAGE_512).finalize().to_hex(),
+            "8e959b75dae313da8cf4f72814fc143f8f7779c6eb9f7fa17299aeadb6889018501d289e4900f7e4331b99dec4b5433ac7d329eeb6dd26545e96e55b874be909"
+        );
+
+        assert_eq!(Sha512_224Realization64::new().update(ONE_BLOCK_MESSAGE).finalize().to_hex(), "4634270f707b6a54daae7530460842e20e37ed265ceee9a43e8924aa");
+        assert_eq!(Sha512_256Realization64::new().update(ONE_BLOCK_MESSAGE).finalize().to_hex(), "53048e2681941ef99b2e29b76b4c7dabe4c2d0c634fc6d46e0e2f13107e7af23");
+
+        // Empty message and one million of 'a'
+        assert_eq!(Sha256Realization32::new().update("").finalize().to_hex(), "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+        let mut sha256 = Sha256Realization32::new();
+        sha256.update_reader(std::io::repeat(b'a').take(1_000_000)).unwrap();
+        assert_eq!(sha256.finalize().to_hex(), "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0");
+    }
+
+    #[test]
+    fn test_sha512_t_initial_state() {
+        assert_eq!(sha512_t_initial_state(224), Ok(INITIAL_STATE_512_224));
+        assert_eq!(sha512_t_initial_state(256), Ok(INITIAL_STATE_512_256));
+
+        // Not named variant gets the generated IV, e.g. SHA-512/160
+        assert_eq!(Sha512Family::<20>::new().state, sha512_t_initial_state(160).unwrap());
+        // It's generated once, a reset after 'finalize' reuses the cached value
+        assert!(SHA512_T_INITIAL_STATES[20].get().is_some());
+        let mut sha512_160 = Sha512Family::<20>::new();
+        assert_eq!(sha512_160.update("abc").finalize().state, sha512_t_initial_state(160).unwrap());
+    }
+
+    #[test]
+    fn test_sha512_t_initial_state_for_384() {
+        // t = 384 is SHA-384 with its own IV, 0 and 512+ aren't truncations
+        for t in [0, 384, 512, 1024] {
+            assert!(matches!(sha512_t_initial_state(t), Err(Error::InvalidArgument(_))), "{}", t);
+        }
+    }
+
+    #[test]
+    fn test_compare_sha2_realization_with_sha2_from_lib() {
+        let mut rng = rand::thread_rng();
+
+        for length in [0, 1, 55, 56, 63, 64, 111, 112, 127, 128, 129, 1000, 5000] {
+            let message: Vec<u8> = (0..length).map(|_| rng.gen()).collect();
+
+            assert_eq!(reference_hash::<Sha224Realization32>(&message), reference_hash::<sha2::Sha224>(&message));
+            assert_eq!(reference_hash::<Sha256Realization32>(&message), reference_hash::<sha2::Sha256>(&message));
+            assert_eq!(reference_hash::<Sha384Realization64>(&message), reference_hash::<sha2::Sha384>(&message));
+            assert_eq!(reference_hash::<Sha512Realization64>(&message), reference_hash::<sha2::Sha512>(&message));
+            assert_eq!(reference_hash::<Sha512_224Realization64>(&message), reference_hash::<sha2::Sha512_224>(&message));
+            assert_eq!(reference_hash::<Sha512_256Realization64>(&message), reference_hash::<sha2::Sha512_256>(&message));
+
+            // Streaming in uneven chunks gives the same result
+            let mut sha256 = Sha256Realization32::new();
+            let mut sha512 = Sha512Realization64::new();
+            for chunk in message.chunks(37) {
+                sha256.update_bytes(chunk);
+                sha512.update_bytes(chunk);
+            }
+            assert_eq!(sha256.finalize().digest().to_vec(), reference_hash::<sha2::Sha256>(&message));
+            assert_eq!(sha512.finalize().digest().to_vec(), reference_hash::<sha2::Sha512>(&message));
+        }
+    }
+}